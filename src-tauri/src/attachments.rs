@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension};
+use std::path::{Path, PathBuf};
+use crate::db::get_database;
+use crate::error::AppError;
+use crate::paths::prompts_dir;
+use crate::prompts::ensure_unlocked;
+use crate::security::validate_uuid;
+
+/// Directory under PromptMaster holding attachment copies, one subdirectory per prompt
+pub const ATTACHMENTS_DIR: &str = "attachments";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Attachment {
+    pub id: i64,
+    pub prompt_uuid: String,
+    pub filename: String,
+    /// Path relative to the PromptMaster directory, e.g. "attachments/<prompt_uuid>/example.pdf"
+    pub relative_path: String,
+    pub created_at: String,
+}
+
+/// Pick a file name in `dir` that doesn't exist yet: "notes.txt", then "notes (1).txt", ...
+fn unique_attachment_name(dir: &Path, filename: &str) -> String {
+    if !dir.join(filename).exists() {
+        return filename.to_string();
+    }
+
+    let path = Path::new(filename);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+
+    (1..)
+        .map(|n| format!("{} ({}){}", stem, n, extension))
+        .find(|candidate| !dir.join(candidate).exists())
+        .expect("unbounded range always yields a free name")
+}
+
+/// Copy a file into PromptMaster/attachments/<prompt_uuid>/ and link it to the prompt.
+/// The prompt must exist and be unlocked, and the source must be a readable file.
+#[tauri::command]
+pub async fn add_attachment(
+    prompt_uuid: String,
+    source_path: String,
+    app_handle: tauri::AppHandle,
+) -> std::result::Result<Attachment, String> {
+    log::info!("Adding attachment {} to prompt {}", source_path, prompt_uuid);
+
+    validate_uuid(&prompt_uuid)?;
+
+    let source = PathBuf::from(source_path.trim());
+    if !source.is_file() {
+        return Err(AppError::InvalidInput(format!("Not a file: {}", source_path)).into());
+    }
+    std::fs::File::open(&source)
+        .map_err(|e| AppError::InvalidInput(format!("Cannot read {}: {}", source_path, e)))?;
+    let original_name = source.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| AppError::InvalidInput(format!("Not a file: {}", source_path)))?;
+
+    let db = get_database()?;
+    db.with_connection(|conn| {
+        conn.query_row("SELECT 1 FROM prompts WHERE uuid = ?1", [&prompt_uuid], |_| Ok(()))
+            .optional()
+    })?.ok_or_else(|| AppError::NotFound(format!("Prompt {}", prompt_uuid)))?;
+
+    let target_dir = prompts_dir(&app_handle)?.join(ATTACHMENTS_DIR).join(&prompt_uuid);
+    std::fs::create_dir_all(&target_dir).map_err(AppError::from)?;
+    let filename = unique_attachment_name(&target_dir, &original_name);
+    let target = target_dir.join(&filename);
+    std::fs::copy(&source, &target).map_err(AppError::from)?;
+
+    let relative_path = format!("{}/{}/{}", ATTACHMENTS_DIR, prompt_uuid, filename);
+    let created_at = Utc::now().to_rfc3339();
+
+    let inserted = db.with_transaction(|tx| {
+        ensure_unlocked(tx, &prompt_uuid)?;
+        tx.execute(
+            "INSERT INTO attachments (prompt_uuid, filename, relative_path, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![&prompt_uuid, &filename, &relative_path, &created_at],
+        )?;
+        Ok(tx.last_insert_rowid())
+    });
+
+    let id = match inserted {
+        Ok(id) => id,
+        Err(e) => {
+            // Don't leave an unreferenced copy behind
+            if let Err(remove_err) = std::fs::remove_file(&target) {
+                log::warn!("Failed to remove attachment copy {}: {}", target.display(), remove_err);
+            }
+            return Err(e.into());
+        }
+    };
+
+    log::info!("Attached {} to prompt {}", relative_path, prompt_uuid);
+    Ok(Attachment {
+        id,
+        prompt_uuid,
+        filename,
+        relative_path,
+        created_at,
+    })
+}
+
+/// List a prompt's attachments, oldest first
+#[tauri::command]
+pub async fn list_attachments(prompt_uuid: String) -> std::result::Result<Vec<Attachment>, String> {
+    validate_uuid(&prompt_uuid)?;
+
+    let db = get_database()?;
+    let attachments = db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, prompt_uuid, filename, relative_path, created_at
+             FROM attachments WHERE prompt_uuid = ?1 ORDER BY created_at, id"
+        )?;
+        let rows = stmt.query_map([&prompt_uuid], |row| Ok(Attachment {
+            id: row.get(0)?,
+            prompt_uuid: row.get(1)?,
+            filename: row.get(2)?,
+            relative_path: row.get(3)?,
+            created_at: row.get(4)?,
+        }))?;
+        rows.collect()
+    })?;
+
+    Ok(attachments)
+}
+
+/// Unlink an attachment and delete its copy from the attachments directory
+#[tauri::command]
+pub async fn remove_attachment(
+    id: i64,
+    app_handle: tauri::AppHandle,
+) -> std::result::Result<(), String> {
+    log::info!("Removing attachment {}", id);
+
+    let db = get_database()?;
+    let relative_path = db.with_transaction(|tx| {
+        let (prompt_uuid, relative_path): (String, String) = tx.query_row(
+            "SELECT prompt_uuid, relative_path FROM attachments WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        ).optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Attachment {}", id)))?;
+
+        ensure_unlocked(tx, &prompt_uuid)?;
+        tx.execute("DELETE FROM attachments WHERE id = ?1", [id])?;
+        Ok(relative_path)
+    })?;
+
+    let path = prompts_dir(&app_handle)?.join(&relative_path);
+    match std::fs::remove_file(&path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            log::warn!("Attachment file already missing: {}", path.display());
+        }
+        Err(e) => return Err(AppError::from(e).into()),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_attachment_name() {
+        let dir = std::env::temp_dir().join(format!("promptmaster-test-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(unique_attachment_name(&dir, "example.pdf"), "example.pdf");
+
+        std::fs::write(dir.join("example.pdf"), "").unwrap();
+        assert_eq!(unique_attachment_name(&dir, "example.pdf"), "example (1).pdf");
+
+        std::fs::write(dir.join("example (1).pdf"), "").unwrap();
+        assert_eq!(unique_attachment_name(&dir, "example.pdf"), "example (2).pdf");
+
+        std::fs::write(dir.join("README"), "").unwrap();
+        assert_eq!(unique_attachment_name(&dir, "README"), "README (1)");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
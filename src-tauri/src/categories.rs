@@ -0,0 +1,632 @@
+use serde::{Deserialize, Serialize};
+use rusqlite::params;
+use std::collections::{BTreeMap, HashMap};
+use crate::db::get_database;
+use crate::error::{AppError, Result};
+use crate::metadata::validate_category_path;
+use crate::settings::{get_bool_setting, NORMALIZE_CATEGORY_PATHS};
+
+/// Category assigned to prompts without one; it has no parent and no children
+pub const UNCATEGORIZED: &str = "Uncategorized";
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Breadcrumb {
+    /// Last segment of `path`, e.g. "ml"
+    pub name: String,
+    /// Full path up to and including this segment, e.g. "work/ml"
+    pub path: String,
+    /// Prompts filed in this category or any of its subcategories
+    pub prompt_count: i64,
+}
+
+/// A category path offered for autocompletion
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CategoryPathCount {
+    pub path: String,
+    /// Prompts filed in this category or any of its subcategories
+    pub prompt_count: i64,
+}
+
+/// Case variants folded into one canonical path by normalize_categories
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CategoryMerge {
+    /// Trimmed, lowercased path the variants now share
+    pub canonical: String,
+    /// Paths that were rewritten to `canonical`, e.g. ["Work/ML", "work/ML "]
+    pub merged: Vec<String>,
+    /// Prompts whose category_path changed
+    pub prompt_count: usize,
+}
+
+/// Result of normalize_categories
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CategoryNormalization {
+    pub merges: Vec<CategoryMerge>,
+    /// Locked prompts left on a non-canonical path
+    pub skipped_locked: Vec<String>,
+}
+
+/// A prompt whose category_path a category move would change
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CategoryChange {
+    pub prompt_uuid: String,
+    pub before: String,
+    pub after: String,
+    pub locked: bool,
+}
+
+/// Result of undo_last_category_operation
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CategoryUndo {
+    /// The operation that was undone, e.g. "move_category"
+    pub operation: String,
+    /// Prompts put back on their previous category_path
+    pub restored: usize,
+    /// Prompts left alone because they were edited, locked or deleted since
+    pub skipped: Vec<String>,
+}
+
+/// Every prompt's current category_path, taken before a bulk category operation
+fn snapshot_category_paths(conn: &rusqlite::Connection) -> rusqlite::Result<HashMap<String, Option<String>>> {
+    let mut stmt = conn.prepare("SELECT uuid, category_path FROM prompts")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// Replace the undo record with the prompts whose category_path differs from `before`.
+/// An operation that changed nothing keeps the previous record.
+fn record_category_undo(
+    conn: &rusqlite::Connection,
+    operation: &str,
+    before: &HashMap<String, Option<String>>,
+) -> rusqlite::Result<usize> {
+    let changed: Vec<(String, Option<String>, Option<String>)> = snapshot_category_paths(conn)?
+        .into_iter()
+        .filter_map(|(uuid, after)| {
+            let previous = before.get(&uuid)?;
+            (*previous != after).then(|| (uuid, previous.clone(), after))
+        })
+        .collect();
+    if changed.is_empty() {
+        return Ok(0);
+    }
+
+    conn.execute("DELETE FROM category_undo", [])?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut insert = conn.prepare(
+        "INSERT INTO category_undo (prompt_uuid, operation, before_path, after_path, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)"
+    )?;
+    for (uuid, before_path, after_path) in &changed {
+        insert.execute(params![uuid, operation, before_path, after_path, now])?;
+    }
+    Ok(changed.len())
+}
+
+/// Put prompts recorded by the last bulk category operation back on their previous paths and
+/// clear the record. Prompts whose path changed since, or that are now locked, are skipped.
+fn undo_category_changes(conn: &rusqlite::Connection) -> Result<CategoryUndo> {
+    let records: Vec<(String, String, Option<String>, Option<String>)> = {
+        let mut stmt = conn.prepare(
+            "SELECT prompt_uuid, operation, before_path, after_path FROM category_undo ORDER BY prompt_uuid"
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    let Some(operation) = records.first().map(|record| record.1.clone()) else {
+        return Err(AppError::NotFound("No category operation to undo".to_string()));
+    };
+
+    let mut restored = 0;
+    let mut skipped = Vec::new();
+    for (uuid, _, before_path, after_path) in records {
+        let changed = conn.execute(
+            "UPDATE prompts SET category_path = ?1, updated_at = datetime('now')
+             WHERE uuid = ?2 AND category_path IS ?3 AND locked = 0",
+            params![before_path, uuid, after_path],
+        )?;
+        if changed == 0 {
+            skipped.push(uuid);
+        } else {
+            restored += 1;
+        }
+    }
+
+    conn.execute("DELETE FROM category_undo", [])?;
+    Ok(CategoryUndo { operation, restored, skipped })
+}
+
+/// Undo the most recent bulk category operation (move_category or normalize_categories) in
+/// one transaction. Only the latest operation is kept, and it can be undone once.
+#[tauri::command]
+pub async fn undo_last_category_operation() -> std::result::Result<CategoryUndo, String> {
+    log::info!("Undoing last category operation");
+
+    let db = get_database()?;
+    let result = db.with_transaction(|tx| undo_category_changes(tx))?;
+
+    log::info!("Undid {}: {} prompts restored, {} skipped",
+               result.operation, result.restored, result.skipped.len());
+    Ok(result)
+}
+
+/// Split "work/ml/vision" into ("work", "work"), ("ml", "work/ml"), ("vision", "work/ml/vision")
+fn breadcrumb_segments(category_path: &str) -> Vec<(String, String)> {
+    let mut segments = Vec::new();
+    let mut path = String::new();
+
+    for name in category_path.split('/') {
+        if !path.is_empty() {
+            path.push('/');
+        }
+        path.push_str(name);
+        segments.push((name.to_string(), path.clone()));
+    }
+
+    segments
+}
+
+/// Count prompts filed under a category path, including its subcategories.
+/// Prompts with no category count as Uncategorized.
+pub fn count_prompts_in_category(conn: &rusqlite::Connection, category_path: &str) -> rusqlite::Result<i64> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM prompts
+         WHERE COALESCE(category_path, ?1) = ?2 OR COALESCE(category_path, ?1) LIKE ?3 ESCAPE '\\'",
+        params![UNCATEGORIZED, category_path, subtree_pattern(category_path)],
+        |row| row.get(0)
+    )
+}
+
+/// Canonical form of a category path: segments trimmed and lowercased, "Work / ML" -> "work/ml".
+/// Any case variant of Uncategorized maps to Uncategorized itself.
+pub fn normalize_category_path(category_path: &str) -> String {
+    let normalized = category_path.trim()
+        .split('/')
+        .map(|segment| segment.trim().to_lowercase())
+        .collect::<Vec<_>>()
+        .join("/");
+    if normalized == UNCATEGORIZED.to_lowercase() {
+        UNCATEGORIZED.to_string()
+    } else {
+        normalized
+    }
+}
+
+/// The category path to store when assigning `category_path`: normalized when the
+/// normalize_category_paths setting is on, otherwise unchanged
+pub fn category_path_for_write(conn: &rusqlite::Connection, category_path: &str) -> rusqlite::Result<String> {
+    if get_bool_setting(conn, NORMALIZE_CATEGORY_PATHS, false)? {
+        Ok(normalize_category_path(category_path))
+    } else {
+        Ok(category_path.to_string())
+    }
+}
+
+/// LIKE pattern matching every subcategory of `category_path`
+pub(crate) fn subtree_pattern(category_path: &str) -> String {
+    let escaped = category_path.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("{}/%", escaped)
+}
+
+/// Where `path` ends up when the subtree at `source` is moved to `target`.
+/// None when `path` is not `source` or one of its descendants.
+fn moved_category_path(path: &str, source: &str, target: &str) -> Option<String> {
+    if path == source {
+        return Some(target.to_string());
+    }
+    path.strip_prefix(source)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .map(|rest| format!("{}/{}", target, rest))
+}
+
+/// Validate a move of `source_path` under `new_parent_path` and return (source, target).
+/// The target keeps the source's name: "work/ml" under "research" is "research/ml",
+/// and an empty parent means the root.
+fn resolve_category_move(source_path: &str, new_parent_path: &str) -> Result<(String, String)> {
+    let source = source_path.trim();
+    let parent = new_parent_path.trim();
+    validate_category_path(source)?;
+    if !parent.is_empty() {
+        validate_category_path(parent)?;
+    }
+
+    if source == UNCATEGORIZED || parent == UNCATEGORIZED {
+        return Err(AppError::Validation("Uncategorized cannot be moved or have subcategories".to_string()));
+    }
+    if moved_category_path(parent, source, source).is_some() {
+        return Err(AppError::Validation(format!("Cannot move '{}' under itself", source)));
+    }
+
+    let name = source.rsplit('/').next().unwrap_or(source);
+    let target = if parent.is_empty() { name.to_string() } else { format!("{}/{}", parent, name) };
+    Ok((source.to_string(), target))
+}
+
+/// Move a category and everything under it to a new parent, keeping its name:
+/// moving "work/ml" under "research" gives "research/ml". An empty parent moves it to the root.
+/// Returns the number of prompts moved.
+#[tauri::command]
+pub async fn move_category(source_path: String, new_parent_path: String) -> std::result::Result<usize, String> {
+    log::info!("Moving category '{}' under '{}'", source_path, new_parent_path);
+
+    let (source, target) = resolve_category_move(&source_path, &new_parent_path)?;
+    if target == source {
+        return Ok(0);
+    }
+
+    let db = get_database()?;
+    let moved = db.with_transaction(|tx| {
+        let target = category_path_for_write(tx, &target)?;
+        let before = snapshot_category_paths(tx)?;
+        let moved = move_category_paths(tx, &source, &target)?;
+        record_category_undo(tx, "move_category", &before)?;
+        Ok(moved)
+    })?;
+
+    log::info!("Moved {} prompts from '{}' to '{}'", moved, source, target);
+    Ok(moved)
+}
+
+/// Show what move_category would change without changing anything: each affected prompt with
+/// its current and resulting category_path. Locked prompts are flagged, since any of them
+/// makes the real move fail.
+#[tauri::command]
+pub async fn preview_move_category(
+    source_path: String,
+    new_parent_path: String,
+) -> std::result::Result<Vec<CategoryChange>, String> {
+    let (source, target) = resolve_category_move(&source_path, &new_parent_path)?;
+    if target == source {
+        return Ok(Vec::new());
+    }
+
+    // Read-only: plain SELECTs on the shared connection, no transaction to commit
+    let db = get_database()?;
+    let changes = db.with_connection(|conn| {
+        let target = category_path_for_write(conn, &target)?;
+        planned_category_changes(conn, &source, &target)
+    })?;
+    Ok(changes)
+}
+
+/// Every prompt in the `source` subtree with the path it gets under `target`. Only reads.
+fn planned_category_changes(conn: &rusqlite::Connection, source: &str, target: &str) -> rusqlite::Result<Vec<CategoryChange>> {
+    let mut stmt = conn.prepare(
+        "SELECT uuid, category_path, locked FROM prompts
+         WHERE category_path = ?1 OR category_path LIKE ?2 ESCAPE '\\'
+         ORDER BY category_path, uuid"
+    )?;
+    let rows = stmt.query_map(params![source, subtree_pattern(source)], |row| {
+        let before: String = row.get(1)?;
+        Ok(CategoryChange {
+            prompt_uuid: row.get(0)?,
+            after: moved_category_path(&before, source, target).unwrap_or_else(|| before.clone()),
+            before,
+            locked: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Rewrite the category_path of every prompt in the `source` subtree to live under `target`.
+/// Refuses if any of those prompts is locked.
+fn move_category_paths(conn: &rusqlite::Connection, source: &str, target: &str) -> Result<usize> {
+    let changes = planned_category_changes(conn, source, target)?;
+
+    let locked = changes.iter().filter(|change| change.locked).count();
+    if locked > 0 {
+        return Err(AppError::Validation(format!(
+            "'{}' contains {} locked prompts; unlock them before moving it", source, locked
+        )));
+    }
+
+    let mut update = conn.prepare(
+        "UPDATE prompts SET category_path = ?1, updated_at = datetime('now') WHERE uuid = ?2"
+    )?;
+    for change in &changes {
+        validate_category_path(&change.after)?;
+        update.execute(params![change.after, change.prompt_uuid])?;
+    }
+
+    Ok(changes.len())
+}
+
+/// Every category path in use, including parents that only exist through their subcategories
+/// ("work" for "work/ml"), with subtree prompt counts. Sorted case-insensitively so variants
+/// like "Work" and "work" end up next to each other.
+fn load_category_paths(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<CategoryPathCount>> {
+    let mut stmt = conn.prepare(
+        "SELECT COALESCE(category_path, ?1), COUNT(*) FROM prompts GROUP BY 1"
+    )?;
+    let rows = stmt.query_map([UNCATEGORIZED], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+
+    let mut counts: BTreeMap<String, i64> = BTreeMap::new();
+    for row in rows {
+        let (category_path, prompt_count) = row?;
+        for (_, path) in breadcrumb_segments(&category_path) {
+            *counts.entry(path).or_insert(0) += prompt_count;
+        }
+    }
+
+    let mut paths: Vec<CategoryPathCount> = counts.into_iter()
+        .map(|(path, prompt_count)| CategoryPathCount { path, prompt_count })
+        .collect();
+    paths.sort_by(|a, b| a.path.to_lowercase().cmp(&b.path.to_lowercase()).then_with(|| a.path.cmp(&b.path)));
+    Ok(paths)
+}
+
+/// Get all category paths with prompt counts, for category autocomplete
+#[tauri::command]
+pub async fn get_all_category_paths() -> std::result::Result<Vec<CategoryPathCount>, String> {
+    log::info!("Getting all category paths for autocomplete");
+
+    let db = get_database()?;
+    let paths = db.with_connection(load_category_paths)?;
+
+    log::debug!("Found {} category paths", paths.len());
+    Ok(paths)
+}
+
+/// Rewrite every category_path to its normalized form, merging case variants such as
+/// "Work/ML" and "work/ml". Locked prompts are left alone and reported.
+fn normalize_all_category_paths(conn: &rusqlite::Connection) -> Result<CategoryNormalization> {
+    let rows: Vec<(String, String, bool)> = {
+        let mut stmt = conn.prepare(
+            "SELECT uuid, category_path, locked FROM prompts WHERE category_path IS NOT NULL ORDER BY category_path, uuid"
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+
+    let mut merges: BTreeMap<String, CategoryMerge> = BTreeMap::new();
+    let mut skipped_locked = Vec::new();
+    let mut update = conn.prepare(
+        "UPDATE prompts SET category_path = ?1, updated_at = datetime('now') WHERE uuid = ?2"
+    )?;
+
+    for (uuid, category_path, locked) in rows {
+        let canonical = normalize_category_path(&category_path);
+        if canonical == category_path {
+            continue;
+        }
+        if locked {
+            skipped_locked.push(uuid);
+            continue;
+        }
+
+        validate_category_path(&canonical)?;
+        update.execute(params![canonical, uuid])?;
+
+        let merge = merges.entry(canonical.clone()).or_insert_with(|| CategoryMerge {
+            canonical,
+            merged: Vec::new(),
+            prompt_count: 0,
+        });
+        if !merge.merged.contains(&category_path) {
+            merge.merged.push(category_path);
+        }
+        merge.prompt_count += 1;
+    }
+
+    Ok(CategoryNormalization {
+        merges: merges.into_values().collect(),
+        skipped_locked,
+    })
+}
+
+/// One-time cleanup: trim and lowercase every category path so case variants ("Work/ML",
+/// "work/ml ") become one category. Returns which paths were merged into which canonical
+/// path, and the locked prompts that were skipped. Runs regardless of the
+/// normalize_category_paths setting, which only affects future writes.
+#[tauri::command]
+pub async fn normalize_categories() -> std::result::Result<CategoryNormalization, String> {
+    log::info!("Normalizing category paths");
+
+    let db = get_database()?;
+    let result = db.with_transaction(|tx| {
+        let before = snapshot_category_paths(tx)?;
+        let result = normalize_all_category_paths(tx)?;
+        record_category_undo(tx, "normalize_categories", &before)?;
+        Ok(result)
+    })?;
+
+    log::info!("Merged category paths into {} canonical paths ({} locked prompts skipped)",
+               result.merges.len(), result.skipped_locked.len());
+    Ok(result)
+}
+
+/// Breadcrumb trail from the root to `category_path`, with prompt counts at each level.
+/// "Uncategorized" is a single root segment.
+#[tauri::command]
+pub async fn get_category_breadcrumbs(category_path: String) -> std::result::Result<Vec<Breadcrumb>, String> {
+    let category_path = category_path.trim();
+    validate_category_path(category_path)?;
+
+    let segments = if category_path == UNCATEGORIZED {
+        vec![(UNCATEGORIZED.to_string(), UNCATEGORIZED.to_string())]
+    } else {
+        breadcrumb_segments(category_path)
+    };
+
+    let db = get_database()?;
+    let breadcrumbs = db.with_connection(|conn| {
+        segments.iter()
+            .map(|(name, path)| {
+                let prompt_count = count_prompts_in_category(conn, path)?;
+                Ok(Breadcrumb { name: name.clone(), path: path.clone(), prompt_count })
+            })
+            .collect()
+    })?;
+
+    Ok(breadcrumbs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_connection;
+
+    #[test]
+    fn test_breadcrumb_segments() {
+        assert_eq!(breadcrumb_segments("work"), vec![("work".to_string(), "work".to_string())]);
+        assert_eq!(breadcrumb_segments("work/ml/vision"), vec![
+            ("work".to_string(), "work".to_string()),
+            ("ml".to_string(), "work/ml".to_string()),
+            ("vision".to_string(), "work/ml/vision".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_count_prompts_in_category() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, category_path, created_at, updated_at)
+                 SELECT column1, column1, column2, '2024-01-01', '2024-01-01' FROM (VALUES
+                     ('a', 'work'), ('b', 'work/ml'), ('c', 'work/ml/vision'),
+                     ('d', 'workshop'), ('e', 'work_x'), ('f', NULL), ('g', 'Uncategorized'));"
+        ).unwrap();
+
+        assert_eq!(count_prompts_in_category(&conn, "work").unwrap(), 3);
+        assert_eq!(count_prompts_in_category(&conn, "work/ml").unwrap(), 2);
+        // '_' is a LIKE wildcard and must be matched literally
+        assert_eq!(count_prompts_in_category(&conn, "work_x").unwrap(), 1);
+        assert_eq!(count_prompts_in_category(&conn, UNCATEGORIZED).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_load_category_paths() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, category_path, created_at, updated_at)
+                 SELECT column1, column1, column2, '2024-01-01', '2024-01-01' FROM (VALUES
+                     ('a', 'work/ml'), ('b', 'work/ml'), ('c', 'Work'),
+                     ('d', 'work/ml/vision'), ('e', NULL), ('f', 'personal'));"
+        ).unwrap();
+
+        let paths: Vec<(String, i64)> = load_category_paths(&conn).unwrap()
+            .into_iter()
+            .map(|p| (p.path, p.prompt_count))
+            .collect();
+        assert_eq!(paths, vec![
+            ("personal".to_string(), 1),
+            ("Uncategorized".to_string(), 1),
+            ("Work".to_string(), 1),
+            ("work".to_string(), 3),
+            ("work/ml".to_string(), 3),
+            ("work/ml/vision".to_string(), 1),
+        ]);
+    }
+
+    #[test]
+    fn test_normalize_category_path() {
+        assert_eq!(normalize_category_path("Work / ML "), "work/ml");
+        assert_eq!(normalize_category_path("work/ml"), "work/ml");
+        assert_eq!(normalize_category_path("uncategorized"), UNCATEGORIZED);
+    }
+
+    #[test]
+    fn test_normalize_all_category_paths() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, category_path, locked, created_at, updated_at)
+                 SELECT column1, column1, column2, column3, '2024-01-01', '2024-01-01' FROM (VALUES
+                     ('a', 'Work/ML', 0), ('b', 'work/ml', 0), ('c', 'work/ML ', 0), ('d', 'Work/ML', 0),
+                     ('e', 'Personal', 1), ('f', 'notes', 0), ('g', NULL, 0));"
+        ).unwrap();
+
+        let result = normalize_all_category_paths(&conn).unwrap();
+        assert_eq!(result, CategoryNormalization {
+            merges: vec![CategoryMerge {
+                canonical: "work/ml".to_string(),
+                merged: vec!["Work/ML".to_string(), "work/ML ".to_string()],
+                prompt_count: 3,
+            }],
+            skipped_locked: vec!["e".to_string()],
+        });
+
+        let distinct: i64 = conn.query_row(
+            "SELECT COUNT(DISTINCT category_path) FROM prompts WHERE uuid IN ('a', 'b', 'c', 'd')", [], |row| row.get(0)
+        ).unwrap();
+        assert_eq!(distinct, 1);
+
+        // Writes are only normalized once the setting is on
+        assert_eq!(category_path_for_write(&conn, "New/Path").unwrap(), "New/Path");
+        conn.execute("INSERT INTO settings VALUES (?1, 'true')", [NORMALIZE_CATEGORY_PATHS]).unwrap();
+        assert_eq!(category_path_for_write(&conn, "New/Path").unwrap(), "new/path");
+    }
+
+    #[test]
+    fn test_undo_category_changes() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, category_path, created_at, updated_at)
+                 SELECT column1, column1, column2, '2024-01-01', '2024-01-01' FROM (VALUES
+                     ('a', 'work/ml'), ('b', 'work/ml/vision'), ('c', 'work/ml'), ('d', 'other'));"
+        ).unwrap();
+        let path = |uuid: &str| conn.query_row(
+            "SELECT category_path FROM prompts WHERE uuid = ?1", [uuid], |row| row.get::<_, String>(0)
+        ).unwrap();
+
+        assert!(matches!(undo_category_changes(&conn), Err(AppError::NotFound(_))));
+
+        let before = snapshot_category_paths(&conn).unwrap();
+        move_category_paths(&conn, "work/ml", "research/ml").unwrap();
+        assert_eq!(record_category_undo(&conn, "move_category", &before).unwrap(), 3);
+
+        // A no-op operation keeps the record of the last real one
+        let before = snapshot_category_paths(&conn).unwrap();
+        assert_eq!(record_category_undo(&conn, "normalize_categories", &before).unwrap(), 0);
+
+        // Edited and locked prompts are not clobbered
+        conn.execute("UPDATE prompts SET category_path = 'elsewhere' WHERE uuid = 'b'", []).unwrap();
+        conn.execute("UPDATE prompts SET locked = 1 WHERE uuid = 'c'", []).unwrap();
+
+        let undo = undo_category_changes(&conn).unwrap();
+        assert_eq!(undo, CategoryUndo {
+            operation: "move_category".to_string(),
+            restored: 1,
+            skipped: vec!["b".to_string(), "c".to_string()],
+        });
+        assert_eq!((path("a"), path("b"), path("c"), path("d")),
+                   ("work/ml".to_string(), "elsewhere".to_string(), "research/ml".to_string(), "other".to_string()));
+
+        // Only undoable once
+        assert!(matches!(undo_category_changes(&conn), Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_moved_category_path() {
+        assert_eq!(moved_category_path("work/ml", "work/ml", "research/ml").as_deref(), Some("research/ml"));
+        assert_eq!(moved_category_path("work/ml/vision", "work/ml", "research/ml").as_deref(), Some("research/ml/vision"));
+        assert_eq!(moved_category_path("work/mlops", "work/ml", "research/ml"), None);
+        assert_eq!(moved_category_path("work", "work/ml", "research/ml"), None);
+    }
+
+    #[test]
+    fn test_move_category_paths() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, category_path, created_at, updated_at)
+                 SELECT column1, column1, column2, '2024-01-01', '2024-01-01' FROM (VALUES
+                     ('a', 'work/ml'), ('b', 'work/ml/vision'), ('c', 'work/mlops'));"
+        ).unwrap();
+
+        let preview = planned_category_changes(&conn, "work/ml", "research/ml").unwrap();
+        assert_eq!(preview, vec![
+            CategoryChange { prompt_uuid: "a".to_string(), before: "work/ml".to_string(), after: "research/ml".to_string(), locked: false },
+            CategoryChange { prompt_uuid: "b".to_string(), before: "work/ml/vision".to_string(), after: "research/ml/vision".to_string(), locked: false },
+        ]);
+
+        assert_eq!(move_category_paths(&conn, "work/ml", "research/ml").unwrap(), 2);
+        let path = |uuid: &str| conn.query_row(
+            "SELECT category_path FROM prompts WHERE uuid = ?1", [uuid], |row| row.get::<_, String>(0)
+        ).unwrap();
+        assert_eq!(path("a"), "research/ml");
+        assert_eq!(path("b"), "research/ml/vision");
+        assert_eq!(path("c"), "work/mlops");
+
+        conn.execute("UPDATE prompts SET locked = 1 WHERE uuid = 'b'", []).unwrap();
+        assert!(matches!(move_category_paths(&conn, "research/ml", "ml"), Err(AppError::Validation(_))));
+        assert_eq!(path("a"), "research/ml");
+    }
+}
@@ -1,7 +1,108 @@
 use rusqlite::{Connection, Result as SqliteResult};
-use std::sync::{Arc, Mutex};
-use tauri::Manager;
+use serde::Serialize;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::Emitter;
 use crate::error::{AppError, Result};
+use crate::paths::prompts_dir;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationStage {
+    Started,
+    Finished,
+}
+
+/// Payload of the `migration-progress` event, emitted around each schema migration that
+/// actually runs so the UI can show "Upgrading database…" instead of looking frozen
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MigrationProgress {
+    pub migration: String,
+    pub stage: MigrationStage,
+    /// Rows rewritten by the migration, once finished
+    pub rows: Option<usize>,
+}
+
+/// Tables whose foreign keys gained ON DELETE CASCADE, in rebuild order, with their current
+/// column definitions. SQLite can't alter a foreign key, so older databases recreate them.
+/// Column migrations run first, so every column listed here exists by the time of a rebuild.
+const CASCADE_REBUILDS: &[(&str, &str)] = &[
+    ("versions", "uuid TEXT PRIMARY KEY,
+        prompt_uuid TEXT NOT NULL,
+        semver TEXT NOT NULL,
+        body TEXT NOT NULL,
+        metadata TEXT,
+        created_at TEXT NOT NULL,
+        parent_uuid TEXT,
+        deleted_at TEXT,
+        author TEXT,
+        FOREIGN KEY (prompt_uuid) REFERENCES prompts(uuid) ON DELETE CASCADE"),
+    ("runs", "uuid TEXT PRIMARY KEY,
+        version_uuid TEXT NOT NULL,
+        model TEXT,
+        input TEXT,
+        output TEXT,
+        bleu REAL,
+        rouge REAL,
+        judge_score REAL,
+        prompt_tokens INT,
+        completion_tokens INT,
+        cost_usd REAL,
+        created_at TEXT,
+        FOREIGN KEY (version_uuid) REFERENCES versions(uuid) ON DELETE CASCADE"),
+    ("version_tags", "version_uuid TEXT NOT NULL,
+        tag TEXT NOT NULL COLLATE NOCASE,
+        created_at TEXT NOT NULL,
+        PRIMARY KEY (version_uuid, tag),
+        FOREIGN KEY (version_uuid) REFERENCES versions(uuid) ON DELETE CASCADE"),
+    ("attachments", "id INTEGER PRIMARY KEY AUTOINCREMENT,
+        prompt_uuid TEXT NOT NULL,
+        filename TEXT NOT NULL,
+        relative_path TEXT NOT NULL UNIQUE,
+        created_at TEXT NOT NULL,
+        FOREIGN KEY (prompt_uuid) REFERENCES prompts(uuid) ON DELETE CASCADE"),
+];
+
+/// Every table created by create_tables, dropped by reset. Dropping prompts_fts also drops
+/// its FTS5 shadow tables.
+const RESET_TABLES: &[&str] = &[
+    "version_tags", "runs", "attachments", "versions", "prompts_fts", "prompts",
+    "model_providers", "settings", "category_undo",
+];
+
+/// Stored in PRAGMA user_version once migrate_schema has run; bump it with every new migration
+pub const SCHEMA_VERSION: i64 = 1;
+
+/// Attempts after the first when SQLite reports the database busy or locked
+const MAX_BUSY_RETRIES: u32 = 5;
+/// Upper bound for the backoff between busy retries
+const MAX_BUSY_BACKOFF: Duration = Duration::from_millis(200);
+
+/// Whether an error is transient lock contention (SQLITE_BUSY / SQLITE_LOCKED)
+fn is_busy(error: &AppError) -> bool {
+    matches!(
+        error,
+        AppError::Database(rusqlite::Error::SqliteFailure(e, _))
+            if matches!(e.code, rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked)
+    )
+}
+
+/// Run `op`, retrying with exponential backoff (10ms, 20ms, ... capped) while it fails with
+/// lock contention, up to MAX_BUSY_RETRIES times
+fn retry_on_busy<R>(mut op: impl FnMut() -> Result<R>) -> Result<R> {
+    let mut attempt = 0;
+    loop {
+        match op() {
+            Err(e) if is_busy(&e) && attempt < MAX_BUSY_RETRIES => {
+                let backoff = Duration::from_millis(10 << attempt).min(MAX_BUSY_BACKOFF);
+                attempt += 1;
+                log::warn!("Database busy, retrying in {:?} (attempt {}/{}): {}", backoff, attempt, MAX_BUSY_RETRIES, e);
+                std::thread::sleep(backoff);
+            }
+            result => return result,
+        }
+    }
+}
 
 pub struct DatabaseManager {
     connection: Arc<Mutex<Connection>>,
@@ -9,19 +110,22 @@ pub struct DatabaseManager {
 
 impl DatabaseManager {
     pub fn new(app_handle: &tauri::AppHandle) -> Result<Self> {
-        let documents_dir = app_handle
-            .path()
-            .document_dir()
-            .map_err(|e| AppError::Path(e.to_string()))?;
-
-        let app_dir = documents_dir.join("PromptMaster");
+        let app_dir = prompts_dir(app_handle)?;
         std::fs::create_dir_all(&app_dir)?;
         let db_path = app_dir.join("promptmaster.db");
         
         let conn = Connection::open(db_path)?;
+        // SQLite leaves foreign keys unenforced unless enabled per connection
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
         
         // Initialize database schema
         Self::create_tables(&conn)?;
+        Self::migrate_schema(&conn, &|progress| {
+            log::info!("Migration {} {:?}", progress.migration, progress.stage);
+            if let Err(e) = app_handle.emit("migration-progress", progress) {
+                log::warn!("Failed to emit migration-progress event: {}", e);
+            }
+        })?;
         
         // Initialize default data
         Self::initialize_default_data(&conn)?;
@@ -41,7 +145,9 @@ impl DatabaseManager {
                 category_path TEXT DEFAULT 'Uncategorized',
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL,
-                prod_version_uuid TEXT
+                prod_version_uuid TEXT,
+                locked INTEGER NOT NULL DEFAULT 0,
+                accessed_at TEXT
             );
             
             CREATE INDEX IF NOT EXISTS idx_category ON prompts(category_path);
@@ -54,7 +160,9 @@ impl DatabaseManager {
                 metadata TEXT,
                 created_at TEXT NOT NULL,
                 parent_uuid TEXT,
-                FOREIGN KEY (prompt_uuid) REFERENCES prompts(uuid)
+                deleted_at TEXT,
+                author TEXT,
+                FOREIGN KEY (prompt_uuid) REFERENCES prompts(uuid) ON DELETE CASCADE
             );
             
             CREATE INDEX IF NOT EXISTS idx_versions_prompt 
@@ -79,7 +187,7 @@ impl DatabaseManager {
                 completion_tokens INT,
                 cost_usd REAL,
                 created_at TEXT,
-                FOREIGN KEY (version_uuid) REFERENCES versions(uuid)
+                FOREIGN KEY (version_uuid) REFERENCES versions(uuid) ON DELETE CASCADE
             );
             
             CREATE INDEX IF NOT EXISTS idx_runs_version 
@@ -105,12 +213,204 @@ impl DatabaseManager {
             
             CREATE INDEX IF NOT EXISTS idx_model_providers_provider 
             ON model_providers(provider);
+            
+            CREATE TABLE IF NOT EXISTS attachments (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                prompt_uuid TEXT NOT NULL,
+                filename TEXT NOT NULL,
+                relative_path TEXT NOT NULL UNIQUE,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (prompt_uuid) REFERENCES prompts(uuid) ON DELETE CASCADE
+            );
+            
+            CREATE INDEX IF NOT EXISTS idx_attachments_prompt 
+            ON attachments(prompt_uuid);
+            
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            
+            CREATE TABLE IF NOT EXISTS category_undo (
+                prompt_uuid TEXT PRIMARY KEY,
+                operation TEXT NOT NULL,
+                before_path TEXT,
+                after_path TEXT,
+                created_at TEXT NOT NULL
+            );
+            
+            CREATE TABLE IF NOT EXISTS version_tags (
+                version_uuid TEXT NOT NULL,
+                tag TEXT NOT NULL COLLATE NOCASE,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (version_uuid, tag),
+                FOREIGN KEY (version_uuid) REFERENCES versions(uuid) ON DELETE CASCADE
+            );
             "#,
         )?;
         
         Ok(())
     }
     
+    /// Bring databases created by older versions up to the current schema.
+    /// CREATE TABLE IF NOT EXISTS leaves existing tables alone, so new columns are added here.
+    /// `progress` is called when each pending migration starts and finishes.
+    fn migrate_schema(conn: &Connection, progress: &dyn Fn(MigrationProgress)) -> Result<()> {
+        Self::add_column_migration(conn, progress, "prompts", "locked", "INTEGER NOT NULL DEFAULT 0", |_| Ok(0))?;
+        Self::add_column_migration(conn, progress, "prompts", "accessed_at", "TEXT", |conn| {
+            // Existing prompts count as last opened when they were created
+            conn.execute("UPDATE prompts SET accessed_at = created_at WHERE accessed_at IS NULL", [])
+        })?;
+        Self::add_column_migration(conn, progress, "versions", "deleted_at", "TEXT", |_| Ok(0))?;
+        Self::add_column_migration(conn, progress, "versions", "author", "TEXT", |_| Ok(0))?;
+        for (table, columns) in CASCADE_REBUILDS {
+            Self::cascade_foreign_keys_migration(conn, progress, table, columns)?;
+        }
+        // Let "most recent first" listings read in index order instead of sorting every row
+        Self::add_index_migration(conn, progress, "idx_versions_created", "versions(created_at)")?;
+        Self::add_index_migration(conn, progress, "idx_prompts_updated", "prompts(updated_at)")?;
+        conn.execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION))?;
+        Ok(())
+    }
+    
+    /// Create an index unless it already exists. Building one on a large library takes a
+    /// moment, so it is reported like any other migration (rows = rows in the table).
+    fn add_index_migration(
+        conn: &Connection,
+        progress: &dyn Fn(MigrationProgress),
+        name: &str,
+        target: &str,
+    ) -> Result<()> {
+        let exists: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'index' AND name = ?1",
+            [name],
+            |row| row.get(0)
+        )?;
+        if exists {
+            return Ok(());
+        }
+        
+        let migration = format!("index {}", target);
+        progress(MigrationProgress { migration: migration.clone(), stage: MigrationStage::Started, rows: None });
+        
+        conn.execute(&format!("CREATE INDEX {} ON {}", name, target), [])?;
+        let table = target.split('(').next().unwrap_or(target);
+        let rows: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))?;
+        
+        progress(MigrationProgress { migration, stage: MigrationStage::Finished, rows: Some(rows as usize) });
+        Ok(())
+    }
+    
+    fn column_names(conn: &Connection, table: &str) -> SqliteResult<Vec<String>> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+        columns.collect()
+    }
+    
+    fn column_exists(conn: &Connection, table: &str, column: &str) -> SqliteResult<bool> {
+        Ok(Self::column_names(conn, table)?.iter().any(|name| name == column))
+    }
+    
+    /// Add a column unless it already exists, then run `backfill` (returning rows updated).
+    /// Nothing is reported for columns that are already present.
+    fn add_column_migration<F>(
+        conn: &Connection,
+        progress: &dyn Fn(MigrationProgress),
+        table: &str,
+        column: &str,
+        definition: &str,
+        backfill: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(&Connection) -> SqliteResult<usize>,
+    {
+        if Self::column_exists(conn, table, column)? {
+            return Ok(());
+        }
+        
+        let migration = format!("add {}.{}", table, column);
+        progress(MigrationProgress { migration: migration.clone(), stage: MigrationStage::Started, rows: None });
+        
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+        let rows = backfill(conn)?;
+        
+        progress(MigrationProgress { migration, stage: MigrationStage::Finished, rows: Some(rows) });
+        Ok(())
+    }
+    
+    /// Recreate `table` with `columns` if any of its foreign keys lacks ON DELETE CASCADE.
+    /// Rows and indexes are carried over; existing orphans are kept (see find_orphaned_versions).
+    fn cascade_foreign_keys_migration(
+        conn: &Connection,
+        progress: &dyn Fn(MigrationProgress),
+        table: &str,
+        columns: &str,
+    ) -> Result<()> {
+        let non_cascading: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM pragma_foreign_key_list(?1) WHERE on_delete != 'CASCADE'",
+            [table],
+            |row| row.get(0)
+        )?;
+        if non_cascading == 0 {
+            return Ok(());
+        }
+        
+        let migration = format!("cascade {} foreign keys", table);
+        progress(MigrationProgress { migration: migration.clone(), stage: MigrationStage::Started, rows: None });
+        
+        // Dropping the old table must not trip (or cascade through) foreign keys. The pragma
+        // is a no-op inside a transaction, so it is toggled around the rebuild.
+        let foreign_keys: bool = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0))?;
+        conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+        let rebuilt = Self::rebuild_table(conn, table, columns);
+        if foreign_keys {
+            conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        }
+        let rows = rebuilt?;
+        
+        progress(MigrationProgress { migration, stage: MigrationStage::Finished, rows: Some(rows) });
+        Ok(())
+    }
+    
+    /// Copy `table` into a new table defined by `columns`, swap it in and restore its indexes,
+    /// all in one transaction. Returns the number of rows copied.
+    fn rebuild_table(conn: &Connection, table: &str, columns: &str) -> Result<usize> {
+        let tx = conn.unchecked_transaction()?;
+        let rebuilt = format!("{}_rebuild", table);
+        
+        let indexes = {
+            let mut stmt = tx.prepare(
+                "SELECT sql FROM sqlite_master WHERE type = 'index' AND tbl_name = ?1 AND sql IS NOT NULL"
+            )?;
+            let rows = stmt.query_map([table], |row| row.get::<_, String>(0))?;
+            rows.collect::<SqliteResult<Vec<String>>>()?
+        };
+        
+        tx.execute(&format!("CREATE TABLE {} ({})", rebuilt, columns), [])?;
+        
+        // Only columns present in both; anything the old table lacks takes its default
+        let mut shared = Vec::new();
+        for column in Self::column_names(&tx, &rebuilt)? {
+            if Self::column_exists(&tx, table, &column)? {
+                shared.push(column);
+            }
+        }
+        let shared = shared.join(", ");
+        let rows = tx.execute(
+            &format!("INSERT INTO {} ({}) SELECT {} FROM {}", rebuilt, shared, shared, table),
+            []
+        )?;
+        
+        tx.execute(&format!("DROP TABLE {}", table), [])?;
+        tx.execute(&format!("ALTER TABLE {} RENAME TO {}", rebuilt, table), [])?;
+        for index in &indexes {
+            tx.execute(index, [])?;
+        }
+        
+        tx.commit()?;
+        Ok(rows)
+    }
+    
     fn initialize_default_data(_conn: &Connection) -> Result<()> {
         // No default model providers - let users add their own current models
         // This prevents the app from shipping with outdated model lists
@@ -118,32 +418,260 @@ impl DatabaseManager {
         Ok(())
     }
     
-    pub fn with_connection<F, R>(&self, f: F) -> Result<R>
+    /// Drop every table and recreate the schema from scratch. All data is lost.
+    pub fn reset(&self) -> Result<()> {
+        let conn = self.lock()?;
+        Self::reset_schema(&conn)
+    }
+    
+    fn reset_schema(conn: &Connection) -> Result<()> {
+        // Tables are dropped in one go, so references between them don't matter
+        conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+        let dropped = (|| {
+            let tx = conn.unchecked_transaction()?;
+            for table in RESET_TABLES {
+                tx.execute(&format!("DROP TABLE IF EXISTS {}", table), [])?;
+            }
+            tx.commit()
+        })();
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        dropped?;
+        
+        Self::create_tables(conn)?;
+        // Indexes added by migrations are not part of create_tables
+        Self::migrate_schema(conn, &|_| {})?;
+        Self::initialize_default_data(conn)
+    }
+    
+    fn lock(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.connection.lock()
+            .map_err(|e| AppError::LockPoisoned(e.to_string()))
+    }
+    
+    /// Run `f` on the connection. `f` is run again (after a short backoff) if SQLite reports
+    /// the database busy or locked, so it must be safe to repeat.
+    pub fn with_connection<F, R>(&self, mut f: F) -> Result<R>
     where
-        F: FnOnce(&Connection) -> SqliteResult<R>,
+        F: FnMut(&Connection) -> SqliteResult<R>,
     {
-        let conn = self.connection.lock()
-            .map_err(|e| AppError::Database(rusqlite::Error::SqliteFailure(
-                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
-                Some(format!("Failed to acquire database lock: {}", e))
-            )))?;
-        
-        f(&*conn).map_err(AppError::from)
+        retry_on_busy(|| {
+            let conn = self.lock()?;
+            f(&conn).map_err(AppError::from)
+        })
     }
     
-    pub fn with_transaction<F, R>(&self, f: F) -> Result<R>
+    /// Run `f` in a transaction, committing only if it succeeds. The closure returns AppError
+    /// so business-rule failures (Conflict, NotFound, ...) propagate typed, not as SQLite errors.
+    /// If SQLite reports the database busy or locked, the transaction is rolled back and retried.
+    pub fn with_transaction<F, R>(&self, mut f: F) -> Result<R>
     where
-        F: FnOnce(&rusqlite::Transaction) -> SqliteResult<R>,
+        F: FnMut(&rusqlite::Transaction) -> Result<R>,
     {
-        let conn = self.connection.lock()
-            .map_err(|e| AppError::Database(rusqlite::Error::SqliteFailure(
-                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
-                Some(format!("Failed to acquire database lock: {}", e))
-            )))?;
-        
-        let tx = conn.unchecked_transaction()?;
-        let result = f(&tx)?;
-        tx.commit()?;
-        Ok(result)
+        retry_on_busy(|| {
+            let conn = self.lock()?;
+            let tx = conn.unchecked_transaction()?;
+            let result = f(&tx)?;
+            tx.commit()?;
+            Ok(result)
+        })
+    }
+}
+
+/// Give a test connection the production schema (create_tables plus every migration) with
+/// foreign keys enforced. No default data is seeded.
+#[cfg(test)]
+pub fn create_test_schema(conn: &Connection) {
+    conn.execute_batch("PRAGMA foreign_keys = ON;").expect("enable foreign keys");
+    DatabaseManager::create_tables(conn).expect("create tables");
+    DatabaseManager::migrate_schema(conn, &|_| {}).expect("migrate schema");
+}
+
+/// In-memory database with the production schema, for tests.
+#[cfg(test)]
+pub fn test_connection() -> Connection {
+    let conn = Connection::open_in_memory().expect("open in-memory database");
+    create_test_schema(&conn);
+    conn
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_reset_schema_empties_every_table() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, tags, created_at, updated_at) VALUES ('p1', 'T', '[]', 'now', 'now');
+             INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at) VALUES ('v1', 'p1', '1.0.0', 'Body', 'now');
+             INSERT INTO settings VALUES ('author', 'Ada');
+             INSERT INTO prompts_fts (title, body, tags) VALUES ('T', 'Body', '');"
+        ).unwrap();
+
+        DatabaseManager::reset_schema(&conn).unwrap();
+
+        for table in RESET_TABLES {
+            let count: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0)).unwrap();
+            assert_eq!(count, 0, "{}", table);
+        }
+        // The recreated schema is complete and foreign keys are enforced again
+        let events = RefCell::new(Vec::new());
+        DatabaseManager::migrate_schema(&conn, &|progress| events.borrow_mut().push(progress)).unwrap();
+        assert!(events.borrow().is_empty());
+        let foreign_keys: i64 = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0)).unwrap();
+        assert_eq!(foreign_keys, 1);
+    }
+
+    #[test]
+    fn test_migrate_schema_reports_only_pending_migrations() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE prompts (uuid TEXT PRIMARY KEY, created_at TEXT NOT NULL, updated_at TEXT, locked INTEGER NOT NULL DEFAULT 0);
+             CREATE TABLE versions (uuid TEXT PRIMARY KEY, created_at TEXT, deleted_at TEXT, author TEXT);
+             CREATE INDEX idx_versions_created ON versions(created_at);
+             INSERT INTO prompts VALUES ('a', '2025-01-01', NULL, 0), ('b', '2025-01-02', NULL, 0);"
+        ).unwrap();
+        let user_version = || -> i64 { conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap() };
+        assert_eq!(user_version(), 0);
+
+        let events = RefCell::new(Vec::new());
+        DatabaseManager::migrate_schema(&conn, &|progress| events.borrow_mut().push(progress)).unwrap();
+        assert_eq!(events.borrow().as_slice(), &[
+            MigrationProgress { migration: "add prompts.accessed_at".to_string(), stage: MigrationStage::Started, rows: None },
+            MigrationProgress { migration: "add prompts.accessed_at".to_string(), stage: MigrationStage::Finished, rows: Some(2) },
+            MigrationProgress { migration: "index prompts(updated_at)".to_string(), stage: MigrationStage::Started, rows: None },
+            MigrationProgress { migration: "index prompts(updated_at)".to_string(), stage: MigrationStage::Finished, rows: Some(2) },
+        ]);
+
+        assert_eq!(user_version(), SCHEMA_VERSION);
+
+        // Already migrated: nothing to report
+        events.borrow_mut().clear();
+        DatabaseManager::migrate_schema(&conn, &|progress| events.borrow_mut().push(progress)).unwrap();
+        assert!(events.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_cascade_migration_preserves_data() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE prompts (uuid TEXT PRIMARY KEY, created_at TEXT NOT NULL, updated_at TEXT, locked INTEGER NOT NULL DEFAULT 0, accessed_at TEXT);
+             CREATE INDEX idx_prompts_updated ON prompts(updated_at);
+             CREATE TABLE versions (uuid TEXT PRIMARY KEY, prompt_uuid TEXT NOT NULL, semver TEXT NOT NULL, body TEXT NOT NULL,
+                                    metadata TEXT, created_at TEXT NOT NULL, parent_uuid TEXT,
+                                    FOREIGN KEY (prompt_uuid) REFERENCES prompts(uuid));
+             CREATE UNIQUE INDEX idx_versions_unique_semver ON versions(prompt_uuid, semver);
+             CREATE INDEX idx_versions_created ON versions(created_at);
+             CREATE TABLE runs (uuid TEXT PRIMARY KEY, version_uuid TEXT NOT NULL, model TEXT,
+                                FOREIGN KEY (version_uuid) REFERENCES versions(uuid));
+             CREATE TABLE attachments (id INTEGER PRIMARY KEY AUTOINCREMENT, prompt_uuid TEXT NOT NULL, filename TEXT NOT NULL,
+                                       relative_path TEXT NOT NULL UNIQUE, created_at TEXT NOT NULL,
+                                       FOREIGN KEY (prompt_uuid) REFERENCES prompts(uuid));
+             CREATE INDEX idx_attachments_prompt ON attachments(prompt_uuid);
+             INSERT INTO prompts VALUES ('p', '2025-01-01', NULL, 0, NULL), ('q', '2025-01-01', NULL, 0, NULL);
+             INSERT INTO versions VALUES ('v1', 'p', '1.0.0', 'one', NULL, '2025-01-01', NULL),
+                                         ('v2', 'q', '1.0.0', 'two', NULL, '2025-01-01', NULL);
+             INSERT INTO runs VALUES ('r1', 'v1', 'gpt-4o');
+             INSERT INTO attachments (prompt_uuid, filename, relative_path, created_at)
+                 VALUES ('p', 'notes.txt', 'p/notes.txt', '2025-01-01');
+             PRAGMA foreign_keys = ON;"
+        ).unwrap();
+
+        let events = RefCell::new(Vec::new());
+        DatabaseManager::migrate_schema(&conn, &|progress| events.borrow_mut().push(progress)).unwrap();
+        let finished: Vec<(String, Option<usize>)> = events.borrow().iter()
+            .filter(|event| event.stage == MigrationStage::Finished)
+            .map(|event| (event.migration.clone(), event.rows))
+            .collect();
+        assert_eq!(finished, vec![
+            ("add versions.deleted_at".to_string(), Some(0)),
+            ("add versions.author".to_string(), Some(0)),
+            ("cascade versions foreign keys".to_string(), Some(2)),
+            ("cascade runs foreign keys".to_string(), Some(1)),
+            ("cascade attachments foreign keys".to_string(), Some(1)),
+        ]);
+
+        // Data, indexes and the foreign_keys setting survive the rebuild
+        let body: String = conn.query_row("SELECT body FROM versions WHERE uuid = 'v1'", [], |row| row.get(0)).unwrap();
+        assert_eq!(body, "one");
+        let model: String = conn.query_row("SELECT model FROM runs WHERE uuid = 'r1'", [], |row| row.get(0)).unwrap();
+        assert_eq!(model, "gpt-4o");
+        assert!(conn.execute("INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at) VALUES ('dup', 'p', '1.0.0', 'x', '')", []).is_err());
+        let foreign_keys: bool = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0)).unwrap();
+        assert!(foreign_keys);
+
+        let filename: String = conn.query_row("SELECT filename FROM attachments WHERE prompt_uuid = 'p'", [], |row| row.get(0)).unwrap();
+        assert_eq!(filename, "notes.txt");
+        let attachment_indexes: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name = 'idx_attachments_prompt'", [], |row| row.get(0)
+        ).unwrap();
+        assert_eq!(attachment_indexes, 1);
+
+        // Deleting a prompt now removes its versions, their runs and its attachments
+        conn.execute("DELETE FROM prompts WHERE uuid = 'p'", []).unwrap();
+        let versions: i64 = conn.query_row("SELECT COUNT(*) FROM versions", [], |row| row.get(0)).unwrap();
+        let runs: i64 = conn.query_row("SELECT COUNT(*) FROM runs", [], |row| row.get(0)).unwrap();
+        let attachments: i64 = conn.query_row("SELECT COUNT(*) FROM attachments", [], |row| row.get(0)).unwrap();
+        assert_eq!((versions, runs, attachments), (1, 0, 0));
+
+        events.borrow_mut().clear();
+        DatabaseManager::migrate_schema(&conn, &|progress| events.borrow_mut().push(progress)).unwrap();
+        assert!(events.borrow().is_empty());
+    }
+
+    fn busy_error() -> AppError {
+        AppError::Database(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            None
+        ))
+    }
+
+    #[test]
+    fn test_poisoned_lock_is_not_retried() {
+        let db = DatabaseManager { connection: Arc::new(Mutex::new(Connection::open_in_memory().unwrap())) };
+        let connection = Arc::clone(&db.connection);
+        let _ = std::thread::spawn(move || {
+            let _guard = connection.lock().unwrap();
+            panic!("poison the lock");
+        }).join();
+
+        let mut calls = 0;
+        let result = db.with_connection(|_| {
+            calls += 1;
+            Ok(())
+        });
+        assert!(matches!(result, Err(AppError::LockPoisoned(_))));
+        assert!(!is_busy(&result.unwrap_err()));
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn test_retry_on_busy_recovers_from_transient_contention() {
+        let mut calls = 0;
+        let result = retry_on_busy(|| {
+            calls += 1;
+            if calls < 3 { Err(busy_error()) } else { Ok(calls) }
+        });
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn test_retry_on_busy_gives_up_and_skips_other_errors() {
+        let mut calls = 0;
+        let result: Result<()> = retry_on_busy(|| {
+            calls += 1;
+            Err(busy_error())
+        });
+        assert!(result.is_err_and(|e| is_busy(&e)));
+        assert_eq!(calls, MAX_BUSY_RETRIES + 1);
+
+        let mut calls = 0;
+        let result: Result<()> = retry_on_busy(|| {
+            calls += 1;
+            Err(AppError::Validation("nope".to_string()))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
     }
 }
@@ -0,0 +1,448 @@
+use serde::{Deserialize, Serialize};
+use rusqlite::Connection;
+use crate::attachments::ATTACHMENTS_DIR;
+use crate::db::get_database;
+use crate::error::AppError;
+use crate::logging::log_security_event;
+use crate::paths::prompts_dir;
+use crate::versions::parse_semver;
+
+/// Phrase reset_database requires, typed exactly, before it deletes anything
+pub const RESET_CONFIRMATION: &str = "DELETE ALL PROMPTMASTER DATA";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseDebugInfo {
+    pub schema_version: i64,
+    pub prompts_count: i64,
+    pub versions_count: i64,
+    pub fts_count: i64,
+    pub fts_table_exists: bool,
+}
+
+/// A version whose prompt_uuid has no matching prompt row
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct OrphanedVersion {
+    pub uuid: String,
+    pub prompt_uuid: String,
+    pub semver: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthStatus {
+    pub db_ok: bool,
+    pub prompts_dir_writable: bool,
+    pub fts_ok: bool,
+    pub prompts_dir: Option<String>,
+    /// Error message per failed check, e.g. ("db", "...")
+    pub errors: Vec<(String, String)>,
+}
+
+/// Count rows in a table, logging and returning 0 instead of failing
+fn count_rows(conn: &Connection, table: &str) -> i64 {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to count rows in {}: {}", table, e);
+            0
+        })
+}
+
+/// Check whether a table (including virtual tables) exists
+fn table_exists(conn: &Connection, table: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [table],
+        |row| row.get::<_, i64>(0)
+    ).map(|count| count > 0).unwrap_or(false)
+}
+
+/// Get database debug information for support and bug reports.
+/// Individual checks never fail the command; missing or broken tables report zero.
+#[tauri::command]
+pub async fn get_database_debug_info() -> std::result::Result<DatabaseDebugInfo, String> {
+    log::info!("Collecting database debug info");
+
+    let db = get_database()?;
+
+    let info = db.with_connection(|conn| {
+        let schema_version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap_or(0);
+        let fts_table_exists = table_exists(conn, "prompts_fts");
+
+        Ok(DatabaseDebugInfo {
+            schema_version,
+            prompts_count: count_rows(conn, "prompts"),
+            versions_count: count_rows(conn, "versions"),
+            fts_count: if fts_table_exists { count_rows(conn, "prompts_fts") } else { 0 },
+            fts_table_exists,
+        })
+    })?;
+
+    log::debug!("Database debug info: {:?}", info);
+    Ok(info)
+}
+
+const ORPHANED_VERSIONS: &str = "SELECT uuid FROM versions WHERE prompt_uuid NOT IN (SELECT uuid FROM prompts)";
+
+fn load_orphaned_versions(conn: &Connection) -> rusqlite::Result<Vec<OrphanedVersion>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT uuid, prompt_uuid, semver, created_at FROM versions
+         WHERE uuid IN ({}) ORDER BY prompt_uuid, created_at",
+        ORPHANED_VERSIONS
+    ))?;
+    let rows = stmt.query_map([], |row| Ok(OrphanedVersion {
+        uuid: row.get(0)?,
+        prompt_uuid: row.get(1)?,
+        semver: row.get(2)?,
+        created_at: row.get(3)?,
+    }))?;
+    rows.collect()
+}
+
+/// Delete orphaned versions along with their version tags and runs, which reference them.
+/// Returns the number of versions deleted.
+fn delete_orphaned_versions(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.execute(&format!("DELETE FROM version_tags WHERE version_uuid IN ({})", ORPHANED_VERSIONS), [])?;
+    conn.execute(&format!("DELETE FROM runs WHERE version_uuid IN ({})", ORPHANED_VERSIONS), [])?;
+    conn.execute(&format!("DELETE FROM versions WHERE uuid IN ({})", ORPHANED_VERSIONS), [])
+}
+
+/// List versions (including trashed ones) whose prompt no longer exists
+#[tauri::command]
+pub async fn find_orphaned_versions() -> std::result::Result<Vec<OrphanedVersion>, String> {
+    let db = get_database()?;
+    let orphans = db.with_connection(load_orphaned_versions)?;
+
+    if !orphans.is_empty() {
+        log::warn!("Found {} orphaned versions", orphans.len());
+    }
+    Ok(orphans)
+}
+
+/// Permanently delete every orphaned version, its version tags and its runs in one transaction.
+/// Returns the number of versions deleted.
+#[tauri::command]
+pub async fn cleanup_orphaned_versions() -> std::result::Result<usize, String> {
+    let db = get_database()?;
+    let deleted = db.with_transaction(|tx| Ok(delete_orphaned_versions(tx)?))?;
+
+    log::info!("Deleted {} orphaned versions", deleted);
+    Ok(deleted)
+}
+
+/// A version whose semver isn't strict X.Y.Z, e.g. "1.0" or "v1.0.0" ingested from frontmatter
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct InvalidSemver {
+    pub uuid: String,
+    pub prompt_uuid: String,
+    pub semver: String,
+    /// Normalized X.Y.Z form, or None when the string can't be repaired automatically
+    pub repaired: Option<String>,
+}
+
+/// Normalize a loose version string to X.Y.Z: surrounding whitespace and a leading "v" are
+/// stripped and missing minor/patch numbers become 0. Anything else (pre-release suffixes,
+/// more than three parts, non-numeric parts) returns None.
+fn normalize_semver(semver: &str) -> Option<String> {
+    let trimmed = semver.trim();
+    let trimmed = trimmed.strip_prefix(['v', 'V']).unwrap_or(trimmed);
+
+    let parts = trimmed.split('.')
+        // u32::from_str would also accept a leading "+"
+        .map(|part| part.bytes().all(|b| b.is_ascii_digit()).then(|| part.parse::<u32>().ok())?)
+        .collect::<Option<Vec<u32>>>()?;
+    match parts[..] {
+        [major] => Some(format!("{}.0.0", major)),
+        [major, minor] => Some(format!("{}.{}.0", major, minor)),
+        [major, minor, patch] => Some(format!("{}.{}.{}", major, minor, patch)),
+        _ => None,
+    }
+}
+
+/// Versions (including trashed ones) whose semver fails parse_semver, with their proposed repair
+fn load_invalid_semvers(conn: &Connection) -> rusqlite::Result<Vec<InvalidSemver>> {
+    let mut stmt = conn.prepare("SELECT uuid, prompt_uuid, semver FROM versions ORDER BY prompt_uuid, created_at")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?;
+
+    let mut invalid = Vec::new();
+    for row in rows {
+        let (uuid, prompt_uuid, semver) = row?;
+        if parse_semver(&semver).is_err() {
+            let repaired = normalize_semver(&semver);
+            invalid.push(InvalidSemver { uuid, prompt_uuid, semver, repaired });
+        }
+    }
+    Ok(invalid)
+}
+
+/// Rewrite every repairable semver to its normalized form. A repair is skipped (and the row
+/// left as is) when the prompt already has a version with that number. Returns the repairs made.
+fn repair_invalid_semvers(conn: &Connection) -> rusqlite::Result<Vec<InvalidSemver>> {
+    let mut repairs = Vec::new();
+    for version in load_invalid_semvers(conn)? {
+        let Some(repaired) = version.repaired.as_deref() else {
+            log::warn!("Cannot repair semver {:?} of version {}", version.semver, version.uuid);
+            continue;
+        };
+
+        let taken: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM versions WHERE prompt_uuid = ?1 AND semver = ?2)",
+            [&version.prompt_uuid, repaired],
+            |row| row.get(0),
+        )?;
+        if taken {
+            log::warn!("Not repairing version {}: prompt {} already has {}", version.uuid, version.prompt_uuid, repaired);
+            continue;
+        }
+
+        conn.execute("UPDATE versions SET semver = ?1 WHERE uuid = ?2", [repaired, &version.uuid])?;
+        repairs.push(version);
+    }
+    Ok(repairs)
+}
+
+/// List versions (including trashed ones) with a semver that isn't strict X.Y.Z, which would
+/// break bump_patch_version on the next save
+#[tauri::command]
+pub async fn validate_versions() -> std::result::Result<Vec<InvalidSemver>, String> {
+    let db = get_database()?;
+    let invalid = db.with_connection(load_invalid_semvers)?;
+
+    if !invalid.is_empty() {
+        log::warn!("Found {} versions with invalid semvers", invalid.len());
+    }
+    Ok(invalid)
+}
+
+/// Normalize every repairable invalid semver ("1.0" → "1.0.0", "v1.2.3" → "1.2.3") in one
+/// transaction. Returns the versions changed; anything left is still reported by validate_versions.
+#[tauri::command]
+pub async fn repair_versions() -> std::result::Result<Vec<InvalidSemver>, String> {
+    let db = get_database()?;
+    let repairs = db.with_transaction(|tx| Ok(repair_invalid_semvers(tx)?))?;
+
+    log::info!("Repaired {} version semvers", repairs.len());
+    Ok(repairs)
+}
+
+/// What reset_database removed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetSummary {
+    pub prompts_deleted: i64,
+    pub versions_deleted: i64,
+    /// Markdown files deleted from the PromptMaster directory (0 unless clear_files was set)
+    pub files_deleted: usize,
+}
+
+/// Delete every `.md` file under `dir`, recursively. Other files (the database itself,
+/// logs, ...) are left in place. Returns the number of files deleted.
+fn remove_markdown_files(dir: &std::path::Path) -> std::io::Result<usize> {
+    let mut removed = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            removed += remove_markdown_files(&path)?;
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            std::fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Development/testing utility: drop and recreate every table (prompts, versions, runs,
+/// model providers, settings, search index, ...). With `clear_files`, also delete the
+/// markdown files and attachments in the PromptMaster directory. Refuses unless `confirm`
+/// is exactly RESET_CONFIRMATION. This cannot be undone.
+#[tauri::command]
+pub async fn reset_database(
+    confirm: String,
+    clear_files: Option<bool>,
+    app_handle: tauri::AppHandle,
+) -> std::result::Result<ResetSummary, String> {
+    if confirm != RESET_CONFIRMATION {
+        log::warn!("Refused database reset: confirmation phrase did not match");
+        return Err(AppError::Validation(format!(
+            "Type \"{}\" to confirm the reset", RESET_CONFIRMATION
+        )).into());
+    }
+
+    let db = get_database()?;
+    let (prompts_deleted, versions_deleted) = db.with_connection(|conn| {
+        Ok((count_rows(conn, "prompts"), count_rows(conn, "versions")))
+    })?;
+
+    log::warn!("!!! RESETTING DATABASE: dropping all tables ({} prompts, {} versions) !!!",
+               prompts_deleted, versions_deleted);
+    let _ = log_security_event("DATABASE_RESET", &format!(
+        "All tables dropped and recreated ({} prompts, {} versions)", prompts_deleted, versions_deleted
+    ));
+    db.reset()?;
+
+    // Files go only after the database is empty, so the watcher has nothing to re-import
+    let mut files_deleted = 0;
+    if clear_files.unwrap_or(false) {
+        let prompts_dir = prompts_dir(&app_handle)?;
+        if prompts_dir.is_dir() {
+            files_deleted = remove_markdown_files(&prompts_dir).map_err(AppError::from)?;
+            let attachments_dir = prompts_dir.join(ATTACHMENTS_DIR);
+            if attachments_dir.is_dir() {
+                std::fs::remove_dir_all(&attachments_dir).map_err(AppError::from)?;
+            }
+        }
+        log::warn!("!!! RESET: deleted {} markdown files and all attachments from {} !!!",
+                   files_deleted, prompts_dir.display());
+    }
+
+    log::warn!("!!! DATABASE RESET COMPLETE !!!");
+    Ok(ResetSummary { prompts_deleted, versions_deleted, files_deleted })
+}
+
+/// Check that a directory is writable by creating and removing a probe file
+fn check_dir_writable(dir: &std::path::Path) -> std::result::Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let probe = dir.join(format!(".health-check-{}", uuid::Uuid::now_v7()));
+    std::fs::write(&probe, b"ok").map_err(|e| e.to_string())?;
+    std::fs::remove_file(&probe).map_err(|e| e.to_string())
+}
+
+/// Cheap startup check of the database, prompts directory and search index.
+/// Each check is reported independently so a partial failure is diagnosable.
+#[tauri::command]
+pub async fn health_check(app_handle: tauri::AppHandle) -> std::result::Result<HealthStatus, String> {
+    let mut errors = Vec::new();
+
+    let (db_ok, fts_ok) = match get_database() {
+        Ok(db) => {
+            let db_ok = match db.with_connection(|conn| conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0))) {
+                Ok(_) => true,
+                Err(e) => {
+                    errors.push(("db".to_string(), e.to_string()));
+                    false
+                }
+            };
+            let fts_ok = match db.with_connection(|conn| conn.query_row("SELECT COUNT(*) FROM prompts_fts", [], |row| row.get::<_, i64>(0))) {
+                Ok(_) => true,
+                Err(e) => {
+                    errors.push(("fts".to_string(), e.to_string()));
+                    false
+                }
+            };
+            (db_ok, fts_ok)
+        }
+        Err(e) => {
+            errors.push(("db".to_string(), e.to_string()));
+            (false, false)
+        }
+    };
+
+    let prompts_dir = match prompts_dir(&app_handle) {
+        Ok(dir) => Some(dir),
+        Err(e) => {
+            errors.push(("prompts_dir".to_string(), e.to_string()));
+            None
+        }
+    };
+
+    let prompts_dir_writable = match prompts_dir {
+        Some(ref dir) => match check_dir_writable(dir) {
+            Ok(()) => true,
+            Err(e) => {
+                errors.push(("prompts_dir".to_string(), e));
+                false
+            }
+        },
+        None => false,
+    };
+
+    if !errors.is_empty() {
+        log::warn!("Health check failures: {:?}", errors);
+    }
+
+    Ok(HealthStatus {
+        db_ok,
+        prompts_dir_writable,
+        fts_ok,
+        prompts_dir: prompts_dir.map(|dir| dir.to_string_lossy().into_owned()),
+        errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_connection;
+
+    #[test]
+    fn test_remove_markdown_files_keeps_other_files() {
+        let dir = std::env::temp_dir().join(format!("promptmaster-test-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(dir.join("work")).unwrap();
+        std::fs::write(dir.join("a.md"), "").unwrap();
+        std::fs::write(dir.join("work").join("b.md"), "").unwrap();
+        std::fs::write(dir.join("promptmaster.db"), "").unwrap();
+
+        assert_eq!(remove_markdown_files(&dir).unwrap(), 2);
+        assert!(!dir.join("work").join("b.md").exists());
+        assert!(dir.join("promptmaster.db").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_orphaned_versions() {
+        let conn = test_connection();
+        // Orphans predate enforced foreign keys, so they are inserted with enforcement off
+        conn.execute_batch(
+            "PRAGMA foreign_keys = OFF;
+             INSERT INTO prompts (uuid, title, created_at, updated_at) VALUES ('p', 'P', '2025-01-01', '2025-01-01');
+             INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at)
+                 VALUES ('v1', 'p', '1.0.0', 'a', '2025-01-01'), ('gone1', 'x', '1.0.0', 'b', '2025-01-01'),
+                        ('gone2', 'x', '1.0.1', 'c', '2025-01-02');
+             INSERT INTO version_tags VALUES ('v1', 'shipped', '2025-01-01'), ('gone1', 'shipped', '2025-01-01');
+             INSERT INTO runs (uuid, version_uuid) VALUES ('r1', 'v1'), ('r2', 'gone2');
+             PRAGMA foreign_keys = ON;"
+        ).unwrap();
+
+        let orphans = load_orphaned_versions(&conn).unwrap();
+        assert_eq!(orphans.iter().map(|v| v.uuid.as_str()).collect::<Vec<_>>(), vec!["gone1", "gone2"]);
+
+        assert_eq!(delete_orphaned_versions(&conn).unwrap(), 2);
+        assert!(load_orphaned_versions(&conn).unwrap().is_empty());
+        assert_eq!(count_rows(&conn, "versions"), 1);
+        assert_eq!(count_rows(&conn, "version_tags"), 1);
+        assert_eq!(count_rows(&conn, "runs"), 1);
+    }
+
+    #[test]
+    fn test_normalize_semver() {
+        assert_eq!(normalize_semver("1.0").as_deref(), Some("1.0.0"));
+        assert_eq!(normalize_semver(" v1.2.3 ").as_deref(), Some("1.2.3"));
+        assert_eq!(normalize_semver("V2").as_deref(), Some("2.0.0"));
+        assert_eq!(normalize_semver("01.002.3").as_deref(), Some("1.2.3"));
+        assert_eq!(normalize_semver("1.0.0-beta"), None);
+        assert_eq!(normalize_semver("1.2.3.4"), None);
+        assert_eq!(normalize_semver("+1.0"), None);
+        assert_eq!(normalize_semver(""), None);
+    }
+
+    #[test]
+    fn test_repair_invalid_semvers() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, created_at, updated_at) VALUES ('p', 'P', '2025-01-01', '2025-01-01');
+             INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at)
+                 VALUES ('v1', 'p', '1.0', 'a', '2025-01-01'), ('v2', 'p', 'v1.0.0', 'b', '2025-01-02'),
+                        ('v3', 'p', 'draft', 'c', '2025-01-03'), ('v4', 'p', '1.0.1', 'd', '2025-01-04');"
+        ).unwrap();
+
+        let invalid = load_invalid_semvers(&conn).unwrap();
+        assert_eq!(invalid.iter().map(|v| v.uuid.as_str()).collect::<Vec<_>>(), vec!["v1", "v2", "v3"]);
+
+        // v2 normalizes to the number v1 just took, so it's left for manual review
+        let repairs = repair_invalid_semvers(&conn).unwrap();
+        assert_eq!(repairs.len(), 1);
+        assert_eq!((repairs[0].uuid.as_str(), repairs[0].repaired.as_deref()), ("v1", Some("1.0.0")));
+        let remaining = load_invalid_semvers(&conn).unwrap();
+        assert_eq!(remaining.iter().map(|v| v.uuid.as_str()).collect::<Vec<_>>(), vec!["v2", "v3"]);
+    }
+}
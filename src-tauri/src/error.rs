@@ -1,4 +1,5 @@
 use std::fmt;
+use crate::versions::VersionInfo;
 
 #[derive(Debug)]
 pub enum AppError {
@@ -9,8 +10,15 @@ pub enum AppError {
     Path(String),
     InvalidInput(String),
     Validation(String),
+    /// An expected business-rule conflict (duplicate content, taken names, ...)
+    Conflict(String),
+    /// Saving a version whose body matches an existing version of the same prompt
+    DuplicateContent { existing: VersionInfo },
+    NotFound(String),
     FileWatcher(notify::Error),
     Regex(regex::Error),
+    /// A thread panicked while holding the database connection; not retryable
+    LockPoisoned(String),
 }
 
 impl fmt::Display for AppError {
@@ -23,8 +31,14 @@ impl fmt::Display for AppError {
             AppError::Path(e) => write!(f, "Path error: {}", e),
             AppError::InvalidInput(e) => write!(f, "Invalid input: {}", e),
             AppError::Validation(e) => write!(f, "Validation error: {}", e),
+            AppError::Conflict(e) => write!(f, "Conflict: {}", e),
+            AppError::DuplicateContent { existing } => {
+                write!(f, "Conflict: A version with this content already exists as {}", existing.semver)
+            }
+            AppError::NotFound(e) => write!(f, "Not found: {}", e),
             AppError::FileWatcher(e) => write!(f, "File watcher error: {}", e),
             AppError::Regex(e) => write!(f, "Regex error: {}", e),
+            AppError::LockPoisoned(e) => write!(f, "Database lock poisoned: {}", e),
         }
     }
 }
@@ -67,11 +81,46 @@ impl From<regex::Error> for AppError {
     }
 }
 
-// Convert AppError to String for Tauri commands
+// Convert AppError to String for Tauri commands. Errors the UI acts on are sent as JSON
+// ({"code", "message", ...}) so it can offer a follow-up instead of just showing the message.
 impl From<AppError> for String {
     fn from(e: AppError) -> Self {
-        e.to_string()
+        match e {
+            AppError::DuplicateContent { ref existing } => serde_json::json!({
+                "code": "duplicate_content",
+                "message": e.to_string(),
+                "existing_semver": existing.semver,
+                "existing": existing,
+            }).to_string(),
+            _ => e.to_string(),
+        }
     }
 }
 
 pub type Result<T> = std::result::Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_content_error_is_structured() {
+        let existing = VersionInfo {
+            uuid: "0190a1b2-0000-7000-8000-000000000000".to_string(),
+            semver: "1.2.0".to_string(),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            parent_uuid: None,
+            author: None,
+        };
+        let error: String = AppError::DuplicateContent { existing }.into();
+        let payload: serde_json::Value = serde_json::from_str(&error).unwrap();
+        assert_eq!(payload["code"], "duplicate_content");
+        assert_eq!(payload["existing_semver"], "1.2.0");
+        assert_eq!(payload["existing"]["uuid"], "0190a1b2-0000-7000-8000-000000000000");
+        assert_eq!(payload["existing"]["created_at"], "2024-01-01T00:00:00Z");
+        assert_eq!(payload["message"], "Conflict: A version with this content already exists as 1.2.0");
+
+        let error: String = AppError::NotFound("Prompt x".to_string()).into();
+        assert_eq!(error, "Not found: Prompt x");
+    }
+}
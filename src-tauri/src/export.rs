@@ -0,0 +1,457 @@
+use std::collections::HashSet;
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use rusqlite::params;
+use crate::categories::{subtree_pattern, UNCATEGORIZED};
+use crate::db::get_database;
+use crate::error::{AppError, Result};
+use crate::markdown::{build_filename, build_markdown, resolve_version_file_path, slugify, uuid_fragment, Frontmatter};
+use crate::metadata::{validate_category_path, PromptMetadata};
+use crate::security::validate_uuid;
+use crate::versions::LATEST_VERSION_FIRST;
+
+/// Header row of export_runs_csv
+const RUNS_CSV_HEADER: [&str; 10] = [
+    "prompt_title", "version_semver", "model", "bleu", "rouge", "judge_score",
+    "prompt_tokens", "completion_tokens", "cost_usd", "created_at",
+];
+
+/// A prompt's latest version as stored in the database, ready to be written out
+struct ExportedPrompt {
+    uuid: String,
+    title: String,
+    tags: Vec<String>,
+    category_path: Option<String>,
+    created_at: String,
+    semver: String,
+    body: String,
+    metadata: Option<PromptMetadata>,
+    author: Option<String>,
+}
+
+/// Load every prompt with its latest version (LATEST_VERSION_FIRST, matching get_latest_version)
+fn load_latest_prompts(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<ExportedPrompt>> {
+    load_latest_prompts_matching(conn, None)
+}
+
+/// Load the latest versions of prompts in `category_path` or any of its subcategories, using
+/// the same matching as count_prompts_in_category; None loads every prompt
+fn load_latest_prompts_matching(conn: &rusqlite::Connection, category_path: Option<&str>) -> rusqlite::Result<Vec<ExportedPrompt>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT p.uuid, p.title, p.tags, p.category_path, p.created_at, v.semver, v.body, v.metadata, v.author
+         FROM prompts p
+         JOIN versions v ON v.uuid = (
+             SELECT uuid FROM versions WHERE prompt_uuid = p.uuid AND deleted_at IS NULL ORDER BY {} LIMIT 1
+         )
+         WHERE ?2 IS NULL
+            OR COALESCE(p.category_path, ?1) = ?2
+            OR COALESCE(p.category_path, ?1) LIKE ?3 ESCAPE '\\'
+         ORDER BY p.title",
+        LATEST_VERSION_FIRST
+    ))?;
+
+    let pattern = category_path.map(subtree_pattern);
+    let rows = stmt.query_map(params![UNCATEGORIZED, category_path, pattern], |row| {
+        let tags_str: Option<String> = row.get(2)?;
+        let metadata_json: Option<String> = row.get(7)?;
+        Ok(ExportedPrompt {
+            uuid: row.get(0)?,
+            title: row.get(1)?,
+            tags: tags_str.and_then(|t| serde_json::from_str(&t).ok()).unwrap_or_default(),
+            category_path: row.get(3)?,
+            created_at: row.get(4)?,
+            semver: row.get(5)?,
+            body: row.get(6)?,
+            metadata: metadata_json.and_then(|json| PromptMetadata::from_json(&json).ok()),
+            author: row.get(8)?,
+        })
+    })?;
+
+    rows.collect()
+}
+
+/// Map a category path ("work/ml") to a relative directory. Segments that could escape the
+/// export root or aren't valid file names are sanitized; "Uncategorized" maps to the root.
+fn category_dir(category_path: Option<&str>) -> PathBuf {
+    let mut dir = PathBuf::new();
+
+    let Some(category_path) = category_path.filter(|path| *path != "Uncategorized") else {
+        return dir;
+    };
+
+    for segment in category_path.split('/') {
+        let segment: String = segment.trim()
+            .chars()
+            .map(|c| if matches!(c, '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '_' } else { c })
+            .collect();
+        if segment.is_empty() || segment.chars().all(|c| c == '.') {
+            continue;
+        }
+        dir.push(segment);
+    }
+
+    dir
+}
+
+/// Result of export_markdown_zip
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportArchive {
+    pub path: String,
+    pub files: usize,
+    pub uncompressed_bytes: u64,
+    pub compressed_bytes: u64,
+}
+
+/// Creation date (YYYY-MM-DD) used in a prompt's exported filename and frontmatter
+fn export_date(prompt: &ExportedPrompt) -> &str {
+    prompt.created_at.get(..10).unwrap_or(&prompt.created_at)
+}
+
+/// Write one prompt as a frontmatter markdown file under `root`, inside its category directory
+fn write_prompt_file(root: &Path, prompt: &ExportedPrompt) -> Result<PathBuf> {
+    let dir = root.join(category_dir(prompt.category_path.as_deref()));
+    std::fs::create_dir_all(&dir)?;
+
+    let slug = slugify(&prompt.title);
+    let path = resolve_version_file_path(&dir, export_date(prompt), &slug, &prompt.semver, &prompt.uuid);
+
+    std::fs::write(&path, prompt_markdown(prompt))?;
+    Ok(path)
+}
+
+/// Render a prompt as the frontmatter markdown written by the exports
+fn prompt_markdown(prompt: &ExportedPrompt) -> String {
+    let date = export_date(prompt);
+    let metadata = prompt.metadata.clone().unwrap_or_default();
+    let frontmatter = Frontmatter {
+        uuid: prompt.uuid.clone(),
+        version: prompt.semver.clone(),
+        title: prompt.title.clone(),
+        tags: prompt.tags.clone(),
+        created: date.to_string(),
+        modified: chrono::Utc::now().format("%Y-%m-%d").to_string(),
+        author: prompt.author.clone(),
+        category: prompt.category_path.clone().filter(|path| path != "Uncategorized"),
+        models: metadata.models.unwrap_or_default(),
+        notes: metadata.notes,
+        custom_fields: metadata.custom_fields,
+    };
+
+    build_markdown(&frontmatter, &prompt.body)
+}
+
+/// Write prompts into a zip archive laid out like export_all_markdown's directory tree.
+/// Entry names that collide get the prompt's uuid fragment, as on disk. Returns the number
+/// of entries and their total uncompressed size.
+fn write_markdown_zip<W: Write + Seek>(prompts: &[ExportedPrompt], writer: W) -> Result<(usize, u64)> {
+    let mut zip = zip::ZipWriter::new(writer);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let mut names = HashSet::new();
+    let mut uncompressed_bytes = 0;
+    for prompt in prompts {
+        let dir: Vec<String> = category_dir(prompt.category_path.as_deref())
+            .iter()
+            .map(|segment| segment.to_string_lossy().into_owned())
+            .collect();
+        let slug = slugify(&prompt.title);
+        let entry_name = |fragment| {
+            let filename = build_filename(export_date(prompt), &slug, &prompt.semver, fragment);
+            dir.iter().cloned().chain(std::iter::once(filename)).collect::<Vec<_>>().join("/")
+        };
+
+        let mut name = entry_name(None);
+        if names.contains(&name) {
+            name = entry_name(Some(uuid_fragment(&prompt.uuid)));
+        }
+
+        let content = prompt_markdown(prompt);
+        zip.start_file(name.as_str(), options).map_err(std::io::Error::from)?;
+        zip.write_all(content.as_bytes())?;
+        uncompressed_bytes += content.len() as u64;
+        names.insert(name);
+    }
+
+    zip.finish().map_err(std::io::Error::from)?;
+    Ok((names.len(), uncompressed_bytes))
+}
+
+/// Export every prompt's latest version as a markdown file into `destination`.
+/// Categories become subdirectories so the export mirrors the category tree.
+/// Returns the number of files written.
+#[tauri::command]
+pub async fn export_all_markdown(destination: String) -> std::result::Result<usize, String> {
+    log::info!("Exporting all prompts as markdown to {}", destination);
+
+    if destination.trim().is_empty() {
+        return Err("Export destination cannot be empty".to_string());
+    }
+
+    let root = PathBuf::from(destination.trim());
+    std::fs::create_dir_all(&root)
+        .map_err(|e| AppError::Path(format!("Cannot create export directory {}: {}", root.display(), e)))?;
+
+    let db = get_database()?;
+    let prompts = db.with_connection(load_latest_prompts)?;
+
+    let mut written = 0;
+    for prompt in &prompts {
+        let path = write_prompt_file(&root, prompt)?;
+        log::debug!("Exported {} to {}", prompt.uuid, path.display());
+        written += 1;
+    }
+
+    log::info!("Exported {} prompts to {}", written, root.display());
+    Ok(written)
+}
+
+/// Export every prompt's latest version into a single deflate-compressed zip archive at
+/// `destination` (a file path), with the same folders and filenames as export_all_markdown.
+/// Reports the uncompressed and compressed sizes so backups can be sized up front.
+#[tauri::command]
+pub async fn export_markdown_zip(destination: String) -> std::result::Result<ExportArchive, String> {
+    log::info!("Exporting all prompts as a zip archive to {}", destination);
+
+    if destination.trim().is_empty() {
+        return Err("Export destination cannot be empty".to_string());
+    }
+    let path = PathBuf::from(destination.trim());
+    if path.is_dir() {
+        return Err(AppError::Path(format!("Export destination is a directory: {}", path.display())).into());
+    }
+
+    let db = get_database()?;
+    let prompts = db.with_connection(load_latest_prompts)?;
+
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| AppError::Path(format!("Cannot create export directory {}: {}", parent.display(), e)))?;
+    }
+    let file = std::fs::File::create(&path)
+        .map_err(|e| AppError::Path(format!("Cannot create {}: {}", path.display(), e)))?;
+    let (files, uncompressed_bytes) = write_markdown_zip(&prompts, file)?;
+    let compressed_bytes = std::fs::metadata(&path).map_err(AppError::from)?.len();
+
+    log::info!("Exported {} prompts to {} ({} bytes, {} uncompressed)", files, path.display(), compressed_bytes, uncompressed_bytes);
+    Ok(ExportArchive {
+        path: path.to_string_lossy().into_owned(),
+        files,
+        uncompressed_bytes,
+        compressed_bytes,
+    })
+}
+
+/// Export the latest version of every prompt in `category_path` and its subcategories as
+/// markdown into `destination`, mirroring the category hierarchy ("clients/acme" is written
+/// under destination/clients/acme). Returns the number of files written.
+#[tauri::command]
+pub async fn export_category(category_path: String, destination: String) -> std::result::Result<usize, String> {
+    log::info!("Exporting category {} as markdown to {}", category_path, destination);
+
+    let category_path = category_path.trim();
+    if category_path.is_empty() {
+        return Err(AppError::Validation("Category path cannot be empty".to_string()).into());
+    }
+    validate_category_path(category_path)?;
+
+    if destination.trim().is_empty() {
+        return Err("Export destination cannot be empty".to_string());
+    }
+    let root = PathBuf::from(destination.trim());
+    if root.is_file() {
+        return Err(AppError::Path(format!("Export destination is a file: {}", root.display())).into());
+    }
+
+    let db = get_database()?;
+    let prompts = db.with_connection(|conn| load_latest_prompts_matching(conn, Some(category_path)))?;
+    if prompts.is_empty() {
+        return Err(AppError::NotFound(format!("No prompts in category {}", category_path)).into());
+    }
+
+    std::fs::create_dir_all(&root)
+        .map_err(|e| AppError::Path(format!("Cannot create export directory {}: {}", root.display(), e)))?;
+
+    for prompt in &prompts {
+        let path = write_prompt_file(&root, prompt)?;
+        log::debug!("Exported {} to {}", prompt.uuid, path.display());
+    }
+
+    log::info!("Exported {} prompts from {} to {}", prompts.len(), category_path, root.display());
+    Ok(prompts.len())
+}
+
+/// Runs of one prompt's versions (or all runs) as CSV fields in RUNS_CSV_HEADER order,
+/// oldest first. Missing values become empty fields.
+fn load_run_rows(conn: &rusqlite::Connection, prompt_uuid: Option<&str>) -> rusqlite::Result<Vec<[String; 10]>> {
+    let mut stmt = conn.prepare(
+        "SELECT p.title, v.semver, r.model, r.bleu, r.rouge, r.judge_score,
+                r.prompt_tokens, r.completion_tokens, r.cost_usd, r.created_at
+         FROM runs r
+         JOIN versions v ON v.uuid = r.version_uuid
+         JOIN prompts p ON p.uuid = v.prompt_uuid
+         WHERE ?1 IS NULL OR v.prompt_uuid = ?1
+         ORDER BY r.created_at, r.uuid"
+    )?;
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    let rows = stmt.query_map([prompt_uuid], |row| Ok([
+        row.get::<_, String>(0)?,
+        row.get::<_, String>(1)?,
+        optional(row.get(2)?),
+        optional(row.get::<_, Option<f64>>(3)?.map(|v| v.to_string())),
+        optional(row.get::<_, Option<f64>>(4)?.map(|v| v.to_string())),
+        optional(row.get::<_, Option<f64>>(5)?.map(|v| v.to_string())),
+        optional(row.get::<_, Option<i64>>(6)?.map(|v| v.to_string())),
+        optional(row.get::<_, Option<i64>>(7)?.map(|v| v.to_string())),
+        optional(row.get::<_, Option<f64>>(8)?.map(|v| v.to_string())),
+        optional(row.get(9)?),
+    ]))?;
+    rows.collect()
+}
+
+/// Write a header and `rows` as CSV, quoting fields with commas, quotes or newlines
+fn write_runs_csv<W: Write>(rows: &[[String; 10]], writer: W) -> Result<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record(RUNS_CSV_HEADER).map_err(std::io::Error::from)?;
+    for row in rows {
+        csv.write_record(row).map_err(std::io::Error::from)?;
+    }
+    csv.flush()?;
+    Ok(())
+}
+
+/// Export recorded runs as a CSV file at `destination` (a file path), optionally limited to
+/// the versions of one prompt. Empty cells mean the run has no value for that column.
+/// Returns the number of runs written.
+#[tauri::command]
+pub async fn export_runs_csv(prompt_uuid: Option<String>, destination: String) -> std::result::Result<usize, String> {
+    log::info!("Exporting runs (prompt: {:?}) as CSV to {}", prompt_uuid, destination);
+
+    if let Some(ref prompt_uuid) = prompt_uuid {
+        validate_uuid(prompt_uuid)?;
+    }
+    if destination.trim().is_empty() {
+        return Err("Export destination cannot be empty".to_string());
+    }
+    let path = PathBuf::from(destination.trim());
+    if path.is_dir() {
+        return Err(AppError::Path(format!("Export destination is a directory: {}", path.display())).into());
+    }
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| AppError::Path(format!("Cannot create export directory {}: {}", parent.display(), e)))?;
+    }
+
+    let db = get_database()?;
+    let rows = db.with_connection(|conn| load_run_rows(conn, prompt_uuid.as_deref()))?;
+
+    let file = std::fs::File::create(&path).map_err(AppError::from)?;
+    write_runs_csv(&rows, std::io::BufWriter::new(file))?;
+
+    log::info!("Exported {} runs to {}", rows.len(), path.display());
+    Ok(rows.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_connection;
+
+    #[test]
+    fn test_category_dir() {
+        assert_eq!(category_dir(None), PathBuf::new());
+        assert_eq!(category_dir(Some("Uncategorized")), PathBuf::new());
+        assert_eq!(category_dir(Some("work/ml")), PathBuf::from("work").join("ml"));
+    }
+
+    #[test]
+    fn test_load_latest_prompts_matching_category_subtree() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, category_path, created_at, updated_at)
+                 VALUES ('p1', 'Acme brief', 'clients/acme', '2024-01-01', '2024-01-01'),
+                        ('p2', 'Acme legal', 'clients/acme/legal', '2024-01-01', '2024-01-01'),
+                        ('p3', 'Acme2', 'clients/acme2', '2024-01-01', '2024-01-01'),
+                        ('p4', 'Loose', NULL, '2024-01-01', '2024-01-01');
+             INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at)
+                 VALUES ('v1', 'p1', '1.0.0', 'a', '2024-01-01'),
+                        ('v2', 'p2', '1.0.0', 'b', '2024-01-01'),
+                        ('v3', 'p3', '1.0.0', 'c', '2024-01-01'),
+                        ('v4', 'p4', '1.0.0', 'd', '2024-01-01');"
+        ).unwrap();
+
+        let uuids = |category: Option<&str>| -> Vec<String> {
+            load_latest_prompts_matching(&conn, category).unwrap().into_iter().map(|p| p.uuid).collect()
+        };
+        assert_eq!(uuids(Some("clients/acme")), vec!["p1", "p2"]);
+        assert_eq!(uuids(Some("Uncategorized")), vec!["p4"]);
+        assert_eq!(uuids(None).len(), 4);
+    }
+
+    #[test]
+    fn test_write_runs_csv_escapes_and_filters() {
+        let conn = test_connection();
+        conn.execute_batch(
+            r#"INSERT INTO prompts (uuid, title, created_at, updated_at)
+                   VALUES ('p1', 'Summarize, "briefly"', '2024-01-01', '2024-01-01'), ('p2', 'Other', '2024-01-01', '2024-01-01');
+               INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at)
+                   VALUES ('v1', 'p1', '1.0.0', 'a', '2024-01-01'), ('v2', 'p2', '1.0.0', 'b', '2024-01-01');
+               INSERT INTO runs (uuid, version_uuid, model, bleu, rouge, judge_score, prompt_tokens, completion_tokens, cost_usd, created_at)
+                   VALUES ('r1', 'v1', 'gpt-4o', 0.5, NULL, 8.0, 120, 40, 0.002, '2024-01-01'),
+                          ('r2', 'v2', NULL, NULL, NULL, NULL, NULL, NULL, NULL, '2024-01-02');"#
+        ).unwrap();
+
+        let rows = load_run_rows(&conn, Some("p1")).unwrap();
+        assert_eq!(rows.len(), 1);
+        let mut out = Vec::new();
+        write_runs_csv(&rows, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "prompt_title,version_semver,model,bleu,rouge,judge_score,prompt_tokens,completion_tokens,cost_usd,created_at\n\
+             \"Summarize, \"\"briefly\"\"\",1.0.0,gpt-4o,0.5,,8,120,40,0.002,2024-01-01\n"
+        );
+
+        assert_eq!(load_run_rows(&conn, None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_write_markdown_zip_layout() {
+        let prompt = |uuid: &str, category: Option<&str>| ExportedPrompt {
+            uuid: uuid.to_string(),
+            title: "Summarize".to_string(),
+            tags: Vec::new(),
+            category_path: category.map(str::to_string),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            semver: "1.0.0".to_string(),
+            body: "Summarize the text. ".repeat(50),
+            metadata: None,
+            author: None,
+        };
+        let prompts = [prompt("0000-aaaaaaaa", Some("work/ml")), prompt("0000-bbbbbbbb", Some("work/ml")), prompt("0000-cccccccc", None)];
+
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        let (files, uncompressed_bytes) = write_markdown_zip(&prompts, &mut buffer).unwrap();
+        assert_eq!(files, 3);
+        assert!((buffer.get_ref().len() as u64) < uncompressed_bytes);
+
+        let mut archive = zip::ZipArchive::new(buffer).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, vec![
+            "2024-01-01--summarize--v1.0.0.md",
+            "work/ml/2024-01-01--summarize--v1.0.0--bbbbbbbb.md",
+            "work/ml/2024-01-01--summarize--v1.0.0.md",
+        ]);
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("2024-01-01--summarize--v1.0.0.md").unwrap(), &mut content).unwrap();
+        assert!(content.contains("uuid: \"0000-cccccccc\""));
+    }
+
+    #[test]
+    fn test_category_dir_cannot_escape_root() {
+        assert_eq!(category_dir(Some("../../etc")), PathBuf::from("etc"));
+        assert_eq!(category_dir(Some("a/./b")), PathBuf::from("a").join("b"));
+        assert_eq!(category_dir(Some("what?/x:y")), PathBuf::from("what_").join("x_y"));
+    }
+}
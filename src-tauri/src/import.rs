@@ -0,0 +1,265 @@
+use serde::{Deserialize, Serialize};
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use crate::categories::category_path_for_write;
+use crate::db::get_database;
+use crate::error::{AppError, Result};
+use crate::markdown::{parse_frontmatter, ParsedMarkdown};
+use crate::metadata::PromptMetadata;
+use crate::prompts::{ensure_unlocked, save_prompt_file_with_metadata};
+use crate::security::{validate_prompt_input, validate_uuid};
+use crate::settings::current_author;
+use crate::versions::{insert_version_with_retry, parse_semver, Version, LATEST_VERSION_FIRST};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportFileResult {
+    pub path: String,
+    pub success: bool,
+    /// true when the file's uuid matched an existing prompt
+    pub updated: bool,
+    pub prompt_uuid: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub updated: usize,
+    pub failed: usize,
+    pub files: Vec<ImportFileResult>,
+}
+
+/// Collect `.md` files under a directory (recursively), skipping hidden files and directories
+fn find_markdown_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path.file_name().is_none_or(|name| name.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+
+        if path.is_dir() {
+            find_markdown_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse a file for import. Files without frontmatter get a synthesized one: the filename
+/// becomes the title and the whole file the body. Files in subdirectories of the import root
+/// default to that relative directory as their category.
+fn parse_import_file(root: &Path, path: &Path) -> Result<ParsedMarkdown> {
+    let content = std::fs::read_to_string(path)?;
+
+    let mut parsed = if content.starts_with("---\n") {
+        parse_frontmatter(&content)?
+    } else {
+        ParsedMarkdown {
+            title: path.file_stem()
+                .map(|stem| stem.to_string_lossy().trim().to_string())
+                .unwrap_or_default(),
+            body: content.trim().to_string(),
+            ..ParsedMarkdown::default()
+        }
+    };
+
+    if parsed.category.is_none() {
+        let relative_dir = path.parent()
+            .and_then(|parent| parent.strip_prefix(root).ok())
+            .map(|dir| dir.components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/"))
+            .filter(|dir| !dir.is_empty());
+        parsed.category = relative_dir;
+    }
+
+    Ok(parsed)
+}
+
+/// Create or update the prompt described by a parsed file. Returns (prompt_uuid, updated).
+pub(crate) fn import_parsed(parsed: &ParsedMarkdown, app_handle: &tauri::AppHandle) -> Result<(String, bool)> {
+    let tags = parsed.tags.clone().unwrap_or_default();
+    validate_prompt_input(&parsed.title, &parsed.body, &tags)?;
+
+    let version = match parsed.version {
+        Some(ref version) if parse_semver(version).is_ok() => version.clone(),
+        _ => "1.0.0".to_string(),
+    };
+
+    let metadata = PromptMetadata {
+        title: Some(parsed.title.clone()),
+        tags: Some(tags.clone()),
+        models: parsed.models.clone(),
+        category_path: parsed.category.clone(),
+        notes: parsed.notes.clone(),
+        custom_fields: parsed.custom_fields.clone()
+            .filter(|fields| fields.as_object().is_some_and(|fields| !fields.is_empty())),
+        ..PromptMetadata::default()
+    };
+    metadata.validate()?;
+    let metadata_json = metadata.to_json()?;
+
+    // Keep the file's identity when it carries a valid uuid
+    let prompt_uuid = if validate_uuid(&parsed.uuid).is_ok() {
+        parsed.uuid.clone()
+    } else {
+        Uuid::now_v7().to_string()
+    };
+    let category_path = parsed.category.clone().unwrap_or_else(|| "Uncategorized".to_string());
+    let tags_json = serde_json::to_string(&tags)?;
+    let now = Utc::now().to_rfc3339();
+
+    let db = get_database()?;
+    let (updated, semver) = db.with_transaction(|tx| {
+        let category_path = category_path_for_write(tx, &category_path)?;
+        let exists = tx.query_row(
+            "SELECT 1 FROM prompts WHERE uuid = ?1",
+            [&prompt_uuid],
+            |_| Ok(())
+        ).optional()?.is_some();
+
+        if exists {
+            ensure_unlocked(tx, &prompt_uuid)?;
+            tx.execute(
+                "UPDATE prompts SET title = ?1, tags = ?2, category_path = ?3, updated_at = ?4 WHERE uuid = ?5",
+                params![&parsed.title, &tags_json, &category_path, &now, &prompt_uuid],
+            )?;
+
+            // Re-importing an unchanged body must not create a duplicate version
+            let existing_semver: Option<String> = tx.query_row(
+                "SELECT semver FROM versions WHERE prompt_uuid = ?1 AND body = ?2 AND deleted_at IS NULL LIMIT 1",
+                params![&prompt_uuid, &parsed.body],
+                |row| row.get(0)
+            ).optional()?;
+            if let Some(semver) = existing_semver {
+                return Ok((true, semver));
+            }
+        } else {
+            tx.execute(
+                "INSERT INTO prompts (uuid, title, tags, category_path, created_at, updated_at, accessed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![&prompt_uuid, &parsed.title, &tags_json, &category_path, &now, &now, &now],
+            )?;
+        }
+
+        let parent_uuid: Option<String> = tx.query_row(
+            &format!("SELECT uuid FROM versions WHERE prompt_uuid = ?1 AND deleted_at IS NULL ORDER BY {} LIMIT 1", LATEST_VERSION_FIRST),
+            [&prompt_uuid],
+            |row| row.get(0)
+        ).optional()?;
+
+        // The file's version is used unless it is taken, in which case it is bumped
+        let mut new_version = Version {
+            uuid: Uuid::now_v7().to_string(),
+            prompt_uuid: prompt_uuid.clone(),
+            semver: version.clone(),
+            body: parsed.body.clone(),
+            metadata: Some(metadata_json.clone()),
+            created_at: now.clone(),
+            parent_uuid,
+            author: current_author(tx)?,
+        };
+        insert_version_with_retry(tx, &mut new_version)?;
+
+        Ok((exists, new_version.semver))
+    })?;
+
+    save_prompt_file_with_metadata(app_handle, &parsed.title, &parsed.body, &tags, &prompt_uuid, Some(&metadata), &semver)?;
+
+    Ok((prompt_uuid, updated))
+}
+
+/// Import a directory of markdown files as prompts.
+/// Files with frontmatter keep their uuid, title, tags, version and metadata; files without
+/// frontmatter use the filename as title. A uuid matching an existing prompt updates that
+/// prompt instead of creating a duplicate. Each file is imported independently and reported.
+#[tauri::command]
+pub async fn import_markdown_directory(
+    path: String,
+    app_handle: tauri::AppHandle,
+) -> std::result::Result<ImportReport, String> {
+    log::info!("Importing markdown files from {}", path);
+
+    let root = PathBuf::from(path.trim());
+    if !root.is_dir() {
+        return Err(AppError::Path(format!("Not a directory: {}", path)).into());
+    }
+
+    let mut files = Vec::new();
+    find_markdown_files(&root, &mut files)?;
+    files.sort();
+
+    let mut report = ImportReport { imported: 0, updated: 0, failed: 0, files: Vec::new() };
+
+    for file in files {
+        let result = parse_import_file(&root, &file)
+            .and_then(|parsed| import_parsed(&parsed, &app_handle));
+
+        let file_result = match result {
+            Ok((prompt_uuid, updated)) => {
+                if updated {
+                    report.updated += 1;
+                } else {
+                    report.imported += 1;
+                }
+                ImportFileResult {
+                    path: file.to_string_lossy().into_owned(),
+                    success: true,
+                    updated,
+                    prompt_uuid: Some(prompt_uuid),
+                    error: None,
+                }
+            }
+            Err(e) => {
+                log::warn!("Failed to import {}: {}", file.display(), e);
+                report.failed += 1;
+                ImportFileResult {
+                    path: file.to_string_lossy().into_owned(),
+                    success: false,
+                    updated: false,
+                    prompt_uuid: None,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+        report.files.push(file_result);
+    }
+
+    log::info!("Import finished: {} imported, {} updated, {} failed",
+               report.imported, report.updated, report.failed);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_import_file_synthesizes_frontmatter() {
+        let root = std::env::temp_dir().join(format!("promptmaster-test-{}", Uuid::now_v7()));
+        let dir = root.join("work").join("ml");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let plain = dir.join("Summarize Paper.md");
+        std::fs::write(&plain, "Summarize {{paper}}\n").unwrap();
+        let parsed = parse_import_file(&root, &plain).unwrap();
+        assert_eq!(parsed.title, "Summarize Paper");
+        assert_eq!(parsed.body, "Summarize {{paper}}");
+        assert_eq!(parsed.category.as_deref(), Some("work/ml"));
+        assert!(parsed.uuid.is_empty());
+
+        let with_frontmatter = root.join("tagged.md");
+        std::fs::write(&with_frontmatter, "---\nuuid: \"abc\"\ntitle: \"Tagged\"\ntags: [\"a\"]\n---\n\nBody").unwrap();
+        let parsed = parse_import_file(&root, &with_frontmatter).unwrap();
+        assert_eq!(parsed.title, "Tagged");
+        assert_eq!(parsed.category, None);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -3,7 +3,7 @@ use std::io::{Write, BufWriter};
 use std::sync::Mutex;
 use chrono::Utc;
 use lazy_static::lazy_static;
-use tauri::Manager;
+use crate::paths::prompts_dir;
 use crate::error::Result;
 
 lazy_static! {
@@ -13,9 +13,8 @@ lazy_static! {
 /// Initialize the application logging system
 pub fn init_app_logging(app_handle: &tauri::AppHandle) -> Result<()> {
     // Try to get documents directory, but don't fail if it doesn't work
-    let log_file_path = match app_handle.path().document_dir() {
-        Ok(documents_dir) => {
-            let log_dir = documents_dir.join("PromptMaster");
+    let log_file_path = match prompts_dir(app_handle) {
+        Ok(log_dir) => {
             if let Err(e) = std::fs::create_dir_all(&log_dir) {
                 log::warn!("Could not create log directory: {}", e);
                 return Ok(()); // Continue without file logging
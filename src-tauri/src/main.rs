@@ -3,18 +3,34 @@
 mod error;
 mod database;
 mod db;
+mod markdown;
 mod metadata;
 mod prompts;
 mod versions;
+mod runs;
 mod watcher;
 mod security;
 mod logging;
+mod diagnostics;
+mod export;
+mod import;
+mod attachments;
+mod categories;
+mod settings;
+mod paths;
 
 use db::init_database;
-use metadata::{metadata_get, metadata_update, metadata_get_all_tags, metadata_get_model_providers, metadata_add_model_provider, metadata_remove_model_provider, regenerate_markdown_file};
-use prompts::{save_prompt, list_prompts};
-use versions::{get_latest_version, save_new_version, list_versions, list_versions_full, get_version_by_uuid, rollback_to_version};
-use watcher::start_file_watcher;
+use metadata::{metadata_get, metadata_update, metadata_bulk_update, metadata_get_all_tags, metadata_get_tag_counts, rename_tag, delete_tag, metadata_get_model_providers, metadata_add_model_provider, metadata_update_model_provider, metadata_remove_model_provider, seed_default_model_providers, get_used_models, regenerate_markdown_file};
+use prompts::{save_prompt, list_prompts, compare_prompts, set_locked, touch_prompt, get_recently_accessed, get_library_stats, reindex_file, rescan_directory};
+use versions::{get_latest_version, get_latest_versions, save_new_version, list_versions, list_versions_full, get_version_by_uuid, get_version_chain, rollback_to_version, promote_version, undo_last_version, prune_versions, restore_version, purge_trashed, get_version_plain, export_version, render_version_html, get_version_stats, get_version_diff_stats, get_recent_versions, replace_in_latest, add_version_tag, remove_version_tag, list_version_tags};
+use runs::{compare_runs, get_prompt_timeline, update_run, delete_run, get_best_version};
+use watcher::{start_file_watcher, pause_watcher, resume_watcher, get_watcher_status};
+use diagnostics::{get_database_debug_info, health_check, find_orphaned_versions, cleanup_orphaned_versions, validate_versions, repair_versions, reset_database};
+use export::{export_all_markdown, export_markdown_zip, export_category, export_runs_csv};
+use import::import_markdown_directory;
+use attachments::{add_attachment, list_attachments, remove_attachment};
+use categories::{get_category_breadcrumbs, get_all_category_paths, move_category, preview_move_category, normalize_categories, undo_last_category_operation};
+use settings::{get_settings, update_settings};
 use logging::init_app_logging;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -51,19 +67,81 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             save_prompt, 
             list_prompts, 
+            compare_prompts,
+            set_locked,
+            touch_prompt,
+            get_recently_accessed,
+            get_library_stats,
+            reindex_file,
+            rescan_directory,
+            pause_watcher,
+            resume_watcher,
+            get_watcher_status,
             get_latest_version, 
+            get_latest_versions, 
             save_new_version, 
             list_versions, 
             list_versions_full,
             get_version_by_uuid,
+            get_version_chain,
             rollback_to_version,
+            promote_version,
+            undo_last_version,
+            get_version_plain,
+            export_version,
+            render_version_html,
+            get_version_stats,
+            get_version_diff_stats,
+            get_recent_versions,
+            replace_in_latest,
+            prune_versions,
+            restore_version,
+            purge_trashed,
+            add_version_tag,
+            remove_version_tag,
+            list_version_tags,
             metadata_get,
             metadata_update,
+            metadata_bulk_update,
             metadata_get_all_tags,
+            metadata_get_tag_counts,
+            rename_tag,
+            delete_tag,
             metadata_get_model_providers,
             metadata_add_model_provider,
+            metadata_update_model_provider,
             metadata_remove_model_provider,
-            regenerate_markdown_file
+            get_used_models,
+            seed_default_model_providers,
+            regenerate_markdown_file,
+            get_database_debug_info,
+            health_check,
+            find_orphaned_versions,
+            cleanup_orphaned_versions,
+            validate_versions,
+            repair_versions,
+            reset_database,
+            compare_runs,
+            get_prompt_timeline,
+            update_run,
+            delete_run,
+            get_best_version,
+            export_all_markdown,
+            export_markdown_zip,
+            export_category,
+            export_runs_csv,
+            import_markdown_directory,
+            add_attachment,
+            list_attachments,
+            remove_attachment,
+            get_category_breadcrumbs,
+            get_all_category_paths,
+            move_category,
+            preview_move_category,
+            normalize_categories,
+            undo_last_category_operation,
+            get_settings,
+            update_settings
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,699 @@
+use crate::error::{AppError, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use lazy_static::lazy_static;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Frontmatter keys owned by the app. Any other key is a custom field; a custom field with one
+/// of these names is dropped, so the app's value always wins.
+pub const RESERVED_FRONTMATTER_KEYS: &[&str] = &[
+    "uuid", "version", "title", "tags", "created", "modified", "author",
+    "category", "category_path", "models", "notes",
+];
+
+/// Fields written to the YAML frontmatter of a prompt markdown file
+#[derive(Debug, Clone, Default)]
+pub struct Frontmatter {
+    pub uuid: String,
+    pub version: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub created: String,
+    pub modified: String,
+    /// Who created this version (see the `author` setting)
+    pub author: Option<String>,
+    pub category: Option<String>,
+    pub models: Vec<String>,
+    pub notes: Option<String>,
+    pub custom_fields: Option<serde_json::Value>,
+}
+
+/// Frontmatter and body read back from a prompt markdown file.
+/// Optional fields are None when absent so callers can decide on defaults.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedMarkdown {
+    pub uuid: String,
+    pub title: String,
+    pub version: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub category: Option<String>,
+    pub models: Option<Vec<String>>,
+    pub notes: Option<String>,
+    pub created: Option<String>,
+    pub modified: Option<String>,
+    /// Keys not in RESERVED_FRONTMATTER_KEYS, as a JSON object (empty when there are none).
+    /// None when the frontmatter could only be read by the legacy parser.
+    pub custom_fields: Option<serde_json::Value>,
+    pub body: String,
+}
+
+/// Convert a title into the filename slug used for markdown files.
+/// Accented and non-Latin titles are transliterated to ASCII first ("Café" -> "cafe",
+/// "北亰" -> "bei-jing"). Titles with no usable characters (e.g. "???") fall back to
+/// "untitled"; the frontmatter keeps the original title.
+pub fn slugify(title: &str) -> String {
+    let slug = deunicode::deunicode(title)
+        .chars()
+        .filter_map(|c| {
+            if c.is_ascii_alphanumeric() || c == ' ' || c == '-' || c == '_' {
+                Some(c.to_ascii_lowercase())
+            } else {
+                None
+            }
+        })
+        .collect::<String>()
+        .replace(' ', "-");
+
+    if slug.trim_matches(|c| c == '-' || c == '_').is_empty() {
+        "untitled".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Short fragment of a prompt UUID used to disambiguate colliding filenames.
+/// Taken from the random tail - the leading UUIDv7 digits are a timestamp shared by
+/// prompts created around the same time.
+pub fn uuid_fragment(uuid: &str) -> &str {
+    let start = uuid.len().saturating_sub(8);
+    uuid.get(start..).unwrap_or(uuid)
+}
+
+/// Whether file content's frontmatter names `prompt_uuid`. Content without parseable
+/// frontmatter belongs to no prompt.
+fn is_owned_by(content: &str, prompt_uuid: &str) -> bool {
+    parse_frontmatter(content).is_ok_and(|parsed| parsed.uuid == prompt_uuid)
+}
+
+/// Build the standard `date--slug--vX.Y.Z.md` filename, with an optional UUID fragment suffix
+pub fn build_filename(date: &str, slug: &str, version: &str, fragment: Option<&str>) -> String {
+    match fragment {
+        Some(fragment) => format!("{}--{}--v{}--{}.md", date, slug, version, fragment),
+        None => format!("{}--{}--v{}.md", date, slug, version),
+    }
+}
+
+/// Filename ending of the canonical `slug--prod.md` files written by promote_version. These
+/// mirror the prod version and are never ingested back.
+pub const PROD_FILE_SUFFIX: &str = "--prod.md";
+
+/// Resolve the prod file path for a prompt: `slug--prod.md`, or `slug--fragment--prod.md` when
+/// the plain name already belongs to a different prompt (as in resolve_version_file_path)
+pub fn resolve_prod_file_path(prompts_dir: &Path, slug: &str, prompt_uuid: &str) -> PathBuf {
+    let file_path = prompts_dir.join(format!("{}{}", slug, PROD_FILE_SUFFIX));
+
+    match fs::read_to_string(&file_path) {
+        Ok(existing) if !is_owned_by(&existing, prompt_uuid) => {
+            prompts_dir.join(format!("{}--{}{}", slug, uuid_fragment(prompt_uuid), PROD_FILE_SUFFIX))
+        }
+        _ => file_path,
+    }
+}
+
+/// Components of a filename produced by build_filename: (date, slug, version, uuid fragment)
+pub fn parse_filename(filename: &str) -> Option<(String, String, String, Option<String>)> {
+    lazy_static! {
+        static ref FILENAME_REGEX: Regex = Regex::new(r"(\d{4}-\d{2}-\d{2})--(.+)--v(\d+\.\d+\.\d+)(?:--([0-9a-f]{8}))?\.md").unwrap();
+    }
+
+    let captures = FILENAME_REGEX.captures(filename)?;
+    Some((
+        captures.get(1)?.as_str().to_string(),
+        captures.get(2)?.as_str().to_string(),
+        captures.get(3)?.as_str().to_string(),
+        captures.get(4).map(|m| m.as_str().to_string()),
+    ))
+}
+
+/// Resolve the markdown path for a version. The plain `date--slug--vX.Y.Z.md` name is used
+/// unless a file with that name already belongs to a different prompt, in which case a
+/// short UUID fragment is appended (`date--slug--vX.Y.Z--fragment.md`).
+pub fn resolve_version_file_path(
+    prompts_dir: &Path,
+    date: &str,
+    slug: &str,
+    semver: &str,
+    prompt_uuid: &str,
+) -> PathBuf {
+    let file_path = prompts_dir.join(build_filename(date, slug, semver, None));
+
+    match fs::read_to_string(&file_path) {
+        Ok(existing) if !is_owned_by(&existing, prompt_uuid) => {
+            let disambiguated = build_filename(date, slug, semver, Some(uuid_fragment(prompt_uuid)));
+            log::info!("Filename {} belongs to another prompt, using {}", file_path.display(), disambiguated);
+            prompts_dir.join(disambiguated)
+        }
+        _ => file_path,
+    }
+}
+
+/// What happened to a file during a write
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileAction {
+    Created,
+    Overwritten,
+    Unchanged,
+    Removed,
+}
+
+/// A file touched by a command, reported back to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    pub action: FileAction,
+}
+
+/// Write a file unless it already holds exactly this content, so unchanged files don't
+/// trigger the file watcher
+pub fn write_if_changed(path: &Path, content: &str) -> std::io::Result<FileChange> {
+    let action = match fs::read_to_string(path) {
+        Ok(existing) if existing == content => FileAction::Unchanged,
+        Ok(_) => FileAction::Overwritten,
+        Err(_) => FileAction::Created,
+    };
+    
+    if action != FileAction::Unchanged {
+        crate::watcher::record_app_write(path);
+        fs::write(path, content)?;
+    }
+    
+    Ok(FileChange {
+        path: path.to_string_lossy().into_owned(),
+        action,
+    })
+}
+
+/// Quote a string as a YAML double-quoted scalar (JSON strings are valid YAML)
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value))
+}
+
+/// Format a string as a YAML literal block scalar (`|-`) indented by two spaces, so multiline
+/// text stays readable and editable in the file. `|-` drops trailing newlines. An explicit
+/// indentation indicator is added when the text itself starts with spaces.
+fn yaml_block(value: &str) -> String {
+    let text = value.trim_end_matches('\n');
+    let leading_spaces = text.lines()
+        .find(|line| !line.is_empty())
+        .is_some_and(|line| line.starts_with(' '));
+    let indicator = if leading_spaces { "2" } else { "" };
+
+    let mut block = format!("|{}-", indicator);
+    for line in text.split('\n') {
+        block.push('\n');
+        if !line.is_empty() {
+            block.push_str("  ");
+            block.push_str(line);
+        }
+    }
+    block
+}
+
+/// Format a list of strings as a YAML flow sequence: ["a", "b"]
+fn yaml_list(values: &[String]) -> String {
+    let items: Vec<String> = values.iter().map(|v| yaml_string(v)).collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// Build the YAML frontmatter block (without the `---` fences)
+pub fn build_frontmatter(frontmatter: &Frontmatter) -> String {
+    let mut content = format!(
+        "uuid: {}\nversion: {}\ntitle: {}\ntags: {}\ncreated: {}\nmodified: {}",
+        yaml_string(&frontmatter.uuid),
+        yaml_string(&frontmatter.version),
+        yaml_string(&frontmatter.title),
+        yaml_list(&frontmatter.tags),
+        frontmatter.created,
+        frontmatter.modified
+    );
+
+    if let Some(ref author) = frontmatter.author {
+        content.push_str(&format!("\nauthor: {}", yaml_string(author)));
+    }
+
+    if let Some(ref category) = frontmatter.category {
+        content.push_str(&format!("\ncategory: {}", yaml_string(category)));
+    }
+
+    if !frontmatter.models.is_empty() {
+        content.push_str(&format!("\nmodels: {}", yaml_list(&frontmatter.models)));
+    }
+
+    if let Some(ref notes) = frontmatter.notes {
+        if !notes.trim().is_empty() {
+            content.push_str(&format!("\nnotes: {}", yaml_block(notes)));
+        }
+    }
+
+    // Add custom fields if present, skipping any that would shadow a field written above
+    if let Some(custom_fields) = frontmatter.custom_fields.as_ref().and_then(|fields| fields.as_object()) {
+        let custom_fields: serde_json::Map<String, serde_json::Value> = custom_fields.iter()
+            .filter(|(key, _)| !RESERVED_FRONTMATTER_KEYS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        if let Ok(custom_yaml) = serde_yaml::to_string(&custom_fields) {
+            // Remove the leading "---\n" from the YAML output
+            let custom_yaml = custom_yaml.trim_start_matches("---\n");
+            if !custom_yaml.trim().is_empty() && custom_yaml.trim() != "{}" {
+                content.push_str(&format!("\n{}", custom_yaml.trim()));
+            }
+        }
+    }
+
+    content
+}
+
+/// Build a complete markdown file: fenced frontmatter followed by the body
+pub fn build_markdown(frontmatter: &Frontmatter, body: &str) -> String {
+    format!("---\n{}\n---\n\n{}", build_frontmatter(frontmatter), body)
+}
+
+/// Rewrite the `version` and `modified` fields of a file's frontmatter in place, leaving every
+/// other line untouched. Returns None when the content has no frontmatter.
+pub fn stamp_frontmatter_version(content: &str, version: &str, modified: &str) -> Option<String> {
+    let rest = content.strip_prefix("---\n")?;
+    let end = rest.find("\n---\n")?;
+    let (frontmatter, tail) = rest.split_at(end);
+
+    let lines: Vec<String> = frontmatter.lines()
+        .map(|line| {
+            if line.starts_with("version:") {
+                format!("version: {}", yaml_string(version))
+            } else if line.starts_with("modified:") {
+                format!("modified: {}", modified)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+
+    Some(format!("---\n{}{}", lines.join("\n"), tail))
+}
+
+/// Whether a link or image URL is safe to render: relative, fragment, or http(s)/mailto.
+/// Browsers ignore whitespace and control characters inside a scheme ("java\tscript:"),
+/// so those are removed before looking at it.
+fn is_safe_url(url: &str) -> bool {
+    let cleaned: String = url.chars().filter(|c| !c.is_ascii_whitespace() && !c.is_control()).collect();
+    match cleaned.find([':', '/', '?', '#']) {
+        Some(i) if cleaned[i..].starts_with(':') => {
+            matches!(cleaned[..i].to_ascii_lowercase().as_str(), "http" | "https" | "mailto")
+        }
+        _ => true,
+    }
+}
+
+/// Render a prompt body (without frontmatter) as HTML for previews and sharing.
+/// Raw HTML in the body is escaped and shown as text rather than passed through, and links
+/// or images with unsafe URLs (javascript:, data:, ...) lose their target, so the output
+/// can't run scripts when displayed.
+pub fn render_markdown_html(body: &str) -> String {
+    use pulldown_cmark::{CowStr, Event, Options, Parser, Tag};
+
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let events = Parser::new_ext(body, options).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        Event::Start(Tag::Link { link_type, dest_url, title, id }) if !is_safe_url(&dest_url) => {
+            Event::Start(Tag::Link { link_type, dest_url: CowStr::Borrowed(""), title, id })
+        }
+        Event::Start(Tag::Image { link_type, dest_url, title, id }) if !is_safe_url(&dest_url) => {
+            Event::Start(Tag::Image { link_type, dest_url: CowStr::Borrowed(""), title, id })
+        }
+        event => event,
+    });
+
+    let mut html = String::with_capacity(body.len() * 3 / 2);
+    pulldown_cmark::html::push_html(&mut html, events);
+    html
+}
+
+/// Convert a YAML scalar to a string, accepting unquoted numbers and booleans
+fn yaml_scalar(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Convert a YAML sequence (or a comma-separated string) to a list of non-empty strings
+fn yaml_string_list(value: &serde_yaml::Value) -> Option<Vec<String>> {
+    let items: Vec<String> = match value {
+        serde_yaml::Value::Sequence(items) => items.iter().filter_map(yaml_scalar).collect(),
+        serde_yaml::Value::String(s) => s.split(',').map(|item| item.to_string()).collect(),
+        serde_yaml::Value::Null => Vec::new(),
+        _ => return None,
+    };
+
+    Some(items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect())
+}
+
+/// Collect the frontmatter entries the app doesn't own into a JSON object.
+/// Entries with non-string keys or values JSON can't hold (e.g. YAML tags) are dropped.
+fn yaml_custom_fields(mapping: &serde_yaml::Mapping) -> serde_json::Value {
+    let fields: serde_json::Map<String, serde_json::Value> = mapping.iter()
+        .filter_map(|(key, value)| {
+            let key = key.as_str()?;
+            if RESERVED_FRONTMATTER_KEYS.contains(&key) {
+                return None;
+            }
+            match serde_json::to_value(value) {
+                Ok(value) => Some((key.to_string(), value)),
+                Err(e) => {
+                    log::warn!("Ignoring frontmatter field '{}': {}", key, e);
+                    None
+                }
+            }
+        })
+        .collect();
+    serde_json::Value::Object(fields)
+}
+
+/// Parse the frontmatter with the regex extraction used before the YAML parser, for files
+/// whose frontmatter isn't valid YAML (e.g. hand-edited files with stray quotes)
+fn parse_frontmatter_legacy(frontmatter_str: &str, parsed: &mut ParsedMarkdown) {
+    lazy_static! {
+        static ref UUID_REGEX: Regex = Regex::new(r#"uuid: "([^"]+)""#).unwrap();
+        static ref TITLE_REGEX: Regex = Regex::new(r#"title: "([^"]+)""#).unwrap();
+        static ref TAGS_REGEX: Regex = Regex::new(r#"tags: \[([^\]]*)\]"#).unwrap();
+        static ref VERSION_REGEX: Regex = Regex::new(r#"version: "([^"]+)""#).unwrap();
+    }
+
+    let capture = |regex: &Regex| {
+        regex.captures(frontmatter_str)
+            .and_then(|c| c.get(1).map(|m| m.as_str().to_string()))
+    };
+
+    parsed.uuid = capture(&UUID_REGEX).unwrap_or_default();
+    parsed.title = capture(&TITLE_REGEX).unwrap_or_default();
+    parsed.version = capture(&VERSION_REGEX);
+    parsed.tags = capture(&TAGS_REGEX).map(|tags_str| {
+        tags_str
+            .split(',')
+            .filter_map(|s| {
+                let trimmed = s.trim().trim_matches('"');
+                if trimmed.is_empty() {
+                    None
+                } else {
+                    Some(trimmed.to_string())
+                }
+            })
+            .collect()
+    });
+}
+
+/// Parse a prompt markdown file into its frontmatter fields and body.
+/// uuid and title are required; everything else is optional.
+pub fn parse_frontmatter(content: &str) -> Result<ParsedMarkdown> {
+    lazy_static! {
+        static ref FRONTMATTER_REGEX: Regex = Regex::new(r"^---\n([\s\S]*?)\n---\n([\s\S]*)").unwrap();
+    }
+
+    let captures = FRONTMATTER_REGEX.captures(content)
+        .ok_or_else(|| AppError::InvalidInput("No frontmatter found".to_string()))?;
+
+    let frontmatter_str = captures.get(1).map_or("", |m| m.as_str());
+    let mut parsed = ParsedMarkdown {
+        body: captures.get(2).map_or("", |m| m.as_str()).trim().to_string(),
+        ..ParsedMarkdown::default()
+    };
+
+    match serde_yaml::from_str::<serde_yaml::Mapping>(frontmatter_str) {
+        Ok(mapping) => {
+            let get = |key: &str| mapping.get(key);
+            parsed.uuid = get("uuid").and_then(yaml_scalar).unwrap_or_default();
+            parsed.title = get("title").and_then(yaml_scalar).unwrap_or_default();
+            parsed.version = get("version").and_then(yaml_scalar);
+            parsed.tags = get("tags").and_then(yaml_string_list);
+            // Older regenerated files used `category_path` instead of `category`
+            parsed.category = get("category").or_else(|| get("category_path")).and_then(yaml_scalar);
+            parsed.models = get("models").and_then(yaml_string_list);
+            parsed.notes = get("notes").and_then(yaml_scalar);
+            parsed.created = get("created").and_then(yaml_scalar);
+            parsed.modified = get("modified").and_then(yaml_scalar);
+            parsed.custom_fields = Some(yaml_custom_fields(&mapping));
+        }
+        Err(e) => {
+            log::debug!("Frontmatter is not valid YAML ({}), falling back to field extraction", e);
+            parse_frontmatter_legacy(frontmatter_str, &mut parsed);
+        }
+    }
+
+    if parsed.uuid.trim().is_empty() {
+        return Err(AppError::InvalidInput("UUID not found in frontmatter".to_string()));
+    }
+    if parsed.title.trim().is_empty() {
+        return Err(AppError::InvalidInput("Title not found in frontmatter".to_string()));
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_prompts_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("promptmaster-test-{}", uuid::Uuid::now_v7()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn sample_frontmatter(uuid: &str) -> Frontmatter {
+        Frontmatter {
+            uuid: uuid.to_string(),
+            version: "1.0.0".to_string(),
+            title: "My \"Quoted\" Prompt".to_string(),
+            tags: vec!["alpha".to_string(), "beta".to_string()],
+            created: "2025-01-01".to_string(),
+            modified: "2025-01-02".to_string(),
+            ..Frontmatter::default()
+        }
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("My Prompt"), "my-prompt");
+        assert_eq!(slugify("Draft_v2 - final"), "draft_v2---final");
+    }
+
+    #[test]
+    fn test_slugify_transliterates_unicode() {
+        assert_eq!(slugify("Café Ideas"), "cafe-ideas");
+        assert_eq!(slugify("北亰"), "bei-jing");
+    }
+
+    #[test]
+    fn test_slugify_empty_falls_back_to_untitled() {
+        assert_eq!(slugify("???"), "untitled");
+        assert_eq!(slugify("!!! ..."), "untitled");
+        assert_eq!(slugify(" - "), "untitled");
+    }
+
+    #[test]
+    fn test_filename_round_trip() {
+        let plain = build_filename("2025-01-01", "my-prompt", "1.2.3", None);
+        assert_eq!(plain, "2025-01-01--my-prompt--v1.2.3.md");
+        assert_eq!(
+            parse_filename(&plain),
+            Some(("2025-01-01".to_string(), "my-prompt".to_string(), "1.2.3".to_string(), None))
+        );
+
+        let disambiguated = build_filename("2025-01-01", "my-prompt", "1.2.3", Some("3bf67e8e"));
+        assert_eq!(parse_filename(&disambiguated).unwrap().3, Some("3bf67e8e".to_string()));
+    }
+
+    #[test]
+    fn test_frontmatter_round_trip() {
+        let uuid = uuid::Uuid::now_v7().to_string();
+        let mut frontmatter = sample_frontmatter(&uuid);
+        frontmatter.category = Some("work/ml".to_string());
+        frontmatter.models = vec!["gpt-4o".to_string()];
+        frontmatter.notes = Some("line one\nline \"two\"".to_string());
+        frontmatter.author = Some("Ada".to_string());
+
+        let content = build_markdown(&frontmatter, "Hello {{name}}\n");
+        assert!(content.contains("\nauthor: \"Ada\"\n"), "{}", content);
+        let parsed = parse_frontmatter(&content).unwrap();
+
+        assert_eq!(parsed.uuid, uuid);
+        assert_eq!(parsed.title, "My \"Quoted\" Prompt");
+        assert_eq!(parsed.version.as_deref(), Some("1.0.0"));
+        assert_eq!(parsed.tags, Some(vec!["alpha".to_string(), "beta".to_string()]));
+        assert_eq!(parsed.category.as_deref(), Some("work/ml"));
+        assert_eq!(parsed.models, Some(vec!["gpt-4o".to_string()]));
+        assert_eq!(parsed.notes.as_deref(), Some("line one\nline \"two\""));
+        assert_eq!(parsed.created.as_deref(), Some("2025-01-01"));
+        assert_eq!(parsed.body, "Hello {{name}}");
+    }
+
+    #[test]
+    fn test_render_markdown_html_is_sanitized() {
+        let html = render_markdown_html("# Title\n\nSome **bold** text with [a link](https://example.com).\n\n| a | b |\n|---|---|\n| 1 | 2 |\n");
+        assert!(html.contains("<h1>Title</h1>"), "{}", html);
+        assert!(html.contains("<strong>bold</strong>"), "{}", html);
+        assert!(html.contains("<a href=\"https://example.com\">a link</a>"), "{}", html);
+        assert!(html.contains("<table>"), "{}", html);
+
+        let html = render_markdown_html("<script>alert(1)</script>\n\nInline <img src=x onerror=alert(1)> html");
+        assert!(!html.contains("<script"), "{}", html);
+        assert!(!html.contains("<img"), "{}", html);
+        assert!(html.contains("&lt;script&gt;"), "{}", html);
+
+        let html = render_markdown_html("[x](javascript:alert(1)) [y](JaVa\tScRiPt:alert(1)) ![z](data:text/html;base64,AAAA) [ok](/relative#frag)");
+        assert!(!html.to_lowercase().contains("javascript"), "{}", html);
+        assert!(!html.contains("data:"), "{}", html);
+        assert!(html.contains("href=\"/relative#frag\""), "{}", html);
+    }
+
+    #[test]
+    fn test_custom_fields_round_trip() {
+        let uuid = uuid::Uuid::now_v7().to_string();
+        // A hand-annotated file: custom keys mixed in with the app's own
+        let content = format!(
+            "---\nuuid: \"{}\"\nproject: foo\nversion: \"1.0.0\"\ntitle: \"T\"\npriority: 2\nreview:\n  owner: ada\n---\n\nBody",
+            uuid
+        );
+        let parsed = parse_frontmatter(&content).unwrap();
+        let custom_fields = parsed.custom_fields.unwrap();
+        assert_eq!(custom_fields, serde_json::json!({ "project": "foo", "priority": 2, "review": { "owner": "ada" } }));
+
+        let mut frontmatter = sample_frontmatter(&uuid);
+        // Custom fields never override the app's own fields
+        let mut with_collision = custom_fields.clone();
+        with_collision["title"] = "Injected".into();
+        frontmatter.custom_fields = Some(with_collision);
+        let rebuilt = build_markdown(&frontmatter, "Body");
+        assert!(rebuilt.contains("\nproject: foo\n"), "{}", rebuilt);
+        assert!(!rebuilt.contains("Injected"), "{}", rebuilt);
+
+        let reparsed = parse_frontmatter(&rebuilt).unwrap();
+        assert_eq!(reparsed.title, "My \"Quoted\" Prompt");
+        assert_eq!(reparsed.custom_fields, Some(custom_fields));
+
+        // No custom keys reads back as an empty object
+        let plain = parse_frontmatter(&build_markdown(&sample_frontmatter(&uuid), "Body")).unwrap();
+        assert_eq!(plain.custom_fields, Some(serde_json::json!({})));
+    }
+
+    #[test]
+    fn test_notes_written_as_block_scalar() {
+        let uuid = uuid::Uuid::now_v7().to_string();
+        let mut frontmatter = sample_frontmatter(&uuid);
+        frontmatter.notes = Some("Works best with:\n- low temperature\n\n---\nversion: not a field".to_string());
+        let content = build_markdown(&frontmatter, "Body");
+        assert!(content.contains("notes: |-\n  Works best with:\n  - low temperature\n\n  ---\n"), "{}", content);
+
+        let parsed = parse_frontmatter(&content).unwrap();
+        assert_eq!(parsed.notes, frontmatter.notes);
+        assert_eq!(parsed.version.as_deref(), Some("1.0.0"));
+        assert_eq!(parsed.body, "Body");
+
+        for notes in ["  indented first line\nsecond", "\n\nleading blanks", "tab\tand: colon # hash"] {
+            frontmatter.notes = Some(notes.to_string());
+            let parsed = parse_frontmatter(&build_markdown(&frontmatter, "Body")).unwrap();
+            assert_eq!(parsed.notes.as_deref(), Some(notes));
+        }
+    }
+
+    #[test]
+    fn test_parse_frontmatter_legacy_fallback() {
+        // Unbalanced quote in an unknown field makes this invalid YAML
+        let content = "---\nuuid: \"abc\"\nversion: \"1.0.1\"\ntitle: \"Legacy\"\ntags: [\"a\", \"b\"]\nnote: \"broken\n---\n\nBody";
+        let parsed = parse_frontmatter(content).unwrap();
+
+        assert_eq!(parsed.uuid, "abc");
+        assert_eq!(parsed.title, "Legacy");
+        assert_eq!(parsed.version.as_deref(), Some("1.0.1"));
+        assert_eq!(parsed.tags, Some(vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(parsed.body, "Body");
+    }
+
+    #[test]
+    fn test_parse_frontmatter_requires_uuid_and_title() {
+        assert!(parse_frontmatter("no frontmatter here").is_err());
+        assert!(parse_frontmatter("---\ntitle: \"No UUID\"\n---\n\nBody").is_err());
+        assert!(parse_frontmatter("---\nuuid: \"abc\"\n---\n\nBody").is_err());
+    }
+
+    #[test]
+    fn test_write_if_changed() {
+        let dir = temp_prompts_dir();
+        let path = dir.join("prompt.md");
+
+        assert_eq!(write_if_changed(&path, "one").unwrap().action, FileAction::Created);
+        assert_eq!(write_if_changed(&path, "one").unwrap().action, FileAction::Unchanged);
+        assert_eq!(write_if_changed(&path, "two").unwrap().action, FileAction::Overwritten);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_same_titled_prompts_get_distinct_files() {
+        let dir = temp_prompts_dir();
+        let uuid_a = uuid::Uuid::now_v7().to_string();
+        let uuid_b = uuid::Uuid::now_v7().to_string();
+
+        // First prompt takes the plain filename
+        let path_a = resolve_version_file_path(&dir, "2025-01-01", "my-prompt", "1.0.0", &uuid_a);
+        assert_eq!(path_a.file_name().unwrap(), "2025-01-01--my-prompt--v1.0.0.md");
+        fs::write(&path_a, build_markdown(&sample_frontmatter(&uuid_a), "body a")).unwrap();
+
+        // Re-syncing the same prompt keeps its file
+        assert_eq!(resolve_version_file_path(&dir, "2025-01-01", "my-prompt", "1.0.0", &uuid_a), path_a);
+
+        // A different prompt with the same slug is disambiguated instead of clobbering
+        let path_b = resolve_version_file_path(&dir, "2025-01-01", "my-prompt", "1.0.0", &uuid_b);
+        assert_ne!(path_a, path_b);
+        assert_eq!(
+            path_b.file_name().unwrap().to_string_lossy(),
+            format!("2025-01-01--my-prompt--v1.0.0--{}.md", uuid_fragment(&uuid_b))
+        );
+        fs::write(&path_b, build_markdown(&sample_frontmatter(&uuid_b), "body b")).unwrap();
+
+        assert!(fs::read_to_string(&path_a).unwrap().contains("body a"));
+        assert!(fs::read_to_string(&path_b).unwrap().contains("body b"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_ownership_reads_the_frontmatter_uuid() {
+        let dir = temp_prompts_dir();
+        let uuid_a = uuid::Uuid::now_v7().to_string();
+        let uuid_b = uuid::Uuid::now_v7().to_string();
+        let plain = dir.join("2025-01-01--my-prompt--v1.0.0.md");
+
+        // Hand-edited YAML with an unquoted uuid still belongs to its prompt
+        fs::write(&plain, format!("---\nuuid: {}\ntitle: T\n---\n\nbody", uuid_a)).unwrap();
+        assert_eq!(resolve_version_file_path(&dir, "2025-01-01", "my-prompt", "1.0.0", &uuid_a), plain);
+
+        // Another prompt's file that quotes this uuid in its body does not
+        fs::write(&plain, format!("---\nuuid: \"{}\"\ntitle: T\n---\n\nuuid: \"{}\"", uuid_b, uuid_a)).unwrap();
+        assert_ne!(resolve_version_file_path(&dir, "2025-01-01", "my-prompt", "1.0.0", &uuid_a), plain);
+
+        let prod = dir.join("my-prompt--prod.md");
+        fs::write(&prod, format!("---\nuuid: '{}'\ntitle: T\n---\n\nbody", uuid_a)).unwrap();
+        assert_eq!(resolve_prod_file_path(&dir, "my-prompt", &uuid_a), prod);
+        assert_ne!(resolve_prod_file_path(&dir, "my-prompt", &uuid_b), prod);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_stamp_frontmatter_version() {
+        let content = "---\nuuid: \"abc\"\nversion: \"1.0.0\"\ntitle: \"T\"\nmodified: 2025-01-01\nauthor: me\n---\n\nBody\n---\nversion: keep\n";
+        let stamped = stamp_frontmatter_version(content, "1.0.1", "2025-02-03").unwrap();
+        assert_eq!(stamped, "---\nuuid: \"abc\"\nversion: \"1.0.1\"\ntitle: \"T\"\nmodified: 2025-02-03\nauthor: me\n---\n\nBody\n---\nversion: keep\n");
+
+        assert_eq!(stamp_frontmatter_version("No frontmatter", "1.0.1", "2025-02-03"), None);
+    }
+}
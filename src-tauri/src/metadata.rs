@@ -1,9 +1,23 @@
 use serde::{Deserialize, Serialize};
 use serde_json;
+use crate::categories::category_path_for_write;
 use crate::db::get_database;
 use crate::error::{AppError, Result};
+use crate::prompts::ensure_unlocked;
+use crate::markdown::{build_markdown, resolve_version_file_path, slugify, write_if_changed, FileAction, FileChange, Frontmatter};
+use crate::security::{validate_tag, validate_uuid};
 use rusqlite::{params, OptionalExtension};
-use tauri::Manager;
+use std::collections::HashMap;
+use std::path::Path;
+use crate::paths::prompts_dir;
+use crate::versions::LATEST_VERSION_FIRST;
+
+/// Maximum serialized size of custom_fields in bytes
+const MAX_CUSTOM_FIELDS_SIZE: usize = 50_000;
+/// Maximum number of top-level keys in custom_fields
+const MAX_CUSTOM_FIELDS_KEYS: usize = 50;
+/// Maximum serialized size of the model parameters object in bytes
+const MAX_PARAMETERS_SIZE: usize = 10_000;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PromptMetadata {
@@ -13,6 +27,10 @@ pub struct PromptMetadata {
     pub category_path: Option<String>,
     pub notes: Option<String>,
     pub custom_fields: Option<serde_json::Value>,
+    /// Model the version was tuned against (a model_providers model_id)
+    pub target_model: Option<String>,
+    /// Runtime parameters used with target_model, e.g. {"temperature": 0.2, "top_p": 0.9}
+    pub parameters: Option<serde_json::Value>,
 }
 
 impl Default for PromptMetadata {
@@ -24,10 +42,31 @@ impl Default for PromptMetadata {
             category_path: Some("Uncategorized".to_string()),
             notes: None,
             custom_fields: None,
+            target_model: None,
+            parameters: None,
         }
     }
 }
 
+/// Validate a slash-separated category path such as "work/ml"
+pub fn validate_category_path(category_path: &str) -> Result<()> {
+    if category_path.len() > 255 {
+        return Err(AppError::Validation("Category path cannot exceed 255 characters".to_string()));
+    }
+    // Validate printable ASCII only for security
+    if !category_path.chars().all(|c| c.is_ascii() && !c.is_control()) {
+        return Err(AppError::Validation("Category path must contain only printable ASCII characters".to_string()));
+    }
+    // Empty segments ("/foo", "foo/", "foo//bar") produce phantom nodes in the category tree
+    if category_path.starts_with('/') || category_path.ends_with('/') {
+        return Err(AppError::Validation("Category path cannot start or end with '/'".to_string()));
+    }
+    if category_path.split('/').any(|segment| segment.trim().is_empty()) {
+        return Err(AppError::Validation("Category path cannot contain empty segments".to_string()));
+    }
+    Ok(())
+}
+
 impl PromptMetadata {
     /// Parse metadata from JSON string
     pub fn from_json(json_str: &str) -> Result<Self> {
@@ -61,6 +100,12 @@ impl PromptMetadata {
         if other.custom_fields.is_some() {
             self.custom_fields = other.custom_fields.clone();
         }
+        if other.target_model.is_some() {
+            self.target_model = other.target_model.clone();
+        }
+        if other.parameters.is_some() {
+            self.parameters = other.parameters.clone();
+        }
     }
 
     /// Validate metadata constraints
@@ -92,13 +137,7 @@ impl PromptMetadata {
 
         // Validate category path
         if let Some(ref category_path) = self.category_path {
-            if category_path.len() > 255 {
-                return Err(AppError::Validation("Category path cannot exceed 255 characters".to_string()));
-            }
-            // Validate printable ASCII only for security
-            if !category_path.chars().all(|c| c.is_ascii() && !c.is_control()) {
-                return Err(AppError::Validation("Category path must contain only printable ASCII characters".to_string()));
-            }
+            validate_category_path(category_path)?;
         }
 
         // Validate notes
@@ -108,6 +147,45 @@ impl PromptMetadata {
             }
         }
 
+        // Validate custom fields
+        if let Some(ref custom_fields) = self.custom_fields {
+            let fields = custom_fields.as_object()
+                .ok_or_else(|| AppError::Validation("Custom fields must be a JSON object".to_string()))?;
+            if fields.len() > MAX_CUSTOM_FIELDS_KEYS {
+                return Err(AppError::Validation(format!(
+                    "Custom fields cannot have more than {} keys", MAX_CUSTOM_FIELDS_KEYS
+                )));
+            }
+            let serialized_size = serde_json::to_string(custom_fields)
+                .map_err(|e| AppError::Validation(format!("Failed to serialize custom fields: {}", e)))?
+                .len();
+            if serialized_size > MAX_CUSTOM_FIELDS_SIZE {
+                return Err(AppError::Validation(format!(
+                    "Custom fields cannot exceed {} bytes when serialized", MAX_CUSTOM_FIELDS_SIZE
+                )));
+            }
+        }
+
+        // Validate target model and its parameters
+        if let Some(ref target_model) = self.target_model {
+            if target_model.trim().is_empty() || target_model.len() > 100 {
+                return Err(AppError::Validation("Target model must be 1-100 characters".to_string()));
+            }
+        }
+        if let Some(ref parameters) = self.parameters {
+            if !parameters.is_object() {
+                return Err(AppError::Validation("Parameters must be a JSON object".to_string()));
+            }
+            let serialized_size = serde_json::to_string(parameters)
+                .map_err(|e| AppError::Validation(format!("Failed to serialize parameters: {}", e)))?
+                .len();
+            if serialized_size > MAX_PARAMETERS_SIZE {
+                return Err(AppError::Validation(format!(
+                    "Parameters cannot exceed {} bytes when serialized", MAX_PARAMETERS_SIZE
+                )));
+            }
+        }
+
         Ok(())
     }
 }
@@ -143,9 +221,142 @@ pub async fn metadata_get(version_uuid: String) -> std::result::Result<PromptMet
     }
 }
 
-/// Update metadata for a specific version
+/// Response for metadata_update: the merged metadata plus any soft-check warnings
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MetadataUpdateResponse {
+    #[serde(flatten)]
+    pub metadata: PromptMetadata,
+    /// Models referenced in the payload that are not active model providers
+    pub unknown_models: Vec<String>,
+    /// Prompt the version belongs to, so the frontend knows which prompt to refresh
+    pub prompt_uuid: Option<String>,
+    /// Whether the update changed the prompt's title or tags (the fields shown in prompt lists)
+    pub prompt_updated: bool,
+}
+
+/// Result of apply_metadata_update
+struct AppliedMetadata {
+    metadata: PromptMetadata,
+    prompt_uuid: Option<String>,
+    prompt_updated: bool,
+}
+
+/// Return the models that don't exist as an active model_id in model_providers
+fn find_unknown_models(conn: &rusqlite::Connection, models: &[String]) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT COUNT(*) FROM model_providers WHERE model_id = ?1 AND active = 1"
+    )?;
+
+    let mut unknown = Vec::new();
+    for model in models {
+        let count: i64 = stmt.query_row(params![model], |row| row.get(0))?;
+        if count == 0 && !unknown.contains(model) {
+            unknown.push(model.clone());
+        }
+    }
+
+    Ok(unknown)
+}
+
+/// Merge new metadata into a version inside an open transaction and sync the extracted
+/// fields (title, tags, category) to the prompts table
+fn apply_metadata_update(
+    tx: &rusqlite::Transaction,
+    version_uuid: &str,
+    new_metadata: &PromptMetadata,
+) -> Result<AppliedMetadata> {
+    let prompt_uuid: Option<String> = tx.query_row(
+        "SELECT prompt_uuid FROM versions WHERE uuid = ?1",
+        params![version_uuid],
+        |row| row.get(0)
+    ).optional()?;
+    if let Some(ref prompt_uuid) = prompt_uuid {
+        ensure_unlocked(tx, prompt_uuid)?;
+    }
+    
+    // Get existing metadata
+    let existing_metadata_json: Option<String> = match tx.query_row(
+        "SELECT metadata FROM versions WHERE uuid = ?1",
+        params![version_uuid],
+        |row| {
+            let metadata: Option<String> = row.get(0)?;
+            Ok(metadata)
+        }
+    ).optional()? {
+        Some(Some(json_str)) => Some(json_str),
+        Some(None) => None,
+        None => None,
+    };
+    
+    // Merge with existing metadata
+    let mut final_metadata = match existing_metadata_json {
+        Some(json_str) => PromptMetadata::from_json(&json_str)
+            .unwrap_or_else(|_| PromptMetadata::default()),
+        None => PromptMetadata::default(),
+    };
+    
+    final_metadata.merge_with(new_metadata);
+    if let Some(ref mut category_path) = final_metadata.category_path {
+        *category_path = category_path_for_write(tx, category_path)?;
+    }
+    
+    // Convert to JSON
+    let final_json = final_metadata.to_json()?;
+    
+    // Update the database
+    tx.execute(
+        "UPDATE versions SET metadata = ?1 WHERE uuid = ?2",
+        params![final_json, version_uuid]
+    )?;
+    
+    let prompt_title_and_tags = || -> rusqlite::Result<Option<(String, Option<String>)>> {
+        tx.query_row(
+            "SELECT title, tags FROM prompts WHERE uuid = (SELECT prompt_uuid FROM versions WHERE uuid = ?1)",
+            params![version_uuid],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        ).optional()
+    };
+    let before = prompt_title_and_tags()?;
+    
+    // Also update the prompts table with extracted fields for easier querying
+    if let Some(ref title) = final_metadata.title {
+        tx.execute(
+            "UPDATE prompts SET title = ?1, updated_at = datetime('now') WHERE uuid = (SELECT prompt_uuid FROM versions WHERE uuid = ?2)",
+            params![title, version_uuid]
+        )?;
+    }
+    
+    if let Some(ref tags) = final_metadata.tags {
+        let tags_json = serde_json::to_string(tags)?;
+        tx.execute(
+            "UPDATE prompts SET tags = ?1, updated_at = datetime('now') WHERE uuid = (SELECT prompt_uuid FROM versions WHERE uuid = ?2)",
+            params![tags_json, version_uuid]
+        )?;
+    }
+    
+    if let Some(ref category_path) = final_metadata.category_path {
+        tx.execute(
+            "UPDATE prompts SET category_path = ?1, updated_at = datetime('now') WHERE uuid = (SELECT prompt_uuid FROM versions WHERE uuid = ?2)",
+            params![category_path, version_uuid]
+        )?;
+    }
+    
+    Ok(AppliedMetadata {
+        metadata: final_metadata,
+        prompt_uuid,
+        prompt_updated: prompt_title_and_tags()? != before,
+    })
+}
+
+/// Update metadata for a specific version.
+/// Models are checked against the active model providers: unknown models are reported in the
+/// response by default, or rejected with a validation error when `strict_models` is true.
 #[tauri::command]
-pub async fn metadata_update(version_uuid: String, payload_json: String) -> std::result::Result<PromptMetadata, String> {
+pub async fn metadata_update(
+    version_uuid: String,
+    payload_json: String,
+    strict_models: Option<bool>,
+) -> std::result::Result<MetadataUpdateResponse, String> {
     log::info!("Updating metadata for version: {}", version_uuid);
     log::debug!("Payload JSON: {}", payload_json);
     
@@ -157,74 +368,116 @@ pub async fn metadata_update(version_uuid: String, payload_json: String) -> std:
     
     let db = get_database()?;
     
-    let final_metadata = db.with_transaction(|tx| {
-        // Get existing metadata
-        let existing_metadata_json: Option<String> = match tx.query_row(
-            "SELECT metadata FROM versions WHERE uuid = ?1",
-            params![version_uuid],
-            |row| {
-                let metadata: Option<String> = row.get(0)?;
-                Ok(metadata)
-            }
-        ).optional()? {
-            Some(Some(json_str)) => Some(json_str),
-            Some(None) => None,
-            None => None,
-        };
-        
-        // Merge with existing metadata
-        let mut final_metadata = match existing_metadata_json {
-            Some(json_str) => PromptMetadata::from_json(&json_str)
-                .unwrap_or_else(|_| PromptMetadata::default()),
-            None => PromptMetadata::default(),
-        };
-        
-        final_metadata.merge_with(&new_metadata);
-        
-        // Convert to JSON
-        let final_json = final_metadata.to_json()
-            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-        
-        // Update the database
-        tx.execute(
-            "UPDATE versions SET metadata = ?1 WHERE uuid = ?2",
-            params![final_json, version_uuid]
-        )?;
-        
-        // Also update the prompts table with extracted fields for easier querying
-        if let Some(ref title) = final_metadata.title {
-            tx.execute(
-                "UPDATE prompts SET title = ?1, updated_at = datetime('now') WHERE uuid = (SELECT prompt_uuid FROM versions WHERE uuid = ?2)",
-                params![title, version_uuid]
-            )?;
-        }
-        
-        if let Some(ref tags) = final_metadata.tags {
-            let tags_json = serde_json::to_string(tags)
-                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-            tx.execute(
-                "UPDATE prompts SET tags = ?1, updated_at = datetime('now') WHERE uuid = (SELECT prompt_uuid FROM versions WHERE uuid = ?2)",
-                params![tags_json, version_uuid]
-            )?;
+    // Check referenced models against registered providers
+    let unknown_models = match new_metadata.models {
+        Some(ref models) => db.with_connection(|conn| find_unknown_models(conn, models))?,
+        None => Vec::new(),
+    };
+    
+    if !unknown_models.is_empty() {
+        if strict_models.unwrap_or(false) {
+            return Err(AppError::Validation(format!(
+                "Unknown models (not registered as active model providers): {}",
+                unknown_models.join(", ")
+            )).into());
         }
-        
-        if let Some(ref category_path) = final_metadata.category_path {
-            tx.execute(
-                "UPDATE prompts SET category_path = ?1, updated_at = datetime('now') WHERE uuid = (SELECT prompt_uuid FROM versions WHERE uuid = ?2)",
-                params![category_path, version_uuid]
+        log::warn!("Metadata for version {} references unknown models: {:?}", version_uuid, unknown_models);
+    }
+    
+    let applied = db.with_transaction(|tx| apply_metadata_update(tx, &version_uuid, &new_metadata))?;
+    
+    log::info!("Successfully updated metadata for version: {}", version_uuid);
+    Ok(MetadataUpdateResponse {
+        metadata: applied.metadata,
+        unknown_models,
+        prompt_uuid: applied.prompt_uuid,
+        prompt_updated: applied.prompt_updated,
+    })
+}
+
+/// Outcome of a single version within metadata_bulk_update
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BulkUpdateResult {
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Apply the same metadata merge to several versions in one transaction.
+/// Each version runs inside its own savepoint so a failing version is rolled back and
+/// reported without aborting the rest of the batch.
+#[tauri::command]
+pub async fn metadata_bulk_update(
+    version_uuids: Vec<String>,
+    payload_json: String,
+) -> std::result::Result<HashMap<String, BulkUpdateResult>, String> {
+    log::info!("Bulk updating metadata for {} versions", version_uuids.len());
+
+    if version_uuids.is_empty() {
+        return Err("At least one version UUID is required".to_string());
+    }
+    if version_uuids.len() > 1000 {
+        return Err("Too many versions in one batch (max 1000)".to_string());
+    }
+
+    // Parse and validate the payload once - it is the same for every version
+    let new_metadata = PromptMetadata::from_json(&payload_json)?;
+    new_metadata.validate()?;
+
+    let db = get_database()?;
+
+    let results = db.with_transaction(|tx| {
+        let mut results = HashMap::new();
+
+        for version_uuid in &version_uuids {
+            if let Err(e) = validate_uuid(version_uuid) {
+                results.insert(version_uuid.clone(), BulkUpdateResult {
+                    success: false,
+                    error: Some(e.to_string()),
+                });
+                continue;
+            }
+
+            let exists: i64 = tx.query_row(
+                "SELECT COUNT(*) FROM versions WHERE uuid = ?1",
+                params![version_uuid],
+                |row| row.get(0)
             )?;
+            if exists == 0 {
+                results.insert(version_uuid.clone(), BulkUpdateResult {
+                    success: false,
+                    error: Some("Version not found".to_string()),
+                });
+                continue;
+            }
+
+            tx.execute_batch("SAVEPOINT metadata_bulk_item")?;
+            let result = match apply_metadata_update(tx, version_uuid, &new_metadata) {
+                Ok(_) => {
+                    tx.execute_batch("RELEASE metadata_bulk_item")?;
+                    BulkUpdateResult { success: true, error: None }
+                }
+                Err(e) => {
+                    log::warn!("Bulk metadata update failed for version {}: {}", version_uuid, e);
+                    tx.execute_batch("ROLLBACK TO metadata_bulk_item; RELEASE metadata_bulk_item")?;
+                    BulkUpdateResult { success: false, error: Some(e.to_string()) }
+                }
+            };
+            results.insert(version_uuid.clone(), result);
         }
-        
-        Ok(final_metadata)
+
+        Ok(results)
     })?;
-    
-    log::info!("Successfully updated metadata for version: {}", version_uuid);
-    Ok(final_metadata)
+
+    let succeeded = results.values().filter(|r| r.success).count();
+    log::info!("Bulk metadata update finished: {} succeeded, {} failed", succeeded, results.len() - succeeded);
+    Ok(results)
 }
 
-/// Regenerate markdown file after metadata update
+/// Regenerate markdown file after metadata update.
+/// Returns the files touched: the regenerated file (created, overwritten, or unchanged when the
+/// content already matches) and any legacy-named file that was removed.
 #[tauri::command]
-pub async fn regenerate_markdown_file(app_handle: tauri::AppHandle, prompt_uuid: String) -> std::result::Result<(), String> {
+pub async fn regenerate_markdown_file(app_handle: tauri::AppHandle, prompt_uuid: String) -> std::result::Result<Vec<FileChange>, String> {
     log::info!("Regenerating markdown file for prompt: {}", prompt_uuid);
     
     let db = get_database()?;
@@ -250,119 +503,101 @@ pub async fn regenerate_markdown_file(app_handle: tauri::AppHandle, prompt_uuid:
         )?;
         
         // Get latest version with metadata
-        let mut stmt = conn.prepare(
-            "SELECT semver, body, metadata FROM versions WHERE prompt_uuid = ?1 ORDER BY created_at DESC LIMIT 1"
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT semver, body, metadata, author FROM versions WHERE prompt_uuid = ?1 AND deleted_at IS NULL ORDER BY {} LIMIT 1",
+            LATEST_VERSION_FIRST
+        ))?;
         
-        let (version, body, metadata) = stmt.query_row(
+        let (version, body, metadata, author) = stmt.query_row(
             [&prompt_uuid],
             |row| {
                 Ok((
                     row.get::<_, String>(0)?,
                     row.get::<_, String>(1)?,
                     row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
                 ))
             }
         )?;
         
-        Ok(((title, tags_json, category_path, created_at, updated_at), (version, body), metadata))
+        Ok(((title, tags_json, category_path, created_at, updated_at), (version, body, author), metadata))
     })?;
     
     // Generate filename and content
     let (title, tags_json, category_path, created_at, _updated_at) = prompt_data;
-    let (version, body) = latest_version;
+    let (version, body, author) = latest_version;
     
-    // Parse metadata to extract notes and models
-    let (notes, models) = if let Some(metadata_json) = metadata {
+    // Parse metadata to extract notes, models and custom fields
+    let (notes, models, custom_fields) = if let Some(metadata_json) = metadata {
         match PromptMetadata::from_json(&metadata_json) {
             Ok(metadata) => (
                 metadata.notes.unwrap_or_default(),
                 metadata.models.unwrap_or_default(),
+                metadata.custom_fields,
             ),
-            Err(_) => (String::new(), Vec::new()),
+            Err(_) => (String::new(), Vec::new(), None),
         }
     } else {
-        (String::new(), Vec::new())
+        (String::new(), Vec::new(), None)
     };
     
     let created_date = created_at.split('T').next().unwrap_or("unknown");
-    let modified_date = chrono::Utc::now().format("%Y-%m-%d");
-    
-    let filename = format!(
-        "{}--{}--v{}.md",
-        created_date,
-        title.to_lowercase()
-            .replace(' ', "-")
-            .chars()
-            .filter(|c| c.is_alphanumeric() || *c == '-')
-            .collect::<String>(),
-        version
-    );
-    
-    // Delete old file if it exists with different name
-    let old_filename = format!(
-        "{}-{}-{}.md",
-        created_date,
-        title.to_lowercase()
-            .replace(' ', "-")
-            .chars()
-            .filter(|c| c.is_alphanumeric() || *c == '-')
-            .collect::<String>(),
-        version
-    );
-    
-    let prompts_dir = app_handle.path().document_dir()
-        .map_err(|e| format!("Failed to get documents directory: {}", e))?
-        .join("PromptMaster");
+    let modified_date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let tags: Vec<String> = serde_json::from_str(&tags_json).unwrap_or_default();
+    
+    let prompts_dir = prompts_dir(&app_handle)
+        .map_err(|e| format!("Failed to get documents directory: {}", e))?;
+    
+    let frontmatter = Frontmatter {
+        uuid: prompt_uuid.clone(),
+        version,
+        title,
+        tags,
+        created: created_date.to_string(),
+        modified: modified_date,
+        author,
+        category: Some(category_path),
+        models,
+        notes: Some(notes),
+        custom_fields,
+    };
+    
+    let changes = write_regenerated_file(&prompts_dir, &frontmatter, &body)?;
+    Ok(changes)
+}
+
+/// Write a regenerated prompt file, removing the pre-`--` `date-slug-version.md` name if present.
+/// The file is named like every other writer's (resolve_version_file_path), so a same-titled
+/// prompt saved the same day keeps its file.
+fn write_regenerated_file(prompts_dir: &Path, frontmatter: &Frontmatter, body: &str) -> Result<Vec<FileChange>> {
+    let slug = slugify(&frontmatter.title);
+    let mut changes = Vec::new();
     
     // Remove old file if it exists
+    let old_filename = format!("{}-{}-{}.md", frontmatter.created, slug, frontmatter.version);
     let old_file_path = prompts_dir.join(&old_filename);
     if old_file_path.exists() {
-        std::fs::remove_file(&old_file_path)
-            .map_err(|e| format!("Failed to remove old file: {}", e))?;
+        std::fs::remove_file(&old_file_path)?;
         log::info!("Removed old file: {}", old_filename);
+        changes.push(FileChange {
+            path: old_file_path.to_string_lossy().into_owned(),
+            action: FileAction::Removed,
+        });
     }
     
-    let models_json = serde_json::to_string(&models).unwrap_or_else(|_| "[]".to_string());
-    
-    let frontmatter = if notes.is_empty() {
-        format!(
-            "---\nuuid: \"{}\"\nversion: \"{}\"\ntitle: \"{}\"\ntags: {}\nmodels: {}\ncategory_path: \"{}\"\ncreated: {}\nmodified: {}\n---\n\n{}",
-            prompt_uuid,
-            version,
-            title,
-            tags_json,
-            models_json,
-            category_path,
-            created_date,
-            modified_date,
-            body
-        )
-    } else {
-        format!(
-            "---\nuuid: \"{}\"\nversion: \"{}\"\ntitle: \"{}\"\ntags: {}\nmodels: {}\ncategory_path: \"{}\"\nnotes: \"{}\"\ncreated: {}\nmodified: {}\n---\n\n{}",
-            prompt_uuid,
-            version,
-            title,
-            tags_json,
-            models_json,
-            category_path,
-            notes,
-            created_date,
-            modified_date,
-            body
-        )
-    };
-    
     // Write the file
-    std::fs::create_dir_all(&prompts_dir)
-        .map_err(|e| format!("Failed to create directory: {}", e))?;
+    std::fs::create_dir_all(prompts_dir)?;
+    let file_path = resolve_version_file_path(prompts_dir, &frontmatter.created, &slug, &frontmatter.version, &frontmatter.uuid);
+    let change = write_if_changed(&file_path, &build_markdown(frontmatter, body))?;
     
-    std::fs::write(prompts_dir.join(&filename), frontmatter)
-        .map_err(|e| format!("Failed to write file: {}", e))?;
+    if change.action == FileAction::Unchanged {
+        log::debug!("Skipping file write - content unchanged: {}", file_path.display());
+    } else {
+        log::info!("Successfully regenerated markdown file: {}", file_path.display());
+    }
+    changes.push(change);
     
-    log::info!("Successfully regenerated markdown file: {}", filename);
-    Ok(())
+    Ok(changes)
 }
 
 /// Get all unique tags from the database for autocomplete
@@ -404,6 +639,200 @@ pub async fn metadata_get_all_tags() -> std::result::Result<Vec<String>, String>
     Ok(tags)
 }
 
+/// Get each tag with the number of prompts using it, most used first
+#[tauri::command]
+pub async fn metadata_get_tag_counts() -> std::result::Result<Vec<(String, i64)>, String> {
+    log::info!("Getting tag usage counts");
+    
+    let db = get_database()?;
+    
+    let counts = db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT tags FROM prompts WHERE tags IS NOT NULL AND tags != ''"
+        )?;
+        
+        let tag_rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        
+        let mut counts: HashMap<String, i64> = HashMap::new();
+        
+        for tags_json in tag_rows {
+            let tags_json = tags_json?;
+            if let Ok(tags_vec) = serde_json::from_str::<Vec<String>>(&tags_json) {
+                // Count each tag once per prompt, case-insensitively like metadata_get_all_tags
+                let unique: std::collections::HashSet<String> = tags_vec
+                    .into_iter()
+                    .map(|tag| tag.to_lowercase())
+                    .collect();
+                for tag in unique {
+                    *counts.entry(tag).or_insert(0) += 1;
+                }
+            }
+        }
+        
+        let mut sorted_counts: Vec<(String, i64)> = counts.into_iter().collect();
+        sorted_counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        
+        Ok(sorted_counts)
+    })?;
+    
+    log::debug!("Found usage counts for {} tags", counts.len());
+    Ok(counts)
+}
+
+/// Result of rename_tag and delete_tag
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct TagRewrite {
+    /// Prompts whose tags were changed
+    pub changed: usize,
+    /// Locked prompts that carry the tag and were left unchanged
+    pub skipped_locked: Vec<String>,
+}
+
+/// Apply `rewrite` to the `tags` array in the metadata of every version of a prompt, so later
+/// metadata edits and saves, which merge into that JSON, don't bring the old tags back.
+/// Other metadata fields are left as they are.
+fn rewrite_version_metadata_tags<F>(tx: &rusqlite::Connection, prompt_uuid: &str, rewrite: &mut F) -> Result<()>
+where
+    F: FnMut(&[String]) -> Option<Vec<String>>,
+{
+    let versions: Vec<(String, String)> = {
+        let mut stmt = tx.prepare("SELECT uuid, metadata FROM versions WHERE prompt_uuid = ?1 AND metadata IS NOT NULL")?;
+        let rows = stmt.query_map([prompt_uuid], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+
+    for (version_uuid, metadata_json) in versions {
+        let Ok(mut metadata) = serde_json::from_str::<serde_json::Value>(&metadata_json) else {
+            continue;
+        };
+        let tags: Option<Vec<String>> = metadata.get("tags").and_then(|tags| serde_json::from_value(tags.clone()).ok());
+        if let Some(new_tags) = tags.and_then(|tags| rewrite(&tags)) {
+            metadata["tags"] = serde_json::json!(new_tags);
+            tx.execute(
+                "UPDATE versions SET metadata = ?1 WHERE uuid = ?2",
+                params![serde_json::to_string(&metadata)?, version_uuid]
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite the tags of every prompt, and of its versions' metadata, inside an open transaction.
+/// `rewrite` returns the new tag list for a prompt, or None to leave it untouched.
+/// Locked prompts are never changed; those `rewrite` would have changed are reported.
+fn rewrite_prompt_tags<F>(tx: &rusqlite::Connection, mut rewrite: F) -> Result<TagRewrite>
+where
+    F: FnMut(&[String]) -> Option<Vec<String>>,
+{
+    let rows: Vec<(String, String, bool)> = {
+        let mut stmt = tx.prepare(
+            "SELECT uuid, tags, locked FROM prompts WHERE tags IS NOT NULL AND tags != '' ORDER BY uuid"
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut changed = 0;
+    let mut skipped_locked = Vec::new();
+
+    for (uuid, tags_json, locked) in rows {
+        let tags: Vec<String> = match serde_json::from_str(&tags_json) {
+            Ok(tags) => tags,
+            Err(e) => {
+                log::warn!("Skipping prompt {} with unparseable tags: {}", uuid, e);
+                continue;
+            }
+        };
+
+        if let Some(new_tags) = rewrite(&tags) {
+            if locked {
+                skipped_locked.push(uuid);
+                continue;
+            }
+            let new_tags_json = serde_json::to_string(&new_tags)?;
+            tx.execute(
+                "UPDATE prompts SET tags = ?1, updated_at = ?2 WHERE uuid = ?3",
+                params![new_tags_json, now, uuid]
+            )?;
+            rewrite_version_metadata_tags(tx, &uuid, &mut rewrite)?;
+            changed += 1;
+        }
+    }
+
+    Ok(TagRewrite { changed, skipped_locked })
+}
+
+/// `tags` with every case-insensitive match of `old_lower` replaced by `new_tag` and duplicates
+/// dropped, keeping the first occurrence. None when no tag matches.
+fn renamed_tags(tags: &[String], old_lower: &str, new_tag: &str) -> Option<Vec<String>> {
+    if !tags.iter().any(|t| t.to_lowercase() == old_lower) {
+        return None;
+    }
+
+    let mut renamed: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = if tag.to_lowercase() == old_lower { new_tag.to_string() } else { tag.clone() };
+        if !renamed.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+            renamed.push(tag);
+        }
+    }
+    Some(renamed)
+}
+
+/// Rename a tag on every unlocked prompt (case-insensitive, matching metadata_get_all_tags).
+/// Locked prompts keep the old tag and are listed in `skipped_locked`.
+#[tauri::command]
+pub async fn rename_tag(old: String, new: String) -> std::result::Result<TagRewrite, String> {
+    log::info!("Renaming tag '{}' to '{}'", old, new);
+
+    if old.trim().is_empty() {
+        return Err("Tag to rename cannot be empty".to_string());
+    }
+    validate_tag(&new)?;
+
+    let old_lower = old.trim().to_lowercase();
+    let new_tag = new.trim().to_string();
+    let db = get_database()?;
+
+    let result = db.with_transaction(|tx| {
+        rewrite_prompt_tags(tx, |tags| renamed_tags(tags, &old_lower, &new_tag))
+    })?;
+
+    log::info!("Renamed tag '{}' to '{}' on {} prompts ({} locked skipped)",
+               old, new_tag, result.changed, result.skipped_locked.len());
+    Ok(result)
+}
+
+/// `tags` without any case-insensitive match of `tag_lower`, or None when none matches
+fn tags_without(tags: &[String], tag_lower: &str) -> Option<Vec<String>> {
+    if !tags.iter().any(|t| t.to_lowercase() == tag_lower) {
+        return None;
+    }
+    Some(tags.iter().filter(|t| t.to_lowercase() != tag_lower).cloned().collect())
+}
+
+/// Remove a tag from every unlocked prompt (case-insensitive). Prompts losing their last tag
+/// keep an empty array. Locked prompts keep the tag and are listed in `skipped_locked`.
+#[tauri::command]
+pub async fn delete_tag(tag: String) -> std::result::Result<TagRewrite, String> {
+    log::info!("Deleting tag '{}' from all prompts", tag);
+
+    if tag.trim().is_empty() {
+        return Err("Tag cannot be empty".to_string());
+    }
+
+    let tag_lower = tag.trim().to_lowercase();
+    let db = get_database()?;
+
+    let result = db.with_transaction(|tx| {
+        rewrite_prompt_tags(tx, |tags| tags_without(tags, &tag_lower))
+    })?;
+
+    log::info!("Deleted tag '{}' from {} prompts ({} locked skipped)", tag, result.changed, result.skipped_locked.len());
+    Ok(result)
+}
+
 /// Get all available model providers
 #[tauri::command]
 pub async fn metadata_get_model_providers() -> std::result::Result<Vec<ModelProvider>, String> {
@@ -437,6 +866,23 @@ pub async fn metadata_get_model_providers() -> std::result::Result<Vec<ModelProv
     Ok(providers)
 }
 
+/// Check a model provider's fields: the id, and any name or provider given, must be non-empty
+/// and at most 100, 100 and 50 characters long. Shared by the add and update commands.
+fn validate_model_provider(model_id: &str, name: Option<&str>, provider: Option<&str>) -> std::result::Result<(), String> {
+    if model_id.trim().is_empty()
+        || name.is_some_and(|n| n.trim().is_empty())
+        || provider.is_some_and(|p| p.trim().is_empty())
+    {
+        return Err("Model ID, name, and provider cannot be empty".to_string());
+    }
+    
+    if model_id.len() > 100 || name.is_some_and(|n| n.len() > 100) || provider.is_some_and(|p| p.len() > 50) {
+        return Err("Model ID, name, or provider too long".to_string());
+    }
+    
+    Ok(())
+}
+
 /// Add a new model provider
 #[tauri::command]
 pub async fn metadata_add_model_provider(
@@ -446,50 +892,127 @@ pub async fn metadata_add_model_provider(
 ) -> std::result::Result<ModelProvider, String> {
     log::info!("Adding new model provider: {} ({})", name, model_id);
     
-    // Validate input
-    if model_id.trim().is_empty() || name.trim().is_empty() || provider.trim().is_empty() {
-        return Err("Model ID, name, and provider cannot be empty".to_string());
-    }
-    
-    if model_id.len() > 100 || name.len() > 100 || provider.len() > 50 {
-        return Err("Model ID, name, or provider too long".to_string());
-    }
+    validate_model_provider(&model_id, Some(&name), Some(&provider))?;
     
     let db = get_database()?;
     
-    let model_provider = db.with_connection(|conn| {
+    db.with_transaction(|tx| {
         // Check if model_id already exists
-        let exists: i64 = conn.query_row(
+        let exists: i64 = tx.query_row(
             "SELECT COUNT(*) FROM model_providers WHERE model_id = ?1",
             params![&model_id],
             |row| row.get(0)
         )?;
         
         if exists > 0 {
-            return Err(rusqlite::Error::SqliteFailure(
-                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
-                Some("Model ID already exists".to_string())
-            ));
+            return Err(AppError::Conflict(format!("Model ID already exists: {}", model_id)));
         }
         
         // Insert new model provider
-        conn.execute(
+        tx.execute(
             "INSERT INTO model_providers (model_id, name, provider, active) VALUES (?1, ?2, ?3, ?4)",
             params![&model_id, &name, &provider, true]
         )?;
         
-        Ok(ModelProvider {
-            id: model_id,
-            name,
-            provider,
-            active: true,
-        })
+        Ok(())
     })?;
     
+    let model_provider = ModelProvider {
+        id: model_id,
+        name,
+        provider,
+        active: true,
+    };
+    
     log::info!("Successfully added model provider: {}", model_provider.name);
     Ok(model_provider)
 }
 
+/// Starter set of model providers offered by seed_default_model_providers: (model_id, name, provider)
+const DEFAULT_MODEL_PROVIDERS: &[(&str, &str, &str)] = &[
+    ("gpt-4o", "GPT-4o", "OpenAI"),
+    ("gpt-4o-mini", "GPT-4o mini", "OpenAI"),
+    ("o3-mini", "o3-mini", "OpenAI"),
+    ("claude-3-7-sonnet-latest", "Claude 3.7 Sonnet", "Anthropic"),
+    ("claude-3-5-haiku-latest", "Claude 3.5 Haiku", "Anthropic"),
+    ("gemini-2.0-flash", "Gemini 2.0 Flash", "Google"),
+    ("gemini-1.5-pro", "Gemini 1.5 Pro", "Google"),
+    ("mistral-large-latest", "Mistral Large", "Mistral"),
+    ("mistral-small-latest", "Mistral Small", "Mistral"),
+];
+
+/// Insert the starter model providers, skipping any model_id that already exists.
+/// This is an explicit user action - nothing is seeded automatically on startup.
+#[tauri::command]
+pub async fn seed_default_model_providers() -> std::result::Result<usize, String> {
+    log::info!("Seeding default model providers");
+
+    let db = get_database()?;
+
+    let added = db.with_transaction(|tx| {
+        let mut stmt = tx.prepare(
+            "INSERT OR IGNORE INTO model_providers (model_id, name, provider, active) VALUES (?1, ?2, ?3, ?4)"
+        )?;
+
+        let mut added = 0;
+        for (model_id, name, provider) in DEFAULT_MODEL_PROVIDERS {
+            added += stmt.execute(params![model_id, name, provider, true])?;
+        }
+
+        Ok(added)
+    })?;
+
+    log::info!("Seeded {} default model providers", added);
+    Ok(added)
+}
+
+/// Update an existing model provider, changing only the supplied fields
+#[tauri::command]
+pub async fn metadata_update_model_provider(
+    model_id: String,
+    name: Option<String>,
+    provider: Option<String>,
+    active: Option<bool>,
+) -> std::result::Result<ModelProvider, String> {
+    log::info!("Updating model provider: {}", model_id);
+
+    validate_model_provider(&model_id, name.as_deref(), provider.as_deref())?;
+
+    let db = get_database()?;
+
+    let model_provider = db.with_connection(|conn| {
+        let rows_affected = conn.execute(
+            "UPDATE model_providers
+             SET name = COALESCE(?1, name),
+                 provider = COALESCE(?2, provider),
+                 active = COALESCE(?3, active),
+                 updated_at = datetime('now')
+             WHERE model_id = ?4",
+            params![&name, &provider, &active, &model_id]
+        )?;
+
+        if rows_affected == 0 {
+            return Ok(None);
+        }
+
+        conn.query_row(
+            "SELECT model_id, name, provider, active FROM model_providers WHERE model_id = ?1",
+            params![&model_id],
+            |row| {
+                Ok(ModelProvider {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    provider: row.get(2)?,
+                    active: row.get::<_, i32>(3)? == 1,
+                })
+            }
+        ).optional()
+    })?.ok_or_else(|| format!("Model provider not found: {}", model_id))?;
+
+    log::info!("Successfully updated model provider: {}", model_provider.id);
+    Ok(model_provider)
+}
+
 /// Remove a model provider
 #[tauri::command]
 pub async fn metadata_remove_model_provider(model_id: String) -> std::result::Result<bool, String> {
@@ -523,9 +1046,79 @@ pub struct ModelProvider {
     pub active: bool,
 }
 
+/// A model referenced by version metadata, with how many prompts reference it
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ModelUsage {
+    pub model: String,
+    /// Number of prompts with at least one version listing the model
+    pub usage_count: i64,
+    /// Whether a model_providers row exists for the model
+    pub registered: bool,
+    /// Whether that row is active; false when unregistered
+    pub active: bool,
+}
+
+/// Count prompts referencing each model in `models` or `target_model` of their non-deleted
+/// versions, joined against model_providers. Sorted by count descending, then by name.
+fn load_model_usage(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<ModelUsage>> {
+    let references: Vec<(String, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT v.prompt_uuid, m.value
+             FROM versions v,
+                  json_each(CASE WHEN json_valid(v.metadata) THEN json_extract(v.metadata, '$.models') END) AS m
+             WHERE v.deleted_at IS NULL AND m.type = 'text'
+             UNION ALL
+             SELECT prompt_uuid, json_extract(metadata, '$.target_model')
+             FROM versions
+             WHERE deleted_at IS NULL AND json_valid(metadata)
+               AND json_type(metadata, '$.target_model') = 'text'"
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+
+    // The same prompt counts once per model, however many versions reference it
+    let mut prompts_by_model: HashMap<String, std::collections::HashSet<String>> = HashMap::new();
+    for (prompt_uuid, model) in references {
+        let model = model.trim();
+        if !model.is_empty() {
+            prompts_by_model.entry(model.to_string()).or_default().insert(prompt_uuid);
+        }
+    }
+
+    let mut stmt = conn.prepare("SELECT active FROM model_providers WHERE model_id = ?1")?;
+    let mut usage = Vec::with_capacity(prompts_by_model.len());
+    for (model, prompts) in prompts_by_model {
+        let active: Option<bool> = stmt.query_row([&model], |row| row.get(0)).optional()?;
+        usage.push(ModelUsage {
+            usage_count: prompts.len() as i64,
+            registered: active.is_some(),
+            active: active.unwrap_or(false),
+            model,
+        });
+    }
+
+    usage.sort_by(|a, b| b.usage_count.cmp(&a.usage_count).then_with(|| a.model.cmp(&b.model)));
+    Ok(usage)
+}
+
+/// List every model referenced by version metadata (`models` and `target_model`) with the
+/// number of prompts using it and whether it is registered and active in model_providers
+#[tauri::command]
+pub async fn get_used_models() -> std::result::Result<Vec<ModelUsage>, String> {
+    log::info!("Getting models referenced across prompts");
+
+    let db = get_database()?;
+    let usage = db.with_connection(load_model_usage)?;
+
+    log::debug!("Found {} referenced models", usage.len());
+    Ok(usage)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::database::test_connection;
 
     #[test]
     fn test_metadata_validation() {
@@ -550,6 +1143,85 @@ mod tests {
         assert!(metadata.validate().is_err());
     }
 
+    #[test]
+    fn test_load_model_usage() {
+        let conn = test_connection();
+        conn.execute_batch(
+            r#"INSERT INTO model_providers (model_id, name, provider, active) VALUES
+                   ('gpt-4o', 'GPT-4o', 'openai', 1), ('old-model', 'Old', 'openai', 0),
+                   ('unused', 'Unused', 'openai', 1);
+               INSERT INTO prompts (uuid, title, created_at, updated_at)
+               SELECT column1, column1, '2024-01-01', '2024-01-01'
+               FROM (VALUES ('p1'), ('p2'), ('p3'), ('p4'), ('p5'), ('p6'));
+               INSERT INTO versions (uuid, prompt_uuid, semver, body, metadata, created_at, deleted_at) VALUES
+                   ('v1', 'p1', '1.0.0', '', '{"models": ["gpt-4o", "old-model"]}', '2024-01-01', NULL),
+                   ('v2', 'p1', '1.0.1', '', '{"models": ["gpt-4o"], "target_model": "gpt-4o"}', '2024-01-01', NULL),
+                   ('v3', 'p2', '1.0.0', '', '{"models": ["gpt-4o", " local-llm "]}', '2024-01-01', NULL),
+                   ('v4', 'p3', '1.0.0', '', '{"target_model": "local-llm"}', '2024-01-01', NULL),
+                   ('v5', 'p4', '1.0.0', '', '{"models": ["deleted-only"]}', '2024-01-01', '2024-01-01'),
+                   ('v6', 'p5', '1.0.0', '', 'not json', '2024-01-01', NULL),
+                   ('v7', 'p6', '1.0.0', '', NULL, '2024-01-01', NULL);"#
+        ).unwrap();
+
+        let usage = load_model_usage(&conn).unwrap();
+        let summary: Vec<(&str, i64, bool, bool)> = usage.iter()
+            .map(|u| (u.model.as_str(), u.usage_count, u.registered, u.active))
+            .collect();
+        assert_eq!(summary, vec![
+            ("gpt-4o", 2, true, true),
+            ("local-llm", 2, false, false),
+            ("old-model", 1, true, false),
+        ]);
+    }
+
+    #[test]
+    fn test_custom_fields_validation() {
+        // A flat object is accepted
+        let mut metadata = PromptMetadata {
+            custom_fields: Some(serde_json::json!({ "project": "acme", "priority": 2 })),
+            ..PromptMetadata::default()
+        };
+        assert!(metadata.validate().is_ok());
+
+        // Arrays and scalars are rejected at the top level
+        metadata.custom_fields = Some(serde_json::json!(["a", "b"]));
+        assert!(metadata.validate().is_err());
+        metadata.custom_fields = Some(serde_json::json!("value"));
+        assert!(metadata.validate().is_err());
+
+        // Too many keys should fail
+        let many_keys: serde_json::Map<String, serde_json::Value> = (0..=MAX_CUSTOM_FIELDS_KEYS)
+            .map(|i| (format!("key{}", i), serde_json::Value::Bool(true)))
+            .collect();
+        metadata.custom_fields = Some(serde_json::Value::Object(many_keys));
+        assert!(metadata.validate().is_err());
+
+        // Oversized payload should fail
+        metadata.custom_fields = Some(serde_json::json!({ "blob": "x".repeat(MAX_CUSTOM_FIELDS_SIZE) }));
+        assert!(metadata.validate().is_err());
+    }
+
+    #[test]
+    fn test_category_path_validation() {
+        let with_path = |path: &str| PromptMetadata {
+            category_path: Some(path.to_string()),
+            ..PromptMetadata::default()
+        };
+
+        assert!(with_path("Uncategorized").validate().is_ok());
+        assert!(with_path("work/ml/prompts").validate().is_ok());
+
+        // Leading and trailing slashes
+        assert!(with_path("/work").validate().is_err());
+        assert!(with_path("work/").validate().is_err());
+        assert!(with_path("/").validate().is_err());
+
+        // Empty and blank segments
+        assert!(with_path("work//ml").validate().is_err());
+        assert!(with_path("work/ /ml").validate().is_err());
+        assert!(with_path("").validate().is_err());
+    }
+
     #[test]
     fn test_metadata_merge() {
         let mut base = PromptMetadata::default();
@@ -563,6 +1235,8 @@ mod tests {
             category_path: None,
             notes: Some("New notes".to_string()),
             custom_fields: None,
+            target_model: Some("gpt-4o".to_string()),
+            parameters: None,
         };
         
         base.merge_with(&update);
@@ -571,5 +1245,166 @@ mod tests {
         assert_eq!(base.tags, Some(vec!["tag1".to_string()])); // Should keep original
         assert_eq!(base.models, Some(vec!["gpt-4".to_string()]));
         assert_eq!(base.notes, Some("New notes".to_string()));
+        assert_eq!(base.target_model, Some("gpt-4o".to_string()));
+    }
+
+    #[test]
+    fn test_parameters_validation() {
+        let mut metadata = PromptMetadata {
+            target_model: Some("gpt-4o".to_string()),
+            parameters: Some(serde_json::json!({ "temperature": 0.2, "top_p": 0.9 })),
+            ..PromptMetadata::default()
+        };
+        assert!(metadata.validate().is_ok());
+
+        metadata.parameters = Some(serde_json::json!([0.2]));
+        assert!(metadata.validate().is_err());
+
+        metadata.parameters = Some(serde_json::json!({ "stop": "x".repeat(MAX_PARAMETERS_SIZE) }));
+        assert!(metadata.validate().is_err());
+
+        metadata.parameters = None;
+        metadata.target_model = Some(" ".to_string());
+        assert!(metadata.validate().is_err());
+
+        // Metadata saved before these fields existed still parses
+        let legacy = PromptMetadata::from_json(r#"{"title":"T","tags":null,"models":null,"category_path":null,"notes":null,"custom_fields":null}"#).unwrap();
+        assert_eq!(legacy.target_model, None);
+    }
+
+    #[test]
+    fn test_apply_metadata_update_reports_prompt() {
+        let mut conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, created_at, updated_at) VALUES ('p1', 'Old', '2024-01-01', '2024-01-01');
+             INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at)
+             VALUES ('v1', 'p1', '1.0.0', '', '2024-01-01');"
+        ).unwrap();
+        let tx = conn.transaction().unwrap();
+
+        let notes_only = PromptMetadata { notes: Some("n".to_string()), ..PromptMetadata::default() };
+        let applied = apply_metadata_update(&tx, "v1", &notes_only).unwrap();
+        assert_eq!((applied.prompt_uuid.as_deref(), applied.prompt_updated), (Some("p1"), false));
+
+        let title = PromptMetadata { title: Some("New".to_string()), ..PromptMetadata::default() };
+        let applied = apply_metadata_update(&tx, "v1", &title).unwrap();
+        assert_eq!((applied.prompt_uuid.as_deref(), applied.prompt_updated), (Some("p1"), true));
+        let stored: String = tx.query_row("SELECT title FROM prompts WHERE uuid = 'p1'", [], |row| row.get(0)).unwrap();
+        assert_eq!(stored, "New");
+        assert!(!apply_metadata_update(&tx, "v1", &title).unwrap().prompt_updated);
+
+        let applied = apply_metadata_update(&tx, "missing", &title).unwrap();
+        assert_eq!((applied.prompt_uuid, applied.prompt_updated), (None, false));
+    }
+
+    #[test]
+    fn test_rewrite_prompt_tags_reports_locked() {
+        let conn = test_connection();
+        conn.execute_batch(
+            r#"INSERT INTO prompts (uuid, title, tags, locked, created_at, updated_at)
+               SELECT column1, column1, column2, column3, '2024-01-01', '2024-01-01'
+               FROM (VALUES ('p1', '["draft", "ml"]', 0), ('p2', '["Draft"]', 1), ('p3', '["ml"]', 1));"#
+        ).unwrap();
+
+        let result = rewrite_prompt_tags(&conn, |tags| tags_without(tags, "draft")).unwrap();
+        assert_eq!(result, TagRewrite { changed: 1, skipped_locked: vec!["p2".to_string()] });
+
+        let tags: Vec<String> = conn.prepare("SELECT tags FROM prompts ORDER BY uuid").unwrap()
+            .query_map([], |row| row.get(0)).unwrap()
+            .collect::<rusqlite::Result<_>>().unwrap();
+        assert_eq!(tags, vec![r#"["ml"]"#, r#"["Draft"]"#, r#"["ml"]"#]);
+    }
+
+    #[test]
+    fn test_validate_model_provider() {
+        assert!(validate_model_provider("gpt-4o", Some("GPT-4o"), Some("openai")).is_ok());
+        // Updates may leave name and provider unchanged
+        assert!(validate_model_provider("gpt-4o", None, None).is_ok());
+
+        // An id too long to add can't be used for an update either
+        let long_id = "m".repeat(101);
+        assert!(validate_model_provider(&long_id, Some("Model"), Some("openai")).is_err());
+        assert!(validate_model_provider(&long_id, None, None).is_err());
+
+        assert!(validate_model_provider(" ", None, None).is_err());
+        assert!(validate_model_provider("gpt-4o", Some(""), None).is_err());
+        assert!(validate_model_provider("gpt-4o", None, Some(&"p".repeat(51))).is_err());
+    }
+
+    #[test]
+    fn test_regenerate_keeps_same_day_prompt_file() {
+        let dir = std::env::temp_dir().join(format!("promptmaster-test-{}", uuid::Uuid::now_v7()));
+        let frontmatter = |uuid: &str| Frontmatter {
+            uuid: uuid.to_string(),
+            version: "1.0.0".to_string(),
+            title: "Summarize".to_string(),
+            created: "2025-01-01".to_string(),
+            modified: "2025-01-01".to_string(),
+            ..Frontmatter::default()
+        };
+        let first = uuid::Uuid::now_v7().to_string();
+        let second = uuid::Uuid::now_v7().to_string();
+
+        let changes = write_regenerated_file(&dir, &frontmatter(&first), "first body").unwrap();
+        let first_path = dir.join("2025-01-01--summarize--v1.0.0.md");
+        assert_eq!(changes[0].path, first_path.to_string_lossy());
+
+        // A second prompt with the same title and day gets its own file
+        let changes = write_regenerated_file(&dir, &frontmatter(&second), "second body").unwrap();
+        assert_ne!(changes[0].path, first_path.to_string_lossy());
+        assert!(std::fs::read_to_string(&first_path).unwrap().ends_with("first body"));
+
+        // Regenerating it again finds its own file unchanged
+        let again = write_regenerated_file(&dir, &frontmatter(&second), "second body").unwrap();
+        assert_eq!((again[0].path.as_str(), again[0].action), (changes[0].path.as_str(), FileAction::Unchanged));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_renamed_tag_stays_renamed_after_metadata_update() {
+        let mut conn = test_connection();
+        conn.execute_batch(
+            r#"INSERT INTO prompts (uuid, title, tags, created_at, updated_at)
+               VALUES ('p1', 'T', '["Draft", "ml"]', '2024-01-01', '2024-01-01');
+               INSERT INTO versions (uuid, prompt_uuid, semver, body, metadata, created_at) VALUES
+                   ('v1', 'p1', '1.0.0', '', '{"title": "T", "tags": ["draft"]}', '2024-01-01'),
+                   ('v2', 'p1', '1.0.1', '', '{"title": "T", "tags": ["Draft", "ml"], "notes": "keep"}', '2024-01-02');"#
+        ).unwrap();
+        let tx = conn.transaction().unwrap();
+
+        let result = rewrite_prompt_tags(&tx, |tags| renamed_tags(tags, "draft", "final")).unwrap();
+        assert_eq!(result.changed, 1);
+
+        let notes = PromptMetadata { notes: Some("edited".to_string()), ..PromptMetadata::default() };
+        apply_metadata_update(&tx, "v2", &notes).unwrap();
+        let prompt_tags: String = tx.query_row("SELECT tags FROM prompts WHERE uuid = 'p1'", [], |row| row.get(0)).unwrap();
+        assert_eq!(prompt_tags, r#"["final","ml"]"#);
+
+        let metadata = |uuid: &str| -> PromptMetadata {
+            let json: String = tx.query_row("SELECT metadata FROM versions WHERE uuid = ?1", [uuid], |row| row.get(0)).unwrap();
+            PromptMetadata::from_json(&json).unwrap()
+        };
+        assert_eq!(metadata("v1").tags, Some(vec!["final".to_string()]));
+        assert_eq!(metadata("v2").notes.as_deref(), Some("edited"));
+    }
+
+    #[test]
+    fn test_deleted_tag_stays_deleted_after_metadata_update() {
+        let mut conn = test_connection();
+        conn.execute_batch(
+            r#"INSERT INTO prompts (uuid, title, tags, created_at, updated_at)
+               VALUES ('p1', 'T', '["Draft", "ml"]', '2024-01-01', '2024-01-01');
+               INSERT INTO versions (uuid, prompt_uuid, semver, body, metadata, created_at)
+               VALUES ('v1', 'p1', '1.0.0', '', '{"title": "T", "tags": ["Draft", "ml"]}', '2024-01-01');"#
+        ).unwrap();
+        let tx = conn.transaction().unwrap();
+
+        assert_eq!(rewrite_prompt_tags(&tx, |tags| tags_without(tags, "draft")).unwrap().changed, 1);
+
+        let title = PromptMetadata { title: Some("Renamed".to_string()), ..PromptMetadata::default() };
+        apply_metadata_update(&tx, "v1", &title).unwrap();
+        let prompt_tags: String = tx.query_row("SELECT tags FROM prompts WHERE uuid = 'p1'", [], |row| row.get(0)).unwrap();
+        assert_eq!(prompt_tags, r#"["ml"]"#);
     }
 }
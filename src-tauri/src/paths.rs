@@ -0,0 +1,42 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+use tauri::Manager;
+use crate::error::{AppError, Result};
+
+/// Environment variable overriding the PromptMaster directory, e.g. for isolated tests or
+/// portable installs
+pub const DATA_DIR_ENV: &str = "PROMPTMASTER_DATA_DIR";
+
+/// The override directory, when the variable is set to a non-empty value. Relative values
+/// resolve against the current directory so reported file paths are always absolute.
+fn data_dir_override(value: Option<OsString>) -> Option<PathBuf> {
+    value.filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .map(|dir| std::path::absolute(&dir).unwrap_or(dir))
+}
+
+/// Directory holding the database, log, prompt markdown files and attachments:
+/// $PROMPTMASTER_DATA_DIR when set, otherwise Documents/PromptMaster
+pub fn prompts_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
+    if let Some(dir) = data_dir_override(std::env::var_os(DATA_DIR_ENV)) {
+        return Ok(dir);
+    }
+
+    let documents_dir = app_handle
+        .path()
+        .document_dir()
+        .map_err(|e| AppError::Path(e.to_string()))?;
+    Ok(documents_dir.join("PromptMaster"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_dir_override() {
+        assert_eq!(data_dir_override(None), None);
+        assert_eq!(data_dir_override(Some(OsString::new())), None);
+        assert_eq!(data_dir_override(Some(OsString::from("/tmp/pm-test"))), Some(PathBuf::from("/tmp/pm-test")));
+    }
+}
@@ -1,16 +1,22 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::Utc;
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension};
 use crate::db::get_database;
 use crate::error::{AppError, Result};
+use crate::markdown::{build_filename, build_markdown, parse_filename, parse_frontmatter, resolve_version_file_path, slugify, stamp_frontmatter_version, Frontmatter, ParsedMarkdown};
 use crate::metadata::PromptMetadata;
-use crate::security::validate_prompt_input;
-use tauri::Manager;
-use regex::Regex;
-use lazy_static::lazy_static;
+use crate::security::{validate_prompt_input, validate_uuid};
+use crate::settings::{current_author, get_bool_setting, max_result_limit, AUTO_BUMP_ON_FILE_SAVE};
+use crate::versions::{insert_version_with_retry, next_patch_semver, parse_semver, Version, LATEST_VERSION_FIRST};
+use crate::paths::prompts_dir;
+use crate::attachments::ATTACHMENTS_DIR;
+use crate::import::import_parsed;
+use crate::watcher::{is_prompt_file, record_app_write};
+use tauri::Emitter;
+use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Prompt {
@@ -19,22 +25,44 @@ pub struct Prompt {
     pub tags: Vec<String>,
     pub created_at: String,
     pub updated_at: String,
+    pub locked: bool,
+}
+
+/// A newly saved prompt with the absolute path of its markdown file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedPrompt {
+    #[serde(flatten)]
+    pub prompt: Prompt,
+    pub file_path: String,
 }
 
 // Input validation moved to security.rs module
 
+/// Save a new prompt and its first version.
+/// The first version defaults to 1.0.0; `initial_version` (e.g. "0.1.0") overrides it.
+/// Returns the prompt along with the path of the markdown file written for it.
 #[tauri::command]
 pub async fn save_prompt(
     title: String,
     content: String,
     tags: Vec<String>,
+    initial_version: Option<String>,
     app_handle: tauri::AppHandle,
-) -> std::result::Result<Prompt, String> {
+) -> std::result::Result<SavedPrompt, String> {
     log::info!("Saving prompt: {} (content: {} chars)", title, content.len());
     
     // Validate input with security checks
     validate_prompt_input(&title, &content, &tags)?;
     
+    let initial_version = match initial_version {
+        Some(version) => {
+            let version = version.trim().to_string();
+            parse_semver(&version)?;
+            version
+        }
+        None => "1.0.0".to_string(),
+    };
+    
     let prompt_uuid = Uuid::now_v7().to_string();
     let version_uuid = Uuid::now_v7().to_string();
     let now = Utc::now().to_rfc3339();
@@ -45,30 +73,32 @@ pub async fn save_prompt(
     // Use transaction for atomicity
     db.with_transaction(|tx| {
         // Insert prompt record
-        let tags_json = serde_json::to_string(&tags)
-            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let tags_json = serde_json::to_string(&tags)?;
         tx.execute(
-            "INSERT INTO prompts (uuid, title, tags, created_at, updated_at) 
-             VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO prompts (uuid, title, tags, created_at, updated_at, accessed_at) 
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
             params![
                 &prompt_uuid, 
                 &title, 
                 &tags_json, 
                 &now, 
+                &now,
                 &now
             ],
         )?;
         
         // Insert version
+        let author = current_author(tx)?;
         tx.execute(
-            "INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at) 
-             VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at, author) 
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
             params![
                 &version_uuid, 
                 &prompt_uuid, 
-                "1.0.0", 
+                &initial_version, 
                 &content, 
-                &now
+                &now,
+                &author
             ],
         )?;
         
@@ -76,16 +106,20 @@ pub async fn save_prompt(
     })?;
     
     // Save to file (after successful database transaction)
-    save_prompt_file(&app_handle, &title, &content, &tags, &prompt_uuid)?;
+    let file_path = save_prompt_file(&app_handle, &title, &content, &tags, &prompt_uuid, &initial_version)?;
     
     log::info!("Successfully saved prompt: {} ({})", title, prompt_uuid);
     
-    Ok(Prompt {
-        uuid: prompt_uuid,
-        title,
-        tags,
-        created_at: now.clone(),
-        updated_at: now,
+    Ok(SavedPrompt {
+        prompt: Prompt {
+            uuid: prompt_uuid,
+            title,
+            tags,
+            created_at: now.clone(),
+            updated_at: now,
+            locked: false,
+        },
+        file_path: file_path.to_string_lossy().into_owned(),
     })
 }
 
@@ -95,11 +129,14 @@ fn save_prompt_file(
     content: &str,
     tags: &[String],
     uuid: &str,
-) -> Result<()> {
-    save_prompt_file_with_metadata(app_handle, title, content, tags, uuid, None, "1.0.0")
+    version: &str,
+) -> Result<PathBuf> {
+    save_prompt_file_with_metadata(app_handle, title, content, tags, uuid, None, version)
 }
 
-/// Save prompt file with optional metadata integration
+/// Save prompt file with optional metadata integration.
+/// The version's author is read from the database, so the version must already be saved.
+/// Returns the path of the written file.
 pub fn save_prompt_file_with_metadata(
     app_handle: &tauri::AppHandle,
     title: &str,
@@ -108,103 +145,258 @@ pub fn save_prompt_file_with_metadata(
     uuid: &str,
     metadata: Option<&PromptMetadata>,
     version: &str,
-) -> Result<()> {
-    let documents_dir = app_handle
-        .path()
-        .document_dir()
-        .map_err(|e| AppError::Path(e.to_string()))?;
-    
-    let prompts_dir = documents_dir.join("PromptMaster");
+) -> Result<PathBuf> {
+    let prompts_dir = prompts_dir(app_handle)?;
     std::fs::create_dir_all(&prompts_dir)?;
     
     let date = Utc::now().format("%Y-%m-%d").to_string();
-    // Sanitize title for filename
-    let slug = title
-        .chars()
-        .filter_map(|c| {
-            if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' {
-                Some(c.to_ascii_lowercase())
-            } else {
-                None
-            }
-        })
-        .collect::<String>()
-        .replace(' ', "-");
-    
-    let filename = format!("{}--{}--v{}.md", date, slug, version);
+    let author: Option<String> = get_database()?.with_connection(|conn| {
+        conn.query_row(
+            "SELECT author FROM versions WHERE prompt_uuid = ?1 AND semver = ?2",
+            [uuid, version],
+            |row| row.get(0)
+        ).optional().map(Option::flatten)
+    })?;
     
     // Build frontmatter with metadata integration
-    let mut frontmatter_content = format!(
-        r#"uuid: "{}"
-version: "{}"
-title: "{}"
-tags: {:?}
-created: {}
-modified: {}"#,
-        uuid,
-        version,
-        title,
-        tags,
-        date,
-        Utc::now().format("%Y-%m-%d")
-    );
+    let mut frontmatter = Frontmatter {
+        uuid: uuid.to_string(),
+        version: version.to_string(),
+        title: title.to_string(),
+        tags: tags.to_vec(),
+        created: date.clone(),
+        modified: date,
+        author,
+        ..Frontmatter::default()
+    };
     
     // Add metadata fields if present
     if let Some(meta) = metadata {
-        if let Some(ref category_path) = meta.category_path {
-            if category_path != "Uncategorized" {
-                frontmatter_content.push_str(&format!("\ncategory: \"{}\"", category_path));
-            }
-        }
-        
-        if let Some(ref models) = meta.models {
-            if !models.is_empty() {
-                frontmatter_content.push_str(&format!("\nmodels: {:?}", models));
-            }
-        }
-        
-        if let Some(ref notes) = meta.notes {
-            if !notes.trim().is_empty() {
-                // Escape notes for YAML
-                let escaped_notes = notes.replace("\"", "\\\"").replace("\n", "\\n");
-                frontmatter_content.push_str(&format!("\nnotes: \"{}\"", escaped_notes));
-            }
-        }
-        
-        // Add custom fields if present
-        if let Some(ref custom_fields) = meta.custom_fields {
-            if let Ok(custom_yaml) = serde_yaml::to_string(custom_fields) {
-                // Remove the leading "---\n" from the YAML output
-                let custom_yaml = custom_yaml.trim_start_matches("---\n");
-                if !custom_yaml.trim().is_empty() {
-                    frontmatter_content.push_str(&format!("\n{}", custom_yaml.trim()));
-                }
-            }
-        }
+        apply_file_metadata(&mut frontmatter, meta);
+    }
+    
+    write_prompt_markdown(&prompts_dir, &frontmatter, content)
+}
+
+/// Copy the metadata fields that prompt files carry into their frontmatter
+fn apply_file_metadata(frontmatter: &mut Frontmatter, meta: &PromptMetadata) {
+    frontmatter.category = meta.category_path.clone()
+        .filter(|category_path| category_path != "Uncategorized");
+    frontmatter.models = meta.models.clone().unwrap_or_default();
+    frontmatter.notes = meta.notes.clone();
+    frontmatter.custom_fields = meta.custom_fields.clone();
+}
+
+/// Write a prompt file named after its frontmatter's created date, title and version.
+/// When that name already holds another prompt's file (same title saved the same day), a
+/// short UUID fragment is appended instead of overwriting it.
+fn write_prompt_markdown(prompts_dir: &Path, frontmatter: &Frontmatter, content: &str) -> Result<PathBuf> {
+    let slug = slugify(&frontmatter.title);
+    let file_path = resolve_version_file_path(prompts_dir, &frontmatter.created, &slug, &frontmatter.version, &frontmatter.uuid);
+    
+    record_app_write(&file_path);
+    std::fs::write(&file_path, build_markdown(frontmatter, content))?;
+    
+    Ok(file_path)
+}
+
+/// Fail with a validation error if the prompt is locked. Unknown prompts are not locked.
+pub fn ensure_unlocked(conn: &rusqlite::Connection, prompt_uuid: &str) -> Result<()> {
+    let locked: Option<bool> = conn.query_row(
+        "SELECT locked FROM prompts WHERE uuid = ?1",
+        [prompt_uuid],
+        |row| row.get(0)
+    ).optional()?;
+    
+    if locked.unwrap_or(false) {
+        return Err(AppError::Validation(format!(
+            "Prompt {} is locked; unlock it before making changes", prompt_uuid
+        )));
+    }
+    
+    Ok(())
+}
+
+/// Lock or unlock a prompt. Locked prompts reject new versions, rollbacks, pruning, metadata
+/// updates and external file edits until explicitly unlocked.
+#[tauri::command]
+pub async fn set_locked(uuid: String, locked: bool) -> std::result::Result<bool, String> {
+    log::info!("Setting locked={} for prompt {}", locked, uuid);
+    
+    validate_uuid(&uuid)?;
+    
+    let db = get_database()?;
+    let updated = db.with_connection(|conn| {
+        conn.execute(
+            "UPDATE prompts SET locked = ?1 WHERE uuid = ?2",
+            params![locked, &uuid],
+        )
+    })?;
+    
+    if updated == 0 {
+        return Err(format!("Prompt not found: {}", uuid));
     }
     
-    let full_content = format!(
-        "---\n{}\n---\n\n{}",
-        frontmatter_content,
-        content
-    );
+    Ok(locked)
+}
+
+/// Record that a prompt was opened, for get_recently_accessed
+#[tauri::command]
+pub async fn touch_prompt(uuid: String) -> std::result::Result<(), String> {
+    validate_uuid(&uuid)?;
+    
+    let db = get_database()?;
+    let updated = db.with_connection(|conn| {
+        conn.execute(
+            "UPDATE prompts SET accessed_at = ?1 WHERE uuid = ?2",
+            params![Utc::now().to_rfc3339(), &uuid],
+        )
+    })?;
     
-    std::fs::write(prompts_dir.join(filename), full_content)?;
+    if updated == 0 {
+        return Err(format!("Prompt not found: {}", uuid));
+    }
     
+    log::debug!("Touched prompt {}", uuid);
     Ok(())
 }
 
+/// List the most recently opened prompts (by accessed_at, falling back to created_at).
+/// `limit` is capped by the `max_result_limit` setting (100 unless raised).
 #[tauri::command]
-pub async fn list_prompts(_app_handle: tauri::AppHandle) -> std::result::Result<Vec<Prompt>, String> {
+pub async fn get_recently_accessed(limit: usize) -> std::result::Result<Vec<Prompt>, String> {
     let db = get_database()?;
     
+    let limit = limit.clamp(1, db.with_connection(max_result_limit)?);
+    log::info!("Getting {} recently accessed prompts", limit);
+    
     let prompts = db.with_connection(|conn| {
         let mut stmt = conn.prepare(
-            "SELECT uuid, title, tags, created_at, updated_at FROM prompts 
-             ORDER BY updated_at DESC"
+            "SELECT uuid, title, tags, created_at, updated_at, locked FROM prompts 
+             ORDER BY datetime(COALESCE(accessed_at, created_at)) DESC 
+             LIMIT ?1"
         )?;
         
-        let prompt_iter = stmt.query_map([], |row| {
+        let prompt_iter = stmt.query_map([limit as i64], |row| {
+            let tags_str: String = row.get(2)?;
+            let tags: Vec<String> = serde_json::from_str(&tags_str)
+                .unwrap_or_else(|_| Vec::new());
+            
+            Ok(Prompt {
+                uuid: row.get(0)?,
+                title: row.get(1)?,
+                tags,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+                locked: row.get(5)?,
+            })
+        })?;
+        
+        prompt_iter.collect::<rusqlite::Result<Vec<Prompt>>>()
+    })?;
+    
+    Ok(prompts)
+}
+
+/// Library-wide totals for summary badges
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct LibraryStats {
+    pub total_prompts: i64,
+    pub total_versions: i64,
+    /// Distinct category paths in use (prompts without one count as Uncategorized)
+    pub total_categories: i64,
+    /// Distinct tags across all prompts, case-insensitive
+    pub total_tags: i64,
+}
+
+/// Compute library totals with COUNT queries only; tags are counted inside SQLite via json_each
+fn load_library_stats(conn: &rusqlite::Connection) -> rusqlite::Result<LibraryStats> {
+    conn.query_row(
+        "SELECT
+            (SELECT COUNT(*) FROM prompts),
+            (SELECT COUNT(*) FROM versions WHERE deleted_at IS NULL),
+            (SELECT COUNT(DISTINCT COALESCE(category_path, 'Uncategorized')) FROM prompts),
+            (SELECT COUNT(DISTINCT LOWER(tag.value))
+             FROM prompts, json_each(prompts.tags) AS tag
+             WHERE json_valid(prompts.tags))",
+        [],
+        |row| Ok(LibraryStats {
+            total_prompts: row.get(0)?,
+            total_versions: row.get(1)?,
+            total_categories: row.get(2)?,
+            total_tags: row.get(3)?,
+        })
+    )
+}
+
+/// Total prompts, versions, categories and tags, without loading the library
+#[tauri::command]
+pub async fn get_library_stats() -> std::result::Result<LibraryStats, String> {
+    let db = get_database()?;
+    let stats = db.with_connection(load_library_stats)?;
+    Ok(stats)
+}
+
+/// Validate an optional RFC3339 filter value and convert it to UTC
+pub fn parse_rfc3339_filter(name: &str, value: Option<&str>) -> Result<Option<chrono::DateTime<Utc>>> {
+    value
+        .map(|value| {
+            chrono::DateTime::parse_from_rfc3339(value.trim())
+                .map(|parsed| parsed.with_timezone(&Utc))
+                .map_err(|e| AppError::InvalidInput(format!("{} must be an RFC3339 datetime: {}", name, e)))
+        })
+        .transpose()
+}
+
+/// Validate an optional RFC3339 filter value and normalize it to SQLite's UTC datetime format
+pub fn parse_datetime_filter(name: &str, value: Option<&str>) -> Result<Option<String>> {
+    Ok(parse_rfc3339_filter(name, value)?.map(|parsed| parsed.format("%Y-%m-%d %H:%M:%S").to_string()))
+}
+
+/// List prompts, optionally restricted to created/updated date windows (RFC3339 bounds, inclusive)
+#[tauri::command]
+pub async fn list_prompts(
+    _app_handle: tauri::AppHandle,
+    created_after: Option<String>,
+    created_before: Option<String>,
+    updated_after: Option<String>,
+    updated_before: Option<String>,
+) -> std::result::Result<Vec<Prompt>, String> {
+    // Build the date filters; timestamps are compared through datetime() because rows
+    // mix RFC3339 values and SQLite datetime('now') values
+    let filters = [
+        ("created_after", "datetime(created_at) >= ?", created_after),
+        ("created_before", "datetime(created_at) <= ?", created_before),
+        ("updated_after", "datetime(updated_at) >= ?", updated_after),
+        ("updated_before", "datetime(updated_at) <= ?", updated_before),
+    ];
+    
+    let mut clauses = Vec::new();
+    let mut values = Vec::new();
+    for (name, clause, value) in filters.iter() {
+        if let Some(value) = parse_datetime_filter(name, value.as_deref())? {
+            clauses.push(*clause);
+            values.push(value);
+        }
+    }
+    
+    let where_clause = if clauses.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", clauses.join(" AND "))
+    };
+    
+    let db = get_database()?;
+    
+    let prompts = db.with_connection(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT uuid, title, tags, created_at, updated_at, locked FROM prompts 
+             {} 
+             ORDER BY updated_at DESC",
+            where_clause
+        ))?;
+        
+        let prompt_iter = stmt.query_map(rusqlite::params_from_iter(values.iter()), |row| {
             let tags_str: String = row.get(2)?;
             let tags: Vec<String> = serde_json::from_str(&tags_str)
                 .unwrap_or_else(|_| Vec::new());
@@ -215,6 +407,7 @@ pub async fn list_prompts(_app_handle: tauri::AppHandle) -> std::result::Result<
                 tags,
                 created_at: row.get(3)?,
                 updated_at: row.get(4)?,
+                locked: row.get(5)?,
             })
         })?;
         
@@ -229,74 +422,299 @@ pub async fn list_prompts(_app_handle: tauri::AppHandle) -> std::result::Result<
     Ok(prompts)
 }
 
-pub fn update_prompt_from_file(
-    _app_handle: &tauri::AppHandle,
-    file_path: &Path,
-) -> Result<()> {
-    // Skip non-markdown files
-    if !file_path.extension().map_or(false, |ext| ext == "md") {
-        return Ok(());
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PromptSummary {
+    pub uuid: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub category_path: Option<String>,
+    pub latest_body: Option<String>,
+    pub version_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PromptComparison {
+    pub prompt_a: PromptSummary,
+    pub prompt_b: PromptSummary,
+    pub shared_tags: Vec<String>,
+    pub tags_only_in_a: Vec<String>,
+    pub tags_only_in_b: Vec<String>,
+}
+
+/// Split two tag lists into (shared, only in a, only in b). Tags match case-insensitively;
+/// shared tags keep the spelling from `a`.
+fn compare_tag_sets(a: &[String], b: &[String]) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let contains = |tags: &[String], tag: &str| tags.iter().any(|t| t.eq_ignore_ascii_case(tag));
+    
+    let (shared, only_a): (Vec<String>, Vec<String>) = a.iter()
+        .cloned()
+        .partition(|tag| contains(b, tag));
+    let only_b = b.iter()
+        .filter(|tag| !contains(a, tag))
+        .cloned()
+        .collect();
+    
+    (shared, only_a, only_b)
+}
+
+fn load_prompt_summary(conn: &rusqlite::Connection, uuid: &str) -> rusqlite::Result<PromptSummary> {
+    let (title, tags_str, category_path): (String, String, Option<String>) = conn.query_row(
+        "SELECT title, tags, category_path FROM prompts WHERE uuid = ?1",
+        [uuid],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    )?;
+    
+    let latest_body = conn.query_row(
+        &format!("SELECT body FROM versions WHERE prompt_uuid = ?1 AND deleted_at IS NULL ORDER BY {} LIMIT 1", LATEST_VERSION_FIRST),
+        [uuid],
+        |row| row.get(0)
+    ).optional()?;
+    
+    let version_count = conn.query_row(
+        "SELECT COUNT(*) FROM versions WHERE prompt_uuid = ?1 AND deleted_at IS NULL",
+        [uuid],
+        |row| row.get(0)
+    )?;
+    
+    Ok(PromptSummary {
+        uuid: uuid.to_string(),
+        title,
+        tags: serde_json::from_str(&tags_str).unwrap_or_default(),
+        category_path,
+        latest_body,
+        version_count,
+    })
+}
+
+/// Compare two different prompts side by side: titles, tags (with shared/unique sets),
+/// categories, latest bodies and version counts
+#[tauri::command]
+pub async fn compare_prompts(uuid_a: String, uuid_b: String) -> std::result::Result<PromptComparison, String> {
+    log::info!("Comparing prompts {} and {}", uuid_a, uuid_b);
+    
+    validate_uuid(&uuid_a)?;
+    validate_uuid(&uuid_b)?;
+    
+    let db = get_database()?;
+    
+    let load = |uuid: &str| {
+        db.with_connection(|conn| load_prompt_summary(conn, uuid))
+            .map_err(|e| match e {
+                AppError::Database(rusqlite::Error::QueryReturnedNoRows) => format!("Prompt not found: {}", uuid),
+                e => e.to_string(),
+            })
+    };
+    let prompt_a = load(&uuid_a)?;
+    let prompt_b = load(&uuid_b)?;
+    
+    let (shared_tags, tags_only_in_a, tags_only_in_b) = compare_tag_sets(&prompt_a.tags, &prompt_b.tags);
+    
+    Ok(PromptComparison {
+        prompt_a,
+        prompt_b,
+        shared_tags,
+        tags_only_in_a,
+        tags_only_in_b,
+    })
+}
+
+/// Details of an external file edit that was not applied because the database is newer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileConflict {
+    pub path: String,
+    pub prompt_uuid: String,
+    pub file_version: String,
+    pub file_modified: Option<String>,
+    pub db_version: Option<String>,
+    pub db_updated_at: String,
+}
+
+/// Payload of the `prompt-updated` event emitted after a file is re-indexed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptUpdated {
+    pub prompt_uuid: String,
+    pub semver: String,
+    /// Non-fatal problems with the file's frontmatter, e.g. "version missing, defaulted to 1.0.0"
+    pub warnings: Vec<String>,
+    /// Whether the file produced a new version, rather than refreshing an existing one
+    pub new_version: bool,
+}
+
+/// Result of syncing an externally edited markdown file into the database
+#[derive(Debug)]
+pub enum FileSyncOutcome {
+    Applied(PromptUpdated),
+    /// Not a markdown file
+    Skipped,
+    /// The file is older than the database state and was left unapplied
+    Conflict(FileConflict),
+}
+
+/// Whether a file's version/modified date is behind the database: either the file's version
+/// is older than the latest version in the database, or it is the latest version but was
+/// last written (by date) before the prompt's most recent update in the app.
+fn is_file_stale(
+    file_version: &str,
+    file_modified: Option<&str>,
+    db_version: Option<&str>,
+    db_updated_at: &str,
+) -> bool {
+    let Some(db_version) = db_version else {
+        return false;
+    };
+    
+    match (parse_semver(file_version), parse_semver(db_version)) {
+        (Ok(file), Ok(db)) if file < db => return true,
+        (Ok(file), Ok(db)) if file > db => return false,
+        _ => {}
     }
     
-    let content = fs::read_to_string(file_path)?;
+    // Same version: compare the file's modified date with the date part of updated_at
+    // (RFC3339 or SQLite datetime - both start with YYYY-MM-DD)
+    match (file_modified, db_updated_at.get(..10)) {
+        (Some(file_modified), Some(db_date)) => file_modified.trim() < db_date,
+        _ => false,
+    }
+}
+
+/// A just-applied file with its `version` and `modified` fields brought up to date, so the
+/// next external edit isn't taken for a copy older than the prompt's new updated_at.
+/// None when the frontmatter already matches or is missing.
+fn restamp_applied_file(content: &str, semver: &str, today: &str) -> Option<String> {
+    stamp_frontmatter_version(content, semver, today).filter(|stamped| stamped != content)
+}
 
-    lazy_static! {
-        static ref FRONTMATTER_REGEX: Regex = Regex::new(r"^---\n([\s\S]*?)\n---\n([\s\S]*)").unwrap();
-        static ref UUID_REGEX: Regex = Regex::new(r#"uuid: "([^"]+)""#).unwrap();
-        static ref TITLE_REGEX: Regex = Regex::new(r#"title: "([^"]+)""#).unwrap();
-        static ref TAGS_REGEX: Regex = Regex::new(r#"tags: \[([^\]]*)\]"#).unwrap();
-        static ref VERSION_REGEX: Regex = Regex::new(r#"version: "([^"]+)""#).unwrap();
+/// Version metadata JSON with `notes` and `custom_fields` set from a file, keeping every other
+/// field. A None argument leaves that field alone; an empty value clears it.
+/// Returns None when the metadata already has these values.
+fn metadata_with_file_fields(
+    metadata_json: Option<&str>,
+    notes: Option<&str>,
+    custom_fields: Option<&serde_json::Value>,
+) -> Result<Option<String>> {
+    let mut metadata = metadata_json
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(json).ok())
+        .filter(|value| value.is_object())
+        .unwrap_or_else(|| serde_json::json!({}));
+    let mut changed = false;
+
+    if let Some(notes) = notes {
+        let current = metadata.get("notes").and_then(|value| value.as_str()).unwrap_or_default();
+        if current.trim_end_matches('\n') != notes {
+            metadata["notes"] = if notes.is_empty() { serde_json::Value::Null } else { notes.into() };
+            changed = true;
+        }
     }
 
-    let captures = FRONTMATTER_REGEX.captures(&content)
-        .ok_or_else(|| AppError::InvalidInput("No frontmatter found".to_string()))?;
+    if let Some(custom_fields) = custom_fields {
+        let custom_fields = Some(custom_fields)
+            .filter(|fields| fields.as_object().is_some_and(|fields| !fields.is_empty()))
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+        let current = metadata.get("custom_fields").cloned().unwrap_or(serde_json::Value::Null);
+        if current != custom_fields {
+            metadata["custom_fields"] = custom_fields;
+            changed = true;
+        }
+    }
 
-    let frontmatter_str = captures.get(1).map_or("", |m| m.as_str());
-    let body = captures.get(2).map_or("", |m| m.as_str()).trim();
+    if !changed {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::to_string(&metadata)?))
+}
 
-    let uuid = UUID_REGEX.captures(frontmatter_str)
-        .and_then(|c| c.get(1).map(|m| m.as_str().to_string()))
-        .ok_or_else(|| AppError::InvalidInput("UUID not found in frontmatter".to_string()))?;
-    
-    let title = TITLE_REGEX.captures(frontmatter_str)
-        .and_then(|c| c.get(1).map(|m| m.as_str().to_string()))
-        .ok_or_else(|| AppError::InvalidInput("Title not found in frontmatter".to_string()))?;
+/// Non-fatal problems in a parsed file: optional fields that were missing and defaulted.
+/// Missing uuid/title are hard errors raised by parse_frontmatter instead.
+fn frontmatter_warnings(parsed: &ParsedMarkdown) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if parsed.custom_fields.is_none() {
+        warnings.push("frontmatter is not valid YAML; only uuid, title, tags and version were read".to_string());
+    }
+    match parsed.version {
+        None => warnings.push("version missing, defaulted to 1.0.0".to_string()),
+        Some(ref version) if parse_semver(version).is_err() => {
+            warnings.push(format!("version \"{}\" is not a semantic version (x.y.z)", version));
+        }
+        Some(_) => {}
+    }
+    if parsed.tags.as_ref().is_none_or(|tags| tags.is_empty()) {
+        warnings.push("no tags found".to_string());
+    }
+    warnings
+}
 
-    let tags_str = TAGS_REGEX.captures(frontmatter_str)
-        .and_then(|c| c.get(1).map(|m| m.as_str().to_string()))
-        .unwrap_or_default();
+pub fn update_prompt_from_file(
+    _app_handle: &tauri::AppHandle,
+    file_path: &Path,
+) -> Result<FileSyncOutcome> {
+    // Skip non-markdown files
+    if !file_path.extension().is_some_and(|ext| ext == "md") {
+        return Ok(FileSyncOutcome::Skipped);
+    }
     
-    // Parse tags more robustly
-    let tags: Vec<String> = if tags_str.trim().is_empty() {
-        Vec::new()
-    } else {
-        tags_str
-            .split(',')
-            .filter_map(|s| {
-                let trimmed = s.trim().trim_matches('"');
-                if trimmed.is_empty() {
-                    None
-                } else {
-                    Some(trimmed.to_string())
-                }
-            })
-            .collect()
-    };
+    let content = fs::read_to_string(file_path)?;
 
-    let version = VERSION_REGEX.captures(frontmatter_str)
-        .and_then(|c| c.get(1).map(|m| m.as_str().to_string()))
-        .unwrap_or_else(|| "1.0.0".to_string());
+    let parsed = parse_frontmatter(&content)?;
+    let warnings = frontmatter_warnings(&parsed);
+    let uuid = parsed.uuid;
+    let title = parsed.title;
+    let body = parsed.body.as_str();
+    let tags = parsed.tags.unwrap_or_default();
+    let version = parsed.version.unwrap_or_else(|| "1.0.0".to_string());
+    let file_modified = parsed.modified;
+    // Notes are written as a `|-` block, which drops trailing newlines. A file without a
+    // notes field leaves the stored notes alone.
+    let notes = parsed.notes.map(|notes| notes.trim_end_matches('\n').to_string());
+    // Frontmatter keys the app doesn't own are kept as custom fields
+    let custom_fields = parsed.custom_fields;
 
     // Validate parsed data
     validate_prompt_input(&title, body, &tags)?;
+    if notes.as_ref().is_some_and(|notes| notes.len() > 10000) {
+        return Err(AppError::Validation("Notes cannot exceed 10,000 characters".to_string()));
+    }
+    PromptMetadata { custom_fields: custom_fields.clone(), ..PromptMetadata::default() }.validate()?;
 
     let now = Utc::now().to_rfc3339();
     let db = get_database()?;
 
-    db.with_transaction(|tx| {
+    let (conflict, bumped_version, new_version) = db.with_transaction(|tx| {
+        // Locked prompts can only be changed after an explicit unlock, never via file edits
+        ensure_unlocked(tx, &uuid)?;
+        
+        // Files are only applied to prompts already in the database; unknown uuids (e.g. files
+        // copied from another library) go through import instead
+        let db_updated_at: Option<String> = tx.query_row(
+            "SELECT updated_at FROM prompts WHERE uuid = ?1",
+            [&uuid],
+            |row| row.get(0)
+        ).optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Prompt {} from {:?} is not in the database", uuid, file_path)))?;
+        
+        // Refuse to apply a file that is behind the database (newer in-app edits)
+        if let Some(db_updated_at) = db_updated_at {
+            let db_version = {
+                let mut stmt = tx.prepare("SELECT semver FROM versions WHERE prompt_uuid = ?1 AND deleted_at IS NULL")?;
+                let semvers = stmt.query_map([&uuid], |row| row.get::<_, String>(0))?
+                    .collect::<rusqlite::Result<Vec<String>>>()?;
+                semvers.into_iter().max_by_key(|semver| parse_semver(semver).ok())
+            };
+            
+            if is_file_stale(&version, file_modified.as_deref(), db_version.as_deref(), &db_updated_at) {
+                return Ok((Some(FileConflict {
+                    path: file_path.to_string_lossy().into_owned(),
+                    prompt_uuid: uuid.clone(),
+                    file_version: version.clone(),
+                    file_modified: file_modified.clone(),
+                    db_version,
+                    db_updated_at,
+                }), None, false));
+            }
+        }
+        
         // Update prompt record
-        let tags_json = serde_json::to_string(&tags)
-            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let tags_json = serde_json::to_string(&tags)?;
         tx.execute(
             "UPDATE prompts SET title = ?1, tags = ?2, updated_at = ?3 WHERE uuid = ?4",
             params![
@@ -307,37 +725,418 @@ pub fn update_prompt_from_file(
             ],
         )?;
 
+        // A file naming a trashed version can't be applied: updating it would change history
+        // the user discarded, and a new row would collide with the trashed semver
+        let trashed: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM versions WHERE prompt_uuid = ?1 AND semver = ?2 AND deleted_at IS NOT NULL)",
+            [&uuid, &version],
+            |row| row.get(0)
+        )?;
+        if trashed {
+            return Err(AppError::Conflict(format!(
+                "Version {} of prompt {} is in the trash; restore it or change the file's version", version, uuid
+            )));
+        }
+        
         // Insert new version only if it doesn't already exist (avoid file watcher duplicates)
-        let version_exists = {
-            let mut stmt = tx.prepare("SELECT COUNT(*) FROM versions WHERE prompt_uuid = ?1 AND semver = ?2")?;
-            let count: i64 = stmt.query_row([&uuid, &version], |row| Ok(row.get(0)?))?;
-            count > 0
-        };
+        let existing: Option<(String, Option<String>)> = tx.query_row(
+            "SELECT body, metadata FROM versions WHERE prompt_uuid = ?1 AND semver = ?2 AND deleted_at IS NULL",
+            [&uuid, &version],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        ).optional()?;
         
-        if !version_exists {
+        // New versions carry the latest version's metadata forward, like in-app saves
+        let latest_metadata: Option<String> = tx.query_row(
+            &format!("SELECT metadata FROM versions WHERE prompt_uuid = ?1 AND deleted_at IS NULL ORDER BY {} LIMIT 1", LATEST_VERSION_FIRST),
+            [&uuid],
+            |row| row.get(0)
+        ).optional()?.flatten();
+        let new_version_metadata = metadata_with_file_fields(latest_metadata.as_deref(), notes.as_deref(), custom_fields.as_ref())?
+            .or(latest_metadata);
+        
+        let created = if let Some((existing_body, existing_metadata)) = existing {
+            // Auto-bump mode: an edited body under an existing version number becomes a new patch
+            // version instead of being ignored
+            if existing_body != body && get_bool_setting(tx, AUTO_BUMP_ON_FILE_SAVE, false)? {
+                let latest_uuid: String = {
+                    let mut stmt = tx.prepare("SELECT uuid, semver FROM versions WHERE prompt_uuid = ?1 AND deleted_at IS NULL")?;
+                    let versions = stmt.query_map([&uuid], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                        .collect::<rusqlite::Result<Vec<_>>>()?;
+                    versions.into_iter()
+                        .max_by_key(|(_, semver)| parse_semver(semver).ok())
+                        .map(|(latest_uuid, _)| latest_uuid)
+                        .ok_or_else(|| AppError::NotFound(format!("Prompt {} has no versions to bump", uuid)))?
+                };
+                let next_semver = next_patch_semver(tx, &uuid)?
+                    .ok_or_else(|| AppError::NotFound(format!("Prompt {} has no versions to bump", uuid)))?;
+                
+                let mut new_version = Version {
+                    uuid: Uuid::now_v7().to_string(),
+                    prompt_uuid: uuid.clone(),
+                    semver: next_semver,
+                    body: body.to_string(),
+                    metadata: new_version_metadata,
+                    created_at: now.clone(),
+                    parent_uuid: Some(latest_uuid),
+                    author: current_author(tx)?,
+                };
+                insert_version_with_retry(tx, &mut new_version)?;
+                log::info!("File watcher auto-bumped prompt {} to version {}", uuid, new_version.semver);
+                return Ok((None, Some(new_version.semver), true));
+            }
+            log::debug!("Version {} already exists for prompt {}, skipping duplicate creation", version, uuid);
+            
+            // Notes and custom fields are metadata, so editing them in the file updates this
+            // version in place
+            if let Some(metadata) = metadata_with_file_fields(existing_metadata.as_deref(), notes.as_deref(), custom_fields.as_ref())? {
+                tx.execute(
+                    "UPDATE versions SET metadata = ?1 WHERE prompt_uuid = ?2 AND semver = ?3 AND deleted_at IS NULL",
+                    params![&metadata, &uuid, &version],
+                )?;
+                log::info!("File watcher updated metadata of version {} for prompt {}", version, uuid);
+            }
+            false
+        } else {
             let version_uuid = Uuid::now_v7().to_string();
             tx.execute(
-                "INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at) 
-                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                "INSERT INTO versions (uuid, prompt_uuid, semver, body, metadata, created_at, author) 
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
                 params![
                     &version_uuid,
                     &uuid,
                     &version,
                     &body,
-                    &now
+                    &new_version_metadata,
+                    &now,
+                    &current_author(tx)?
                 ],
             )?;
             log::info!("File watcher created new version {} for prompt {}", version, uuid);
-        } else {
-            log::debug!("Version {} already exists for prompt {}, skipping duplicate creation", version, uuid);
-        }
+            true
+        };
 
-        Ok(())
+        Ok((None, None, created))
     })?;
 
+    if let Some(conflict) = conflict {
+        log::warn!("File {} (v{}) is behind the database (v{:?}, updated {}), not applying",
+                   conflict.path, conflict.file_version, conflict.db_version, conflict.db_updated_at);
+        return Ok(FileSyncOutcome::Conflict(conflict));
+    }
+    
+    // Point the edited file at the applied (or auto-bumped) version and today's date, matching
+    // the updated_at just written, so further edits to it aren't stale
+    let semver = bumped_version.unwrap_or(version);
+    if let Some(stamped) = restamp_applied_file(&content, &semver, &now[..10]) {
+        record_app_write(file_path);
+        if let Err(e) = fs::write(file_path, stamped) {
+            log::warn!("Failed to update version in {:?}: {}", file_path, e);
+        }
+    }
+    
+    if !warnings.is_empty() {
+        log::warn!("File {:?} applied with warnings: {}", file_path, warnings.join("; "));
+    }
+    Ok(FileSyncOutcome::Applied(PromptUpdated {
+        prompt_uuid: uuid,
+        semver,
+        warnings,
+        new_version,
+    }))
+}
+
+/// Outcome of reindex_file, tagged by `status`
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ReindexResult {
+    Applied(PromptUpdated),
+    /// The file is behind the database and was not applied
+    Conflict(FileConflict),
+    /// The file could not be parsed or failed validation; fix it and retry
+    Invalid { message: String },
+}
+
+/// Resolve `path` to an existing markdown file inside `prompts_dir`, following symlinks and `..`
+fn resolve_prompt_file(prompts_dir: &Path, path: &Path) -> Result<PathBuf> {
+    let not_found = |e: std::io::Error| AppError::InvalidInput(format!("Cannot read {}: {}", path.display(), e));
+    let prompts_dir = prompts_dir.canonicalize().map_err(not_found)?;
+    let resolved = path.canonicalize().map_err(not_found)?;
+    
+    if !resolved.starts_with(&prompts_dir) {
+        return Err(AppError::InvalidInput(format!("{} is outside the PromptMaster directory", path.display())));
+    }
+    if !resolved.is_file() || resolved.extension().and_then(|ext| ext.to_str()) != Some("md") {
+        return Err(AppError::InvalidInput(format!("{} is not a markdown file", path.display())));
+    }
+    
+    Ok(resolved)
+}
+
+/// Re-ingest one markdown file from the PromptMaster directory without waiting for the file
+/// watcher, e.g. after fixing a file that failed to import. Parse and validation failures are
+/// returned as `invalid` rather than as errors so the UI can show them next to the file.
+#[tauri::command]
+pub async fn reindex_file(path: String, app_handle: tauri::AppHandle) -> std::result::Result<ReindexResult, String> {
+    log::info!("Re-indexing file: {}", path);
+    
+    let prompts_dir = prompts_dir(&app_handle)?;
+    let file_path = resolve_prompt_file(&prompts_dir, Path::new(path.trim()))?;
+    
+    match update_prompt_from_file(&app_handle, &file_path) {
+        Ok(FileSyncOutcome::Applied(updated)) => {
+            if let Err(e) = app_handle.emit("prompt-updated", updated.clone()) {
+                log::error!("Failed to emit prompt-updated event: {}", e);
+            }
+            Ok(ReindexResult::Applied(updated))
+        }
+        Ok(FileSyncOutcome::Conflict(conflict)) => Ok(ReindexResult::Conflict(conflict)),
+        Ok(FileSyncOutcome::Skipped) => {
+            Err(AppError::InvalidInput(format!("{} is not a markdown file", path)).into())
+        }
+        Err(AppError::InvalidInput(message)) | Err(AppError::Validation(message)) => {
+            log::warn!("File {} is not valid: {}", file_path.display(), message);
+            Ok(ReindexResult::Invalid { message })
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// A file rescan_directory could not apply
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RescanFailure {
+    pub path: String,
+    pub message: String,
+}
+
+/// A file whose uuid wasn't in the database, imported as a new prompt by rescan_directory
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RescanImport {
+    pub path: String,
+    pub prompt_uuid: String,
+}
+
+/// A prompt in the database with no markdown file on disk
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct MissingPromptFile {
+    pub prompt_uuid: String,
+    pub title: String,
+}
+
+/// Counts from rescan_directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RescanSummary {
+    /// Files that produced a new version
+    pub created: usize,
+    /// Files matching an existing version, whose prompt record and metadata were refreshed
+    pub updated: usize,
+    /// Files carrying a uuid unknown to the database, imported as new prompts
+    pub imported: Vec<RescanImport>,
+    /// Prompts none of the scanned files belong to, e.g. to regenerate their files
+    pub missing_files: Vec<MissingPromptFile>,
+    /// Files behind the database, left unapplied
+    pub conflicts: Vec<FileConflict>,
+    pub failed: Vec<RescanFailure>,
+}
+
+/// The frontmatter of a prompt file, if it parses and carries a valid uuid
+fn parse_prompt_file_with_uuid(path: &Path) -> Option<ParsedMarkdown> {
+    let content = fs::read_to_string(path).ok()?;
+    parse_frontmatter(&content).ok().filter(|parsed| validate_uuid(&parsed.uuid).is_ok())
+}
+
+/// Prompts whose uuid is not in `seen`, sorted by title
+fn prompts_missing_files(conn: &rusqlite::Connection, seen: &HashSet<String>) -> rusqlite::Result<Vec<MissingPromptFile>> {
+    let mut stmt = conn.prepare("SELECT uuid, title FROM prompts ORDER BY title, uuid")?;
+    let rows = stmt.query_map([], |row| Ok(MissingPromptFile {
+        prompt_uuid: row.get(0)?,
+        title: row.get(1)?,
+    }))?;
+    let mut missing = Vec::new();
+    for prompt in rows {
+        let prompt = prompt?;
+        if !seen.contains(&prompt.prompt_uuid) {
+            missing.push(prompt);
+        }
+    }
+    Ok(missing)
+}
+
+/// Every markdown file under `dir` the watcher would process, sorted. Hidden directories and
+/// the attachments directory are not entered.
+fn collect_prompt_files(dir: &Path, attachments_dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            let hidden = path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            if !hidden && path != attachments_dir {
+                collect_prompt_files(&path, attachments_dir, files)?;
+            }
+        } else if is_prompt_file(&path, attachments_dir) {
+            files.push(path);
+        }
+    }
+    files.sort();
     Ok(())
 }
 
+/// Reconcile the database with the markdown files on disk, e.g. after edits made while the
+/// app was closed or the watcher paused: every prompt file in the PromptMaster directory goes
+/// through the same ingestion as a watcher event. Files whose uuid is unknown (e.g. copied in
+/// from another library) are imported as new prompts, and prompts without any file on disk
+/// are reported so their files can be regenerated. Returns per-outcome results.
+#[tauri::command]
+pub async fn rescan_directory(app_handle: tauri::AppHandle) -> std::result::Result<RescanSummary, String> {
+    let prompts_dir = prompts_dir(&app_handle)?;
+    log::info!("Rescanning {}", prompts_dir.display());
+    
+    let mut files = Vec::new();
+    collect_prompt_files(&prompts_dir, &prompts_dir.join(ATTACHMENTS_DIR), &mut files)?;
+    
+    let db = get_database()?;
+    let mut known: HashSet<String> = db.with_connection(|conn| {
+        let mut stmt = conn.prepare("SELECT uuid FROM prompts")?;
+        let uuids = stmt.query_map([], |row| row.get(0))?;
+        uuids.collect()
+    })?;
+    let mut seen = HashSet::new();
+    
+    let mut summary = RescanSummary::default();
+    for path in &files {
+        let parsed = parse_prompt_file_with_uuid(path);
+        if let Some(parsed) = parsed.filter(|parsed| !known.contains(&parsed.uuid)) {
+            match import_parsed(&parsed, &app_handle) {
+                Ok((prompt_uuid, _)) => {
+                    log::info!("Imported {} as new prompt {}", path.display(), prompt_uuid);
+                    known.insert(prompt_uuid.clone());
+                    seen.insert(prompt_uuid.clone());
+                    summary.imported.push(RescanImport {
+                        path: path.to_string_lossy().into_owned(),
+                        prompt_uuid,
+                    });
+                }
+                Err(e) => {
+                    log::warn!("Rescan could not import {}: {}", path.display(), e);
+                    summary.failed.push(RescanFailure {
+                        path: path.to_string_lossy().into_owned(),
+                        message: e.to_string(),
+                    });
+                }
+            }
+            continue;
+        }
+        
+        match update_prompt_from_file(&app_handle, path) {
+            Ok(FileSyncOutcome::Applied(updated)) => {
+                seen.insert(updated.prompt_uuid.clone());
+                if updated.new_version {
+                    summary.created += 1;
+                } else {
+                    summary.updated += 1;
+                }
+                if let Err(e) = app_handle.emit("prompt-updated", updated) {
+                    log::error!("Failed to emit prompt-updated event: {}", e);
+                }
+            }
+            Ok(FileSyncOutcome::Skipped) => {}
+            Ok(FileSyncOutcome::Conflict(conflict)) => {
+                seen.insert(conflict.prompt_uuid.clone());
+                summary.conflicts.push(conflict);
+            }
+            Err(e) => {
+                // A file that exists but can't be applied (e.g. the prompt is locked) still
+                // belongs to its prompt
+                if let Some(parsed) = parse_prompt_file_with_uuid(path) {
+                    seen.insert(parsed.uuid);
+                }
+                log::warn!("Rescan failed for {}: {}", path.display(), e);
+                summary.failed.push(RescanFailure {
+                    path: path.to_string_lossy().into_owned(),
+                    message: e.to_string(),
+                });
+            }
+        }
+    }
+    
+    summary.missing_files = db.with_connection(|conn| prompts_missing_files(conn, &seen))?;
+    
+    log::info!("Rescanned {} files: {} created, {} updated, {} imported, {} conflicts, {} failed, {} prompts without a file",
+               files.len(), summary.created, summary.updated, summary.imported.len(),
+               summary.conflicts.len(), summary.failed.len(), summary.missing_files.len());
+    Ok(summary)
+}
+
+/// Frontmatter and body for recreating a deleted `date--slug--vX.Y.Z[--fragment].md` file: the
+/// prompt whose title slug (and UUID fragment, if any) match, at that version. Category, models,
+/// notes and custom fields come from the version's metadata, so editing the recreated file
+/// doesn't read their absence as removal. Fails with QueryReturnedNoRows when nothing matches.
+fn deleted_file_frontmatter(
+    conn: &rusqlite::Connection,
+    title_slug: &str,
+    version: &str,
+    uuid_fragment: Option<&str>,
+) -> rusqlite::Result<(Frontmatter, String)> {
+    // Disambiguated filenames carry a UUID fragment identifying the owning prompt
+    let owns_file = |uuid: &str| uuid_fragment.is_none_or(|fragment| uuid.ends_with(fragment));
+    let to_frontmatter = |row: &rusqlite::Row| -> rusqlite::Result<(Frontmatter, String)> {
+        let tags_str: String = row.get(2)?;
+        let created_at: String = row.get(5)?;
+        let metadata: Option<String> = row.get(7)?;
+        
+        // Parse the created_at date for the frontmatter and filename
+        let date = match chrono::DateTime::parse_from_rfc3339(&created_at) {
+            Ok(datetime) => datetime.format("%Y-%m-%d").to_string(),
+            Err(_) => Utc::now().format("%Y-%m-%d").to_string(),
+        };
+        let mut frontmatter = Frontmatter {
+            uuid: row.get(0)?,
+            version: version.to_string(),
+            title: row.get(1)?,
+            tags: serde_json::from_str(&tags_str).unwrap_or_else(|_| Vec::new()),
+            created: date,
+            modified: Utc::now().format("%Y-%m-%d").to_string(),
+            author: row.get(6)?,
+            ..Frontmatter::default()
+        };
+        let mut metadata = metadata
+            .and_then(|json| PromptMetadata::from_json(&json).ok())
+            .unwrap_or_default();
+        metadata.category_path = row.get(3)?;
+        apply_file_metadata(&mut frontmatter, &metadata);
+        Ok((frontmatter, row.get(4)?))
+    };
+    let matches = |frontmatter: &Frontmatter| slugify(&frontmatter.title) == title_slug && owns_file(&frontmatter.uuid);
+    
+    // First, try the most recent version with this number
+    let mut stmt = conn.prepare(
+        "SELECT p.uuid, p.title, p.tags, p.category_path, v.body, v.created_at, v.author, v.metadata 
+         FROM prompts p 
+         JOIN versions v ON p.uuid = v.prompt_uuid 
+         WHERE v.semver = ?1 AND v.deleted_at IS NULL 
+         ORDER BY v.created_at DESC 
+         LIMIT 1"
+    )?;
+    let (frontmatter, body) = stmt.query_row([version], to_frontmatter)?;
+    if matches(&frontmatter) {
+        return Ok((frontmatter, body));
+    }
+    
+    // If it belongs to another prompt, search every prompt with this version number
+    let mut stmt = conn.prepare(
+        "SELECT p.uuid, p.title, p.tags, p.category_path, v.body, v.created_at, v.author, v.metadata 
+         FROM prompts p 
+         JOIN versions v ON p.uuid = v.prompt_uuid 
+         WHERE v.semver = ?1 AND v.deleted_at IS NULL"
+    )?;
+    for row in stmt.query_map([version], to_frontmatter)? {
+        let (frontmatter, body) = row?;
+        if matches(&frontmatter) {
+            return Ok((frontmatter, body));
+        }
+    }
+    
+    Err(rusqlite::Error::QueryReturnedNoRows)
+}
+
 pub fn recreate_prompt_file(
     app_handle: &tauri::AppHandle,
     deleted_file_path: &Path,
@@ -350,172 +1149,32 @@ pub fn recreate_prompt_file(
     
     // Parse the filename to extract UUID from frontmatter
     // First check if the file exists in the database by trying to match the filename pattern
-    lazy_static! {
-        static ref FILENAME_REGEX: Regex = Regex::new(r"(\d{4}-\d{2}-\d{2})--(.+)--v(\d+\.\d+\.\d+)\.md").unwrap();
-    }
-    
-    let captures = FILENAME_REGEX.captures(&filename);
-    if captures.is_none() {
-        log::warn!("Deleted file doesn't match expected pattern: {}", filename);
-        return Ok(false);
-    }
-    
-    let captures = captures.unwrap();
-    let _date = captures.get(1).map(|m| m.as_str());
-    let title_slug = captures.get(2).map(|m| m.as_str()).unwrap_or("");
-    let version = captures.get(3).map(|m| m.as_str()).unwrap_or("1.0.0");
+    let (title_slug, version, uuid_fragment) = match parse_filename(&filename) {
+        Some((_date, slug, version, fragment)) => (slug, version, fragment),
+        None => {
+            log::warn!("Deleted file doesn't match expected pattern: {}", filename);
+            return Ok(false);
+        }
+    };
     
-    // Find the prompt by searching for matching title slug in database
+    // Find the prompt by matching the title slug in the database
     let db = get_database()?;
     let prompt_data = db.with_connection(|conn| {
-        // First, try to find the prompt by matching the title slug
-        let mut stmt = conn.prepare(
-            "SELECT p.uuid, p.title, p.tags, v.body, v.created_at 
-             FROM prompts p 
-             JOIN versions v ON p.uuid = v.prompt_uuid 
-             WHERE v.semver = ?1 
-             ORDER BY v.created_at DESC 
-             LIMIT 1"
-        )?;
-        
-        let result = stmt.query_row([version], |row| {
-            let uuid: String = row.get(0)?;
-            let title: String = row.get(1)?;
-            let tags_str: String = row.get(2)?;
-            let body: String = row.get(3)?;
-            let created_at: String = row.get(4)?;
-            
-            // Parse tags
-            let tags: Vec<String> = serde_json::from_str(&tags_str)
-                .unwrap_or_else(|_| Vec::new());
-            
-            // Check if this prompt matches the deleted file's title slug
-            let computed_slug = title
-                .chars()
-                .filter_map(|c| {
-                    if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' {
-                        Some(c.to_ascii_lowercase())
-                    } else {
-                        None
-                    }
-                })
-                .collect::<String>()
-                .replace(' ', "-");
-            
-            if computed_slug == title_slug {
-                Ok(Some((uuid, title, tags, body, created_at)))
-            } else {
-                Ok(None)
-            }
-        });
-        
-        match result {
-            Ok(Some(data)) => Ok(data),
-            Ok(None) => {
-                // If no match found, try to find by searching all prompts
-                let mut stmt = conn.prepare(
-                    "SELECT p.uuid, p.title, p.tags, v.body, v.created_at 
-                     FROM prompts p 
-                     JOIN versions v ON p.uuid = v.prompt_uuid 
-                     WHERE v.semver = ?1"
-                )?;
-                
-                let rows = stmt.query_map([version], |row| {
-                    let uuid: String = row.get(0)?;
-                    let title: String = row.get(1)?;
-                    let tags_str: String = row.get(2)?;
-                    let body: String = row.get(3)?;
-                    let created_at: String = row.get(4)?;
-                    
-                    let tags: Vec<String> = serde_json::from_str(&tags_str)
-                        .unwrap_or_else(|_| Vec::new());
-                    
-                    Ok((uuid, title, tags, body, created_at))
-                })?;
-                
-                // Find the first match by title slug
-                for row in rows {
-                    let (uuid, title, tags, body, created_at) = row?;
-                    let computed_slug = title
-                        .chars()
-                        .filter_map(|c| {
-                            if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' {
-                                Some(c.to_ascii_lowercase())
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<String>()
-                        .replace(' ', "-");
-                    
-                    if computed_slug == title_slug {
-                        return Ok((uuid, title, tags, body, created_at));
-                    }
-                }
-                
-                Err(rusqlite::Error::QueryReturnedNoRows)
-            }
-            Err(e) => Err(e),
-        }
+        deleted_file_frontmatter(conn, &title_slug, &version, uuid_fragment.as_deref())
     });
     
     match prompt_data {
-        Ok(data) => {
-            let (uuid, title, tags, body, created_at) = data;
-            
+        Ok((frontmatter, body)) => {
             // Recreate the file
-            let documents_dir = app_handle
-                .path()
-                .document_dir()
-                .map_err(|e| AppError::Path(e.to_string()))?;
-            
-            let prompts_dir = documents_dir.join("PromptMaster");
+            let prompts_dir = prompts_dir(app_handle)?;
             std::fs::create_dir_all(&prompts_dir)?;
             
-            // Parse the created_at date for filename
-            let date = if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(&created_at) {
-                datetime.format("%Y-%m-%d").to_string()
-            } else {
-                Utc::now().format("%Y-%m-%d").to_string()
-            };
-            
-            let slug = title
-                .chars()
-                .filter_map(|c| {
-                    if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' {
-                        Some(c.to_ascii_lowercase())
-                    } else {
-                        None
-                    }
-                })
-                .collect::<String>()
-                .replace(' ', "-");
-            
-            let filename = format!("{}--{}--v{}.md", date, slug, version);
+            let slug = slugify(&frontmatter.title);
+            let filename = build_filename(&frontmatter.created, &slug, &version, uuid_fragment.as_deref());
             let file_path = prompts_dir.join(&filename);
             
-            // Create the frontmatter content
-            let frontmatter = format!(
-                r#"---
-uuid: "{}"
-version: "{}"
-title: "{}"
-tags: {:?}
-created: {}
-modified: {}
----
-
-{}"#,
-                uuid,
-                version,
-                title,
-                tags,
-                date,
-                Utc::now().format("%Y-%m-%d"),
-                body
-            );
-            
-            std::fs::write(&file_path, frontmatter)?;
+            record_app_write(&file_path);
+            std::fs::write(&file_path, build_markdown(&frontmatter, &body))?;
             
             log::info!("Successfully recreated file: {} -> {}", filename, file_path.display());
             Ok(true)
@@ -530,3 +1189,271 @@ modified: {}
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_connection;
+
+    fn tags(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_prompts_missing_files() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, created_at, updated_at)
+             SELECT column1, column2, '2024-01-01', '2024-01-01'
+             FROM (VALUES ('p1', 'Beta'), ('p2', 'Alpha'), ('p3', 'Gamma'));"
+        ).unwrap();
+
+        let seen: HashSet<String> = ["p1".to_string()].into_iter().collect();
+        let missing = prompts_missing_files(&conn, &seen).unwrap();
+        assert_eq!(missing, vec![
+            MissingPromptFile { prompt_uuid: "p2".to_string(), title: "Alpha".to_string() },
+            MissingPromptFile { prompt_uuid: "p3".to_string(), title: "Gamma".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn test_collect_prompt_files() {
+        let dir = std::env::temp_dir().join(format!("promptmaster-test-{}", Uuid::now_v7()));
+        for sub in ["work/ml", "attachments/p1", ".git"] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        for file in ["a.md", "work/ml/b.md", "attachments/p1/c.md", ".git/d.md", ".hidden.md", "e.md~", "f.txt"] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+
+        let mut files = Vec::new();
+        collect_prompt_files(&dir, &dir.join("attachments"), &mut files).unwrap();
+        assert_eq!(files, vec![dir.join("a.md"), dir.join("work/ml/b.md")]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_prompt_markdown_keeps_same_day_same_title_prompts_apart() {
+        let dir = std::env::temp_dir().join(format!("promptmaster-test-{}", Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let frontmatter_for = |uuid: String| Frontmatter {
+            uuid,
+            version: "1.0.0".to_string(),
+            title: "Code Review".to_string(),
+            created: "2024-05-01".to_string(),
+            modified: "2024-05-01".to_string(),
+            ..Frontmatter::default()
+        };
+        let first = frontmatter_for(Uuid::now_v7().to_string());
+        let second = frontmatter_for(Uuid::now_v7().to_string());
+
+        let first_path = write_prompt_markdown(&dir, &first, "First body").unwrap();
+        let second_path = write_prompt_markdown(&dir, &second, "Second body").unwrap();
+
+        assert_ne!(first_path, second_path);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+        assert!(std::fs::read_to_string(&first_path).unwrap().contains("First body"));
+        assert!(second_path.to_string_lossy().ends_with(&format!("--{}.md", crate::markdown::uuid_fragment(&second.uuid))));
+
+        // Rewriting a prompt's own file reuses its name
+        assert_eq!(write_prompt_markdown(&dir, &first, "First body, edited").unwrap(), first_path);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_with_older_version_is_stale() {
+        assert!(is_file_stale("1.0.1", Some("2025-01-05"), Some("1.0.2"), "2025-01-01T00:00:00+00:00"));
+        assert!(is_file_stale("1.0.9", None, Some("1.0.10"), "2025-01-01 00:00:00"));
+        assert!(!is_file_stale("1.0.3", None, Some("1.0.2"), "2025-01-01T00:00:00+00:00"));
+    }
+
+    #[test]
+    fn test_file_with_latest_version_is_stale_only_if_modified_earlier() {
+        let updated = "2025-01-05T10:00:00+00:00";
+        assert!(is_file_stale("1.0.2", Some("2025-01-04"), Some("1.0.2"), updated));
+        assert!(!is_file_stale("1.0.2", Some("2025-01-05"), Some("1.0.2"), updated));
+        assert!(!is_file_stale("1.0.2", None, Some("1.0.2"), updated));
+    }
+
+    #[test]
+    fn test_applied_file_stays_current_across_days() {
+        let mut content = "---\nuuid: \"p\"\nversion: \"1.0.2\"\ntitle: \"T\"\nmodified: 2025-01-01\n---\n\nBody".to_string();
+
+        let mut db_updated_at = "2025-01-01T09:00:00+00:00";
+
+        // Two external edits on later days: each must still apply after the previous one
+        // moved updated_at forward
+        for now in ["2025-01-05T10:00:00+00:00", "2025-01-09T08:00:00+00:00"] {
+            content = content.replace("Body", "Body!");
+            let parsed = parse_frontmatter(&content).unwrap();
+            assert!(!is_file_stale("1.0.2", parsed.modified.as_deref(), Some("1.0.2"), db_updated_at), "{}", now);
+
+            db_updated_at = now;
+            content = restamp_applied_file(&content, "1.0.2", &now[..10]).unwrap();
+            assert_eq!(parse_frontmatter(&content).unwrap().modified.as_deref(), Some(&now[..10]));
+        }
+        assert!(restamp_applied_file(&content, "1.0.2", "2025-01-09").is_none());
+    }
+
+    #[test]
+    fn test_file_for_prompt_without_versions_is_not_stale() {
+        assert!(!is_file_stale("1.0.0", Some("2020-01-01"), None, "2025-01-05T10:00:00+00:00"));
+    }
+
+    #[test]
+    fn test_recreated_file_keeps_custom_fields() {
+        let conn = test_connection();
+        let metadata = r#"{"notes": "Keep it short", "custom_fields": {"project": "acme", "priority": 2}}"#;
+        conn.execute_batch(&format!(
+            "INSERT INTO prompts (uuid, title, tags, category_path, created_at, updated_at)
+             VALUES ('p1', 'Summarize', '[\"work\"]', 'work/ml', '2025-01-01', '2025-01-01');
+             INSERT INTO versions (uuid, prompt_uuid, semver, body, metadata, created_at)
+             VALUES ('v1', 'p1', '1.0.0', 'Body', '{}', '2025-01-01T10:00:00+00:00');", metadata
+        )).unwrap();
+
+        let (frontmatter, body) = deleted_file_frontmatter(&conn, "summarize", "1.0.0", None).unwrap();
+        assert_eq!((frontmatter.created.as_str(), frontmatter.category.as_deref()), ("2025-01-01", Some("work/ml")));
+        let parsed = parse_frontmatter(&build_markdown(&frontmatter, &body)).unwrap();
+        assert_eq!(parsed.custom_fields, Some(serde_json::json!({"project": "acme", "priority": 2})));
+
+        // Editing the recreated file leaves the stored notes and custom fields as they were
+        let notes = parsed.notes.map(|notes| notes.trim_end_matches('\n').to_string());
+        assert_eq!(metadata_with_file_fields(Some(metadata), notes.as_deref(), parsed.custom_fields.as_ref()).unwrap(), None);
+
+        assert!(matches!(deleted_file_frontmatter(&conn, "other", "1.0.0", None), Err(rusqlite::Error::QueryReturnedNoRows)));
+        assert!(matches!(deleted_file_frontmatter(&conn, "summarize", "1.0.0", Some("ffffffff")), Err(rusqlite::Error::QueryReturnedNoRows)));
+    }
+
+    #[test]
+    fn test_ensure_unlocked() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, locked, created_at, updated_at)
+             SELECT column1, column1, column2, '2024-01-01', '2024-01-01'
+             FROM (VALUES ('open', 0), ('frozen', 1));"
+        ).unwrap();
+
+        assert!(ensure_unlocked(&conn, "open").is_ok());
+        assert!(ensure_unlocked(&conn, "missing").is_ok());
+
+        let err = ensure_unlocked(&conn, "frozen").unwrap_err();
+        assert!(matches!(err, AppError::Validation(ref msg) if msg.contains("locked")));
+    }
+
+    #[test]
+    fn test_resolve_prompt_file() {
+        let root = std::env::temp_dir().join(format!("promptmaster-test-{}", Uuid::now_v7()));
+        let prompts_dir = root.join("PromptMaster");
+        fs::create_dir_all(&prompts_dir).unwrap();
+        fs::write(prompts_dir.join("prompt.md"), "").unwrap();
+        fs::write(prompts_dir.join("notes.txt"), "").unwrap();
+        fs::write(root.join("outside.md"), "").unwrap();
+
+        let resolved = resolve_prompt_file(&prompts_dir, &prompts_dir.join("prompt.md")).unwrap();
+        assert!(resolved.ends_with("prompt.md"));
+        assert!(resolve_prompt_file(&prompts_dir, &prompts_dir.join("notes.txt")).is_err());
+        assert!(resolve_prompt_file(&prompts_dir, &prompts_dir.join("missing.md")).is_err());
+        // `..` can't be used to reach files outside the directory
+        assert!(resolve_prompt_file(&prompts_dir, &prompts_dir.join("../outside.md")).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_frontmatter_warnings() {
+        let uuid = Uuid::now_v7().to_string();
+        let complete = format!("---\nuuid: \"{}\"\ntitle: \"T\"\nversion: \"1.2.0\"\ntags: [\"a\"]\n---\n\nBody", uuid);
+        assert!(frontmatter_warnings(&parse_frontmatter(&complete).unwrap()).is_empty());
+
+        let bare = format!("---\nuuid: \"{}\"\ntitle: \"T\"\n---\n\nBody", uuid);
+        assert_eq!(frontmatter_warnings(&parse_frontmatter(&bare).unwrap()), vec![
+            "version missing, defaulted to 1.0.0".to_string(),
+            "no tags found".to_string(),
+        ]);
+
+        let odd = format!("---\nuuid: \"{}\"\ntitle: \"T\"\nversion: \"v2\"\ntags: []\n---\n\nBody", uuid);
+        let warnings = frontmatter_warnings(&parse_frontmatter(&odd).unwrap());
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("\"v2\" is not a semantic version"), "{:?}", warnings);
+    }
+
+    #[test]
+    fn test_metadata_with_file_fields() {
+        let metadata = r#"{"title":"T","models":["gpt-4o"],"notes":"old"}"#;
+
+        let updated = metadata_with_file_fields(Some(metadata), Some("new\nlines"), None).unwrap().unwrap();
+        let updated = PromptMetadata::from_json(&updated).unwrap();
+        assert_eq!(updated.notes.as_deref(), Some("new\nlines"));
+        assert_eq!(updated.models, Some(vec!["gpt-4o".to_string()]));
+
+        assert_eq!(metadata_with_file_fields(Some(metadata), Some("old"), None).unwrap(), None);
+        assert_eq!(metadata_with_file_fields(None, Some(""), None).unwrap(), None);
+        let cleared = metadata_with_file_fields(Some(metadata), Some(""), None).unwrap().unwrap();
+        assert_eq!(PromptMetadata::from_json(&cleared).unwrap().notes, None);
+    }
+
+    #[test]
+    fn test_metadata_with_file_custom_fields() {
+        let metadata = r#"{"title":"T","notes":"keep","custom_fields":{"project":"old"}}"#;
+        let fields = serde_json::json!({ "project": "foo" });
+
+        let updated = metadata_with_file_fields(Some(metadata), None, Some(&fields)).unwrap().unwrap();
+        let updated = PromptMetadata::from_json(&updated).unwrap();
+        assert_eq!(updated.custom_fields, Some(fields.clone()));
+        assert_eq!(updated.notes.as_deref(), Some("keep"));
+
+        let unchanged = r#"{"custom_fields":{"project":"foo"}}"#;
+        assert_eq!(metadata_with_file_fields(Some(unchanged), None, Some(&fields)).unwrap(), None);
+        // A file without custom keys clears them; an empty set is stored as null
+        let cleared = metadata_with_file_fields(Some(metadata), None, Some(&serde_json::json!({}))).unwrap().unwrap();
+        assert_eq!(PromptMetadata::from_json(&cleared).unwrap().custom_fields, None);
+        assert_eq!(metadata_with_file_fields(None, None, Some(&serde_json::json!({}))).unwrap(), None);
+    }
+
+    #[test]
+    fn test_compare_tag_sets() {
+        let (shared, only_a, only_b) = compare_tag_sets(
+            &tags(&["writing", "GPT", "draft"]),
+            &tags(&["gpt", "review", "writing"]),
+        );
+
+        assert_eq!(shared, tags(&["writing", "GPT"]));
+        assert_eq!(only_a, tags(&["draft"]));
+        assert_eq!(only_b, tags(&["review"]));
+    }
+
+    #[test]
+    fn test_compare_tag_sets_empty() {
+        let (shared, only_a, only_b) = compare_tag_sets(&[], &tags(&["a"]));
+        assert!(shared.is_empty());
+        assert!(only_a.is_empty());
+        assert_eq!(only_b, tags(&["a"]));
+    }
+
+    #[test]
+    fn test_load_library_stats() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, tags, category_path, created_at, updated_at)
+             SELECT column1, column1, column2, column3, '2024-01-01', '2024-01-01'
+             FROM (VALUES ('a', '[\"ML\", \"draft\"]', 'work/ml'),
+                          ('b', '[\"ml\"]', 'work/ml'),
+                          ('c', 'not json', NULL),
+                          ('d', NULL, 'Uncategorized'));
+             INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at, deleted_at)
+             SELECT column1, 'a', column2, '', '2024-01-01', column3
+             FROM (VALUES ('v1', '1.0.0', NULL), ('v2', '1.0.1', NULL), ('v3', '1.0.2', NULL),
+                          ('trashed', '1.0.3', '2025-01-01'));"
+        ).unwrap();
+
+        assert_eq!(load_library_stats(&conn).unwrap(), LibraryStats {
+            total_prompts: 4,
+            total_versions: 3,
+            total_categories: 2,
+            total_tags: 2,
+        });
+    }
+}
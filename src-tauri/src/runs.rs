@@ -0,0 +1,583 @@
+use serde::{Deserialize, Serialize};
+use rusqlite::{params, OptionalExtension};
+use crate::db::get_database;
+use crate::error::{AppError, Result};
+use crate::security::validate_uuid;
+
+/// Maximum number of events returned by get_prompt_timeline (the most recent ones)
+const MAX_TIMELINE_EVENTS: usize = 200;
+
+/// Which side of a comparison is better on a metric
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Winner {
+    A,
+    B,
+    Tie,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetricComparison {
+    pub mean_a: Option<f64>,
+    pub mean_b: Option<f64>,
+    /// None when either side has no recorded values for the metric
+    pub winner: Option<Winner>,
+    pub comparable: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RunComparison {
+    pub version_a_uuid: String,
+    pub version_b_uuid: String,
+    pub runs_a: i64,
+    pub runs_b: i64,
+    pub same_prompt: bool,
+    pub bleu: MetricComparison,
+    pub rouge: MetricComparison,
+    pub judge_score: MetricComparison,
+    pub cost_usd: MetricComparison,
+}
+
+/// A recorded evaluation run of a version
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Run {
+    pub uuid: String,
+    pub version_uuid: String,
+    pub model: Option<String>,
+    pub input: Option<String>,
+    pub output: Option<String>,
+    pub bleu: Option<f64>,
+    pub rouge: Option<f64>,
+    pub judge_score: Option<f64>,
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    pub cost_usd: Option<f64>,
+    pub created_at: Option<String>,
+}
+
+/// Fields to change with update_run; omitted fields keep their current value
+#[derive(Debug, Default, Deserialize)]
+pub struct RunUpdate {
+    pub model: Option<String>,
+    pub bleu: Option<f64>,
+    pub rouge: Option<f64>,
+    pub judge_score: Option<f64>,
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    pub cost_usd: Option<f64>,
+}
+
+impl RunUpdate {
+    fn validate(&self) -> Result<()> {
+        if let Some(ref model) = self.model {
+            if model.trim().is_empty() || model.len() > 100 {
+                return Err(AppError::Validation("Model must be 1-100 characters".to_string()));
+            }
+        }
+        for (name, score) in [("bleu", self.bleu), ("rouge", self.rouge)] {
+            if score.is_some_and(|score| !(0.0..=1.0).contains(&score)) {
+                return Err(AppError::Validation(format!("{} must be between 0 and 1", name)));
+            }
+        }
+        if self.judge_score.is_some_and(|score| !score.is_finite()) {
+            return Err(AppError::Validation("judge_score must be a finite number".to_string()));
+        }
+        if self.cost_usd.is_some_and(|cost| !cost.is_finite() || cost < 0.0) {
+            return Err(AppError::Validation("cost_usd cannot be negative".to_string()));
+        }
+        if self.prompt_tokens.is_some_and(|n| n < 0) || self.completion_tokens.is_some_and(|n| n < 0) {
+            return Err(AppError::Validation("Token counts cannot be negative".to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn load_run(conn: &rusqlite::Connection, run_uuid: &str) -> rusqlite::Result<Option<Run>> {
+    conn.query_row(
+        "SELECT uuid, version_uuid, model, input, output, bleu, rouge, judge_score,
+                prompt_tokens, completion_tokens, cost_usd, created_at
+         FROM runs WHERE uuid = ?1",
+        [run_uuid],
+        |row| Ok(Run {
+            uuid: row.get(0)?,
+            version_uuid: row.get(1)?,
+            model: row.get(2)?,
+            input: row.get(3)?,
+            output: row.get(4)?,
+            bleu: row.get(5)?,
+            rouge: row.get(6)?,
+            judge_score: row.get(7)?,
+            prompt_tokens: row.get(8)?,
+            completion_tokens: row.get(9)?,
+            cost_usd: row.get(10)?,
+            created_at: row.get(11)?,
+        })
+    ).optional()
+}
+
+/// Apply the supplied fields of `update` to a run; None when the run doesn't exist
+fn update_run_fields(conn: &rusqlite::Connection, run_uuid: &str, update: &RunUpdate) -> rusqlite::Result<Option<Run>> {
+    let changed = conn.execute(
+        "UPDATE runs SET
+            model = COALESCE(?2, model),
+            bleu = COALESCE(?3, bleu),
+            rouge = COALESCE(?4, rouge),
+            judge_score = COALESCE(?5, judge_score),
+            prompt_tokens = COALESCE(?6, prompt_tokens),
+            completion_tokens = COALESCE(?7, completion_tokens),
+            cost_usd = COALESCE(?8, cost_usd)
+         WHERE uuid = ?1",
+        params![
+            run_uuid,
+            update.model.as_deref().map(str::trim),
+            update.bleu,
+            update.rouge,
+            update.judge_score,
+            update.prompt_tokens,
+            update.completion_tokens,
+            update.cost_usd
+        ],
+    )?;
+    if changed == 0 {
+        return Ok(None);
+    }
+    load_run(conn, run_uuid)
+}
+
+/// Correct a recorded run. Only the fields present in `fields` change; metrics are validated
+/// (bleu/rouge in 0..1, no negative cost or token counts). Comparisons are computed from the
+/// runs table on demand, so they reflect the change immediately. Returns the updated run.
+#[tauri::command]
+pub async fn update_run(run_uuid: String, fields: RunUpdate) -> std::result::Result<Run, String> {
+    log::info!("Updating run {}", run_uuid);
+
+    validate_uuid(&run_uuid)?;
+    fields.validate()?;
+
+    let db = get_database()?;
+    let run = db.with_transaction(|tx| Ok(update_run_fields(tx, &run_uuid, &fields)?))?
+        .ok_or_else(|| AppError::NotFound(format!("Run {}", run_uuid)))?;
+
+    Ok(run)
+}
+
+/// Delete a mistaken run
+#[tauri::command]
+pub async fn delete_run(run_uuid: String) -> std::result::Result<(), String> {
+    log::info!("Deleting run {}", run_uuid);
+
+    validate_uuid(&run_uuid)?;
+
+    let db = get_database()?;
+    let deleted = db.with_connection(|conn| conn.execute("DELETE FROM runs WHERE uuid = ?1", [&run_uuid]))?;
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("Run {}", run_uuid)).into());
+    }
+
+    Ok(())
+}
+
+/// One entry in a prompt's activity feed, tagged by `type`
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimelineEvent {
+    /// A version was created
+    Version {
+        uuid: String,
+        semver: String,
+        author: Option<String>,
+        timestamp: String,
+    },
+    /// A run was recorded against one of the prompt's versions
+    Run {
+        uuid: String,
+        version_uuid: String,
+        semver: String,
+        model: Option<String>,
+        bleu: Option<f64>,
+        rouge: Option<f64>,
+        judge_score: Option<f64>,
+        cost_usd: Option<f64>,
+        /// Runs without a created_at use their version's timestamp
+        timestamp: String,
+    },
+}
+
+impl TimelineEvent {
+    fn timestamp(&self) -> &str {
+        match self {
+            TimelineEvent::Version { timestamp, .. } | TimelineEvent::Run { timestamp, .. } => timestamp,
+        }
+    }
+}
+
+/// The newest `limit` version and run events of a prompt, oldest first. Trashed versions and
+/// their runs are left out. On equal timestamps a version comes before runs against it.
+fn load_timeline(conn: &rusqlite::Connection, prompt_uuid: &str, limit: usize) -> rusqlite::Result<Vec<TimelineEvent>> {
+    let mut events: Vec<TimelineEvent> = {
+        let mut stmt = conn.prepare(
+            "SELECT uuid, semver, author, created_at FROM versions
+             WHERE prompt_uuid = ?1 AND deleted_at IS NULL
+             ORDER BY created_at DESC LIMIT ?2"
+        )?;
+        let rows = stmt.query_map(rusqlite::params![prompt_uuid, limit as i64], |row| Ok(TimelineEvent::Version {
+            uuid: row.get(0)?,
+            semver: row.get(1)?,
+            author: row.get(2)?,
+            timestamp: row.get(3)?,
+        }))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+
+    let mut stmt = conn.prepare(
+        "SELECT r.uuid, r.version_uuid, v.semver, r.model, r.bleu, r.rouge, r.judge_score, r.cost_usd,
+                COALESCE(r.created_at, v.created_at) AS timestamp
+         FROM runs r JOIN versions v ON v.uuid = r.version_uuid
+         WHERE v.prompt_uuid = ?1 AND v.deleted_at IS NULL
+         ORDER BY timestamp DESC LIMIT ?2"
+    )?;
+    let runs = stmt.query_map(rusqlite::params![prompt_uuid, limit as i64], |row| Ok(TimelineEvent::Run {
+        uuid: row.get(0)?,
+        version_uuid: row.get(1)?,
+        semver: row.get(2)?,
+        model: row.get(3)?,
+        bleu: row.get(4)?,
+        rouge: row.get(5)?,
+        judge_score: row.get(6)?,
+        cost_usd: row.get(7)?,
+        timestamp: row.get(8)?,
+    }))?;
+    for run in runs {
+        events.push(run?);
+    }
+
+    // Stable sort keeps versions ahead of runs with the same timestamp
+    events.sort_by(|a, b| a.timestamp().cmp(b.timestamp()));
+    let excess = events.len().saturating_sub(limit);
+    events.drain(..excess);
+    Ok(events)
+}
+
+/// Chronological activity feed for a prompt: version creations and recorded runs interleaved
+/// by timestamp, oldest first. Only the most recent MAX_TIMELINE_EVENTS events are returned.
+#[tauri::command]
+pub async fn get_prompt_timeline(prompt_uuid: String) -> std::result::Result<Vec<TimelineEvent>, String> {
+    log::info!("Getting timeline for prompt: {}", prompt_uuid);
+
+    validate_uuid(&prompt_uuid)?;
+
+    let db = get_database()?;
+    let events = db.with_connection(|conn| {
+        let exists = conn.query_row("SELECT 1 FROM prompts WHERE uuid = ?1", [&prompt_uuid], |_| Ok(()))
+            .optional()?
+            .is_some();
+        if !exists {
+            return Ok(None);
+        }
+        load_timeline(conn, &prompt_uuid, MAX_TIMELINE_EVENTS).map(Some)
+    })?.ok_or_else(|| AppError::NotFound(format!("Prompt {}", prompt_uuid)))?;
+
+    log::debug!("Timeline for prompt {} has {} events", prompt_uuid, events.len());
+    Ok(events)
+}
+
+/// Per-version aggregates: run count and mean bleu/rouge/judge_score/cost_usd.
+/// Means ignore runs where the metric is NULL and are None when no run has a value.
+struct RunAggregate {
+    count: i64,
+    bleu: Option<f64>,
+    rouge: Option<f64>,
+    judge_score: Option<f64>,
+    cost_usd: Option<f64>,
+}
+
+fn aggregate_runs(conn: &rusqlite::Connection, version_uuid: &str) -> rusqlite::Result<RunAggregate> {
+    conn.query_row(
+        "SELECT COUNT(*), AVG(bleu), AVG(rouge), AVG(judge_score), AVG(cost_usd)
+         FROM runs WHERE version_uuid = ?1",
+        [version_uuid],
+        |row| Ok(RunAggregate {
+            count: row.get(0)?,
+            bleu: row.get(1)?,
+            rouge: row.get(2)?,
+            judge_score: row.get(3)?,
+            cost_usd: row.get(4)?,
+        })
+    )
+}
+
+/// The best-scoring version of a prompt on one metric
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct BestVersion {
+    pub version_uuid: String,
+    pub semver: String,
+    pub metric: String,
+    pub average: f64,
+    /// Number of runs with a value for the metric
+    pub sample_size: i64,
+}
+
+/// Column holding a rankable metric; only these names are ever interpolated into SQL
+fn metric_column(metric: &str) -> Result<&'static str> {
+    match metric {
+        "bleu" => Ok("bleu"),
+        "rouge" => Ok("rouge"),
+        "judge_score" => Ok("judge_score"),
+        _ => Err(AppError::Validation(format!(
+            "Unknown metric '{}': expected bleu, rouge or judge_score", metric
+        ))),
+    }
+}
+
+/// Highest mean of `column` across the prompt's non-deleted versions. Versions without a
+/// value for the metric are skipped; ties go to the larger sample, then the newer version.
+fn best_version_by(conn: &rusqlite::Connection, prompt_uuid: &str, column: &'static str) -> rusqlite::Result<Option<BestVersion>> {
+    conn.query_row(
+        &format!(
+            "SELECT v.uuid, v.semver, AVG(r.{column}), COUNT(r.{column})
+             FROM versions v JOIN runs r ON r.version_uuid = v.uuid
+             WHERE v.prompt_uuid = ?1 AND v.deleted_at IS NULL AND r.{column} IS NOT NULL
+             GROUP BY v.uuid
+             ORDER BY AVG(r.{column}) DESC, COUNT(r.{column}) DESC, v.created_at DESC
+             LIMIT 1"
+        ),
+        [prompt_uuid],
+        |row| Ok(BestVersion {
+            version_uuid: row.get(0)?,
+            semver: row.get(1)?,
+            metric: column.to_string(),
+            average: row.get(2)?,
+            sample_size: row.get(3)?,
+        })
+    ).optional()
+}
+
+/// Version of a prompt with the highest average `metric` ("bleu", "rouge" or "judge_score")
+/// over its runs, with that average and the sample size. None when no version has runs
+/// scored on the metric.
+#[tauri::command]
+pub async fn get_best_version(prompt_uuid: String, metric: String) -> std::result::Result<Option<BestVersion>, String> {
+    log::info!("Getting best version of prompt {} by {}", prompt_uuid, metric);
+
+    validate_uuid(&prompt_uuid)?;
+    let column = metric_column(metric.trim())?;
+
+    let db = get_database()?;
+    let best = db.with_connection(|conn| {
+        let exists = conn.query_row("SELECT 1 FROM prompts WHERE uuid = ?1", [&prompt_uuid], |_| Ok(()))
+            .optional()?
+            .is_some();
+        if !exists {
+            return Ok(None);
+        }
+        best_version_by(conn, &prompt_uuid, column).map(Some)
+    })?.ok_or_else(|| AppError::NotFound(format!("Prompt {}", prompt_uuid)))?;
+
+    Ok(best)
+}
+
+/// Compare two metric means. Higher is better unless `lower_is_better` (e.g. cost).
+fn compare_metric(mean_a: Option<f64>, mean_b: Option<f64>, lower_is_better: bool) -> MetricComparison {
+    let winner = match (mean_a, mean_b) {
+        (Some(a), Some(b)) => {
+            let (a, b) = if lower_is_better { (b, a) } else { (a, b) };
+            Some(if (a - b).abs() < f64::EPSILON {
+                Winner::Tie
+            } else if a > b {
+                Winner::A
+            } else {
+                Winner::B
+            })
+        }
+        _ => None,
+    };
+
+    MetricComparison {
+        mean_a,
+        mean_b,
+        comparable: winner.is_some(),
+        winner,
+    }
+}
+
+/// Compare the recorded runs of two versions for A/B evaluation.
+/// Returns mean bleu/rouge/judge_score/cost per version, the winner on each metric and the
+/// sample sizes. Metrics with no values on either side are marked incomparable.
+/// With `require_same_prompt`, versions belonging to different prompts are rejected.
+#[tauri::command]
+pub async fn compare_runs(
+    version_a_uuid: String,
+    version_b_uuid: String,
+    require_same_prompt: Option<bool>,
+) -> std::result::Result<RunComparison, String> {
+    log::info!("Comparing runs for versions {} and {}", version_a_uuid, version_b_uuid);
+
+    validate_uuid(&version_a_uuid)?;
+    validate_uuid(&version_b_uuid)?;
+
+    let db = get_database()?;
+
+    let (prompt_a, prompt_b, aggregate_a, aggregate_b) = db.with_connection(|conn| {
+        let prompt_of = |version_uuid: &str| {
+            conn.query_row(
+                "SELECT prompt_uuid FROM versions WHERE uuid = ?1",
+                [version_uuid],
+                |row| row.get::<_, String>(0)
+            ).optional()
+        };
+
+        Ok((
+            prompt_of(&version_a_uuid)?,
+            prompt_of(&version_b_uuid)?,
+            aggregate_runs(conn, &version_a_uuid)?,
+            aggregate_runs(conn, &version_b_uuid)?,
+        ))
+    })?;
+
+    let prompt_a = prompt_a.ok_or_else(|| format!("Version not found: {}", version_a_uuid))?;
+    let prompt_b = prompt_b.ok_or_else(|| format!("Version not found: {}", version_b_uuid))?;
+    let same_prompt = prompt_a == prompt_b;
+
+    if require_same_prompt.unwrap_or(false) && !same_prompt {
+        return Err(format!(
+            "Versions {} and {} belong to different prompts", version_a_uuid, version_b_uuid
+        ));
+    }
+
+    log::debug!("Run sample sizes: {} runs for {}, {} runs for {}",
+                aggregate_a.count, version_a_uuid, aggregate_b.count, version_b_uuid);
+
+    Ok(RunComparison {
+        version_a_uuid,
+        version_b_uuid,
+        runs_a: aggregate_a.count,
+        runs_b: aggregate_b.count,
+        same_prompt,
+        bleu: compare_metric(aggregate_a.bleu, aggregate_b.bleu, false),
+        rouge: compare_metric(aggregate_a.rouge, aggregate_b.rouge, false),
+        judge_score: compare_metric(aggregate_a.judge_score, aggregate_b.judge_score, false),
+        cost_usd: compare_metric(aggregate_a.cost_usd, aggregate_b.cost_usd, true),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_connection;
+
+    #[test]
+    fn test_compare_metric_higher_is_better() {
+        assert_eq!(compare_metric(Some(0.8), Some(0.5), false).winner, Some(Winner::A));
+        assert_eq!(compare_metric(Some(0.2), Some(0.5), false).winner, Some(Winner::B));
+        assert_eq!(compare_metric(Some(0.5), Some(0.5), false).winner, Some(Winner::Tie));
+    }
+
+    #[test]
+    fn test_compare_metric_lower_is_better() {
+        assert_eq!(compare_metric(Some(0.02), Some(0.05), true).winner, Some(Winner::A));
+        assert_eq!(compare_metric(Some(0.09), Some(0.05), true).winner, Some(Winner::B));
+    }
+
+    #[test]
+    fn test_update_run_fields_only_touches_supplied_fields() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, created_at, updated_at) VALUES ('p1', 'P', '2024-01-01', '2024-01-01');
+             INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at) VALUES ('v1', 'p1', '1.0.0', 'a', '2024-01-01');
+             INSERT INTO runs VALUES ('r1', 'v1', 'gpt-4o', 'in', 'out', 0.4, 0.5, 7.0, 100, 50, 0.02, '2024-01-01');"
+        ).unwrap();
+
+        let update = RunUpdate { bleu: Some(0.6), cost_usd: Some(0.03), ..RunUpdate::default() };
+        let run = update_run_fields(&conn, "r1", &update).unwrap().unwrap();
+        assert_eq!((run.bleu, run.cost_usd), (Some(0.6), Some(0.03)));
+        assert_eq!(run.model.as_deref(), Some("gpt-4o"));
+        assert_eq!((run.rouge, run.judge_score, run.prompt_tokens), (Some(0.5), Some(7.0), Some(100)));
+
+        assert_eq!(update_run_fields(&conn, "missing", &update).unwrap(), None);
+
+        assert!(RunUpdate { bleu: Some(1.5), ..RunUpdate::default() }.validate().is_err());
+        assert!(RunUpdate { cost_usd: Some(-1.0), ..RunUpdate::default() }.validate().is_err());
+        assert!(RunUpdate { model: Some(" ".to_string()), ..RunUpdate::default() }.validate().is_err());
+        assert!(update.validate().is_ok());
+    }
+
+    #[test]
+    fn test_best_version_by_averages_runs_per_version() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, created_at, updated_at) VALUES ('p1', 'P', '2024-01-01', '2024-01-01');
+             INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at, deleted_at)
+                 VALUES ('v1', 'p1', '1.0.0', 'a', '2024-01-01', NULL),
+                        ('v2', 'p1', '1.0.1', 'b', '2024-01-02', NULL),
+                        ('v3', 'p1', '1.0.2', 'c', '2024-01-03', NULL),
+                        ('v4', 'p1', '1.0.3', 'd', '2024-01-04', '2024-01-05');
+             INSERT INTO runs (uuid, version_uuid, bleu, rouge, judge_score)
+                 VALUES ('r1', 'v1', 0.9, NULL, 6.0),
+                        ('r2', 'v1', 0.5, NULL, 8.0),
+                        ('r3', 'v2', 0.6, NULL, NULL),
+                        ('r4', 'v4', 1.0, NULL, 10.0);"
+        ).unwrap();
+
+        let best = best_version_by(&conn, "p1", "bleu").unwrap().unwrap();
+        assert_eq!((best.version_uuid.as_str(), best.sample_size), ("v1", 2));
+        assert!((best.average - 0.7).abs() < 1e-9);
+
+        // v2 has no judge score; deleted v4 is ignored
+        let best = best_version_by(&conn, "p1", "judge_score").unwrap().unwrap();
+        assert_eq!((best.semver.as_str(), best.average), ("1.0.0", 7.0));
+
+        assert_eq!(best_version_by(&conn, "p1", "rouge").unwrap(), None);
+        assert!(metric_column("cost_usd").is_err());
+        assert!(metric_column("bleu; DROP TABLE runs").is_err());
+    }
+
+    #[test]
+    fn test_load_timeline_interleaves_versions_and_runs() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, created_at, updated_at)
+                 VALUES ('p1', 'P', '2024-01-01', '2024-01-01'), ('p2', 'Q', '2024-01-01', '2024-01-01');
+             INSERT INTO versions (uuid, prompt_uuid, semver, body, author, created_at, deleted_at)
+                 VALUES ('v1', 'p1', '1.0.0', 'a', 'ada', '2024-01-01', NULL),
+                        ('v2', 'p1', '1.0.1', 'b', NULL, '2024-01-03', NULL),
+                        ('v3', 'p1', '1.0.2', 'c', NULL, '2024-01-05', '2024-01-06'),
+                        ('x1', 'p2', '1.0.0', 'x', NULL, '2024-01-02', NULL);
+             INSERT INTO runs (uuid, version_uuid, model, bleu, rouge, judge_score, cost_usd, created_at)
+                 VALUES ('r1', 'v1', 'gpt-4o', NULL, NULL, 0.9, 0.01, '2024-01-02'),
+                        ('r2', 'v2', 'claude', NULL, NULL, NULL, NULL, NULL),
+                        ('r3', 'v3', 'gpt-4o', NULL, NULL, NULL, NULL, '2024-01-07'),
+                        ('r4', 'x1', 'gpt-4o', NULL, NULL, NULL, NULL, '2024-01-02');"
+        ).unwrap();
+
+        let events = load_timeline(&conn, "p1", 10).unwrap();
+        let summary: Vec<(&str, &str)> = events.iter().map(|event| match event {
+            TimelineEvent::Version { uuid, timestamp, .. } => (uuid.as_str(), timestamp.as_str()),
+            TimelineEvent::Run { uuid, timestamp, .. } => (uuid.as_str(), timestamp.as_str()),
+        }).collect();
+        assert_eq!(summary, vec![
+            ("v1", "2024-01-01"),
+            ("r1", "2024-01-02"),
+            ("v2", "2024-01-03"),
+            // No created_at: placed at its version's timestamp, after the version
+            ("r2", "2024-01-03"),
+        ]);
+        assert!(matches!(&events[1], TimelineEvent::Run { semver, judge_score: Some(_), .. } if semver == "1.0.0"));
+
+        // The cap keeps the most recent events
+        let capped = load_timeline(&conn, "p1", 2).unwrap();
+        assert_eq!(capped.len(), 2);
+        assert_eq!(capped[0].timestamp(), "2024-01-03");
+
+        let json = serde_json::to_value(&events[0]).unwrap();
+        assert_eq!(json["type"], "version");
+        assert_eq!(json["semver"], "1.0.0");
+    }
+
+    #[test]
+    fn test_compare_metric_missing_side_is_incomparable() {
+        let comparison = compare_metric(Some(0.8), None, false);
+        assert!(!comparison.comparable);
+        assert_eq!(comparison.winner, None);
+        assert!(!compare_metric(None, None, true).comparable);
+    }
+}
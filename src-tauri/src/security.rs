@@ -13,35 +13,42 @@ pub fn validate_prompt_content(content: &str) -> Result<()> {
         static ref EVENT_HANDLER_REGEX: Regex = Regex::new(r"(?i)on\w+\s*=").unwrap();
     }
     
-    if HTML_TAG_REGEX.is_match(content) {
-        let _ = log_security_event("INVALID_HTML", "Prompt contains HTML tags");
-        return Err(AppError::InvalidInput(
-            "Prompt contains HTML tags. Only plain text, Markdown, and XML tags are allowed.".to_string()
-        ));
+    // Errors name the first offending match and its byte offset so users can find it
+    if let Some(m) = HTML_TAG_REGEX.find(content) {
+        let _ = log_security_event("INVALID_HTML", &format!("Prompt contains HTML tags: {}", describe_match(&m)));
+        return Err(AppError::InvalidInput(format!(
+            "Prompt contains HTML tags ({}). Only plain text, Markdown, and XML tags are allowed.",
+            describe_match(&m)
+        )));
     }
     
-    if SCRIPT_URL_REGEX.is_match(content) {
-        let _ = log_security_event("INVALID_SCRIPT", "Prompt contains script URLs");
-        return Err(AppError::InvalidInput(
-            "Prompt contains script URLs which are not allowed.".to_string()
-        ));
+    if let Some(m) = SCRIPT_URL_REGEX.find(content) {
+        let _ = log_security_event("INVALID_SCRIPT", &format!("Prompt contains script URLs: {}", describe_match(&m)));
+        return Err(AppError::InvalidInput(format!(
+            "Prompt contains script URLs which are not allowed ({}).", describe_match(&m)
+        )));
     }
     
-    if DATA_URL_REGEX.is_match(content) {
-        return Err(AppError::InvalidInput(
-            "Prompt contains data URLs which are not allowed.".to_string()
-        ));
+    if let Some(m) = DATA_URL_REGEX.find(content) {
+        return Err(AppError::InvalidInput(format!(
+            "Prompt contains data URLs which are not allowed ({}).", describe_match(&m)
+        )));
     }
     
-    if EVENT_HANDLER_REGEX.is_match(content) {
-        return Err(AppError::InvalidInput(
-            "Prompt contains event handlers which are not allowed.".to_string()
-        ));
+    if let Some(m) = EVENT_HANDLER_REGEX.find(content) {
+        return Err(AppError::InvalidInput(format!(
+            "Prompt contains event handlers which are not allowed ({}).", describe_match(&m)
+        )));
     }
     
     Ok(())
 }
 
+/// "'<script>' at byte 42", with the matched text truncated like logged content
+fn describe_match(m: &regex::Match) -> String {
+    format!("'{}' at byte {}", clean_content_for_logging(m.as_str()), m.start())
+}
+
 /// Enhanced input validation with security checks
 pub fn validate_prompt_input(title: &str, content: &str, tags: &[String]) -> Result<()> {
     // Basic validation
@@ -63,16 +70,7 @@ pub fn validate_prompt_input(title: &str, content: &str, tags: &[String]) -> Res
     
     // Validate each tag
     for tag in tags {
-        if tag.trim().is_empty() {
-            return Err(AppError::InvalidInput("Tag cannot be empty".to_string()));
-        }
-        if tag.len() > 50 {
-            return Err(AppError::InvalidInput("Tag too long (max 50 characters)".to_string()));
-        }
-        // Tags should be simple text
-        if tag.contains('<') || tag.contains('>') {
-            return Err(AppError::InvalidInput("Tags cannot contain HTML".to_string()));
-        }
+        validate_tag(tag)?;
     }
     
     // Security validation for content
@@ -86,6 +84,22 @@ pub fn validate_prompt_input(title: &str, content: &str, tags: &[String]) -> Res
     Ok(())
 }
 
+/// Validate a single prompt tag
+pub fn validate_tag(tag: &str) -> Result<()> {
+    if tag.trim().is_empty() {
+        return Err(AppError::InvalidInput("Tag cannot be empty".to_string()));
+    }
+    if tag.len() > 50 {
+        return Err(AppError::InvalidInput("Tag too long (max 50 characters)".to_string()));
+    }
+    // Tags should be simple text
+    if tag.contains('<') || tag.contains('>') {
+        return Err(AppError::InvalidInput("Tags cannot contain HTML".to_string()));
+    }
+    
+    Ok(())
+}
+
 /// Validate UUID format
 pub fn validate_uuid(uuid: &str) -> Result<()> {
     lazy_static! {
@@ -100,14 +114,44 @@ pub fn validate_uuid(uuid: &str) -> Result<()> {
 }
 
 /// Clean content for logging (remove sensitive data and truncate)
-#[allow(dead_code)]
 pub fn clean_content_for_logging(content: &str) -> String {
     let mut cleaned = content.to_string();
     
-    // Truncate if too long for logging
+    // Truncate if too long for logging, backing off to a char boundary so multi-byte
+    // characters straddling the limit don't cause a panic
     if cleaned.len() > 500 {
-        cleaned = format!("{}... [truncated]", &cleaned[..500]);
+        let end = (0..=500).rev().find(|&i| cleaned.is_char_boundary(i)).unwrap_or(0);
+        cleaned = format!("{}... [truncated]", &cleaned[..end]);
     }
     
     cleaned
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_prompt_content_reports_first_match() {
+        let err = validate_prompt_content("Summarize this.\n<script src=\"x.js\">").unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("'<script src=\"x.js\">'"), "{}", message);
+        assert!(message.contains("at byte 16"), "{}", message);
+
+        let err = validate_prompt_content("Click <a onclick = go()>").unwrap_err();
+        assert!(err.to_string().contains("'onclick =' at byte 9"), "{}", err);
+
+        assert!(validate_prompt_content("Use <context> XML tags freely").is_ok());
+    }
+
+    #[test]
+    fn test_clean_content_for_logging_truncates_on_char_boundary() {
+        // 499 ASCII bytes then a 3-byte character spanning bytes 499..502
+        let content = format!("{}€tail", "a".repeat(499));
+        let cleaned = clean_content_for_logging(&content);
+        assert_eq!(cleaned, format!("{}... [truncated]", "a".repeat(499)));
+
+        let short = "héllo";
+        assert_eq!(clean_content_for_logging(short), short);
+    }
+}
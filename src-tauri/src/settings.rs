@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+use rusqlite::{params, OptionalExtension};
+use crate::db::get_database;
+use crate::error::AppError;
+
+/// Setting key: bump the patch version when an external file edit changes the body
+pub const AUTO_BUMP_ON_FILE_SAVE: &str = "auto_bump_on_file_save";
+/// Setting key: name recorded as the author of new versions
+pub const AUTHOR: &str = "author";
+/// Setting key: trim and lowercase category path segments when a category is assigned
+pub const NORMALIZE_CATEGORY_PATHS: &str = "normalize_category_paths";
+/// Setting key: largest `limit` accepted by list queries such as get_recently_accessed
+pub const MAX_RESULT_LIMIT: &str = "max_result_limit";
+
+/// Default for MAX_RESULT_LIMIT
+pub const DEFAULT_MAX_RESULT_LIMIT: usize = 100;
+/// Highest value MAX_RESULT_LIMIT may be set to
+pub const MAX_RESULT_LIMIT_CEILING: usize = 1000;
+
+/// Application settings stored in the `settings` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    /// When true, a body change picked up from a file edit creates a new patch version even
+    /// if the file's version field wasn't changed. When false (default), a new version is only
+    /// created when the file declares a version number the prompt doesn't have yet.
+    pub auto_bump_on_file_save: bool,
+    /// Name recorded on new versions for attribution in shared libraries.
+    /// None when unset, in which case the OS username is used.
+    pub author: Option<String>,
+    /// When true, category paths are stored trimmed and lowercased ("Work / ML" becomes
+    /// "work/ml") so case variants don't become separate categories. Off by default so
+    /// existing paths are kept as typed; normalize_categories merges existing variants.
+    pub normalize_category_paths: bool,
+    /// Cap on the `limit` of list queries (default 100, at most 1000). Every returned row is
+    /// held in memory and serialized to the UI at once, so raising it trades memory and
+    /// responsiveness for larger result sets.
+    pub max_result_limit: usize,
+}
+
+/// Read a boolean setting, falling back to `default` when it was never set
+pub fn get_bool_setting(conn: &rusqlite::Connection, key: &str, default: bool) -> rusqlite::Result<bool> {
+    let value: Option<String> = conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        [key],
+        |row| row.get(0)
+    ).optional()?;
+
+    Ok(value.map_or(default, |value| value == "true"))
+}
+
+fn set_bool_setting(conn: &rusqlite::Connection, key: &str, value: bool) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value.to_string()],
+    )?;
+    Ok(())
+}
+
+fn get_string_setting(conn: &rusqlite::Connection, key: &str) -> rusqlite::Result<Option<String>> {
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        [key],
+        |row| row.get(0)
+    ).optional()
+}
+
+/// Store a string setting; None removes it
+fn set_string_setting(conn: &rusqlite::Connection, key: &str, value: Option<&str>) -> rusqlite::Result<()> {
+    match value {
+        Some(value) => conn.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?,
+        None => conn.execute("DELETE FROM settings WHERE key = ?1", [key])?,
+    };
+    Ok(())
+}
+
+/// Largest `limit` list queries accept: the `max_result_limit` setting, else the default.
+/// Out-of-range stored values are clamped to 1..=MAX_RESULT_LIMIT_CEILING.
+pub fn max_result_limit(conn: &rusqlite::Connection) -> rusqlite::Result<usize> {
+    Ok(get_string_setting(conn, MAX_RESULT_LIMIT)?
+        .and_then(|value| value.parse::<usize>().ok())
+        .map_or(DEFAULT_MAX_RESULT_LIMIT, |limit| limit.clamp(1, MAX_RESULT_LIMIT_CEILING)))
+}
+
+/// Author to record on a new version: the `author` setting, else the OS username
+pub fn current_author(conn: &rusqlite::Connection) -> rusqlite::Result<Option<String>> {
+    if let Some(author) = get_string_setting(conn, AUTHOR)? {
+        return Ok(Some(author));
+    }
+    Ok(["USER", "USERNAME"].iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(|name| name.trim().to_string())
+        .find(|name| !name.is_empty()))
+}
+
+fn load_settings(conn: &rusqlite::Connection) -> rusqlite::Result<Settings> {
+    Ok(Settings {
+        auto_bump_on_file_save: get_bool_setting(conn, AUTO_BUMP_ON_FILE_SAVE, false)?,
+        author: get_string_setting(conn, AUTHOR)?,
+        normalize_category_paths: get_bool_setting(conn, NORMALIZE_CATEGORY_PATHS, false)?,
+        max_result_limit: max_result_limit(conn)?,
+    })
+}
+
+#[tauri::command]
+pub async fn get_settings() -> std::result::Result<Settings, String> {
+    let db = get_database()?;
+    let settings = db.with_connection(load_settings)?;
+    Ok(settings)
+}
+
+/// Update settings; only supplied fields change. An empty `author` clears it so the OS
+/// username is used again. `max_result_limit` must be between 1 and 1000.
+/// Returns the resulting settings.
+#[tauri::command]
+pub async fn update_settings(
+    auto_bump_on_file_save: Option<bool>,
+    author: Option<String>,
+    normalize_category_paths: Option<bool>,
+    max_result_limit: Option<usize>,
+) -> std::result::Result<Settings, String> {
+    log::info!(
+        "Updating settings: auto_bump_on_file_save={:?}, author={:?}, normalize_category_paths={:?}, max_result_limit={:?}",
+        auto_bump_on_file_save, author, normalize_category_paths, max_result_limit
+    );
+
+    if max_result_limit.is_some_and(|limit| !(1..=MAX_RESULT_LIMIT_CEILING).contains(&limit)) {
+        return Err(AppError::Validation(format!(
+            "max_result_limit must be between 1 and {}", MAX_RESULT_LIMIT_CEILING
+        )).into());
+    }
+
+    let author = author.map(|author| author.trim().to_string());
+    if let Some(ref author) = author {
+        if author.len() > 100 || author.contains('<') || author.contains('>') {
+            return Err(AppError::Validation("Author must be at most 100 characters, without HTML".to_string()).into());
+        }
+    }
+
+    let db = get_database()?;
+    let settings = db.with_transaction(|tx| {
+        if let Some(value) = auto_bump_on_file_save {
+            set_bool_setting(tx, AUTO_BUMP_ON_FILE_SAVE, value)?;
+        }
+        if let Some(value) = normalize_category_paths {
+            set_bool_setting(tx, NORMALIZE_CATEGORY_PATHS, value)?;
+        }
+        if let Some(limit) = max_result_limit {
+            set_string_setting(tx, MAX_RESULT_LIMIT, Some(&limit.to_string()))?;
+        }
+        if let Some(ref author) = author {
+            set_string_setting(tx, AUTHOR, Some(author).filter(|author| !author.is_empty()).map(String::as_str))?;
+        }
+        Ok(load_settings(tx)?)
+    })?;
+
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_connection;
+
+    #[test]
+    fn test_bool_setting_round_trip() {
+        let conn = test_connection();
+
+        assert!(!get_bool_setting(&conn, AUTO_BUMP_ON_FILE_SAVE, false).unwrap());
+        assert!(get_bool_setting(&conn, AUTO_BUMP_ON_FILE_SAVE, true).unwrap());
+
+        set_bool_setting(&conn, AUTO_BUMP_ON_FILE_SAVE, true).unwrap();
+        assert!(load_settings(&conn).unwrap().auto_bump_on_file_save);
+
+        set_bool_setting(&conn, AUTO_BUMP_ON_FILE_SAVE, false).unwrap();
+        assert!(!get_bool_setting(&conn, AUTO_BUMP_ON_FILE_SAVE, true).unwrap());
+    }
+
+    #[test]
+    fn test_current_author_prefers_setting() {
+        let conn = test_connection();
+
+        set_string_setting(&conn, AUTHOR, Some("Ada")).unwrap();
+        assert_eq!(current_author(&conn).unwrap().as_deref(), Some("Ada"));
+        assert_eq!(load_settings(&conn).unwrap().author.as_deref(), Some("Ada"));
+
+        set_string_setting(&conn, AUTHOR, None).unwrap();
+        assert_eq!(load_settings(&conn).unwrap().author, None);
+        assert_ne!(current_author(&conn).unwrap().as_deref(), Some("Ada"));
+    }
+
+    #[test]
+    fn test_max_result_limit_defaults_and_clamps() {
+        let conn = test_connection();
+
+        assert_eq!(max_result_limit(&conn).unwrap(), DEFAULT_MAX_RESULT_LIMIT);
+
+        set_string_setting(&conn, MAX_RESULT_LIMIT, Some("500")).unwrap();
+        assert_eq!(load_settings(&conn).unwrap().max_result_limit, 500);
+
+        set_string_setting(&conn, MAX_RESULT_LIMIT, Some("50000")).unwrap();
+        assert_eq!(max_result_limit(&conn).unwrap(), MAX_RESULT_LIMIT_CEILING);
+
+        set_string_setting(&conn, MAX_RESULT_LIMIT, Some("lots")).unwrap();
+        assert_eq!(max_result_limit(&conn).unwrap(), DEFAULT_MAX_RESULT_LIMIT);
+    }
+}
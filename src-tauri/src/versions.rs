@@ -1,14 +1,20 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::Utc;
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension};
 use crate::db::get_database;
 use crate::error::{AppError, Result};
-use crate::security::{validate_prompt_content, validate_uuid};
+use crate::metadata::PromptMetadata;
+use crate::prompts::{ensure_unlocked, parse_rfc3339_filter};
+use crate::markdown::{build_markdown, parse_filename, parse_frontmatter, render_markdown_html, resolve_prod_file_path, resolve_version_file_path, slugify, write_if_changed, FileAction, Frontmatter};
+use crate::security::{validate_prompt_content, validate_tag, validate_uuid};
+use crate::paths::prompts_dir;
+use crate::settings::{current_author, max_result_limit};
 use regex::Regex;
 use lazy_static::lazy_static;
-use tauri::Manager;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Version {
@@ -19,6 +25,29 @@ pub struct Version {
     pub metadata: Option<String>,
     pub created_at: String,
     pub parent_uuid: Option<String>,
+    /// Who created the version (see the `author` setting); None for versions from before it existed
+    pub author: Option<String>,
+}
+
+/// A newly saved version with the absolute path of the markdown file holding it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedVersion {
+    #[serde(flatten)]
+    pub version: Version,
+    /// None when syncing the file failed; the version itself is saved regardless
+    pub file_path: Option<String>,
+}
+
+/// A version together with its version-level tags, as returned by get_version_by_uuid
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionDetails {
+    #[serde(flatten)]
+    pub version: Version,
+    /// Tags on this version only (e.g. "shipped"); distinct from the prompt's tags
+    pub version_tags: Vec<String>,
+    /// Model and runtime parameters recorded in the version's metadata
+    pub target_model: Option<String>,
+    pub parameters: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -27,10 +56,11 @@ pub struct VersionInfo {
     pub semver: String,
     pub created_at: String,
     pub parent_uuid: Option<String>,
+    pub author: Option<String>,
 }
 
 // Semantic version parsing and bumping utilities
-fn parse_semver(version: &str) -> Result<(u32, u32, u32)> {
+pub fn parse_semver(version: &str) -> Result<(u32, u32, u32)> {
     lazy_static! {
         static ref SEMVER_REGEX: Regex = Regex::new(r"^(\d+)\.(\d+)\.(\d+)$").unwrap();
     }
@@ -48,34 +78,216 @@ fn parse_semver(version: &str) -> Result<(u32, u32, u32)> {
     Ok((major, minor, patch))
 }
 
-fn bump_patch_version(version: &str) -> Result<String> {
+pub fn bump_patch_version(version: &str) -> Result<String> {
     let (major, minor, patch) = parse_semver(version)?;
     Ok(format!("{}.{}.{}", major, minor, patch + 1))
 }
 
-/// Check for version conflicts (same content)
+/// The semver for a prompt's next version: one patch past its highest semver, counting trashed
+/// versions, which keep their number in idx_versions_unique_semver until purged. None when the
+/// prompt has no versions at all.
+pub fn next_patch_semver(conn: &rusqlite::Connection, prompt_uuid: &str) -> Result<Option<String>> {
+    let highest: Option<String> = conn.query_row(
+        &format!("SELECT semver FROM versions WHERE prompt_uuid = ?1 ORDER BY {} LIMIT 1", LATEST_VERSION_FIRST),
+        [prompt_uuid],
+        |row| row.get(0)
+    ).optional()?;
+    highest.map(|semver| bump_patch_version(&semver)).transpose()
+}
+
+/// Maximum number of bumped semvers tried when concurrent saves collide on the same number
+const MAX_SEMVER_RETRIES: usize = 10;
+
+/// Insert a version row, starting from `version.semver`. If another writer already took that
+/// semver (UNIQUE violation on idx_versions_unique_semver), the patch number is bumped and the
+/// insert retried, up to MAX_SEMVER_RETRIES times. `version.semver` holds the number actually used.
+pub fn insert_version_with_retry(conn: &rusqlite::Connection, version: &mut Version) -> Result<()> {
+    for attempt in 0..MAX_SEMVER_RETRIES {
+        let result = conn.execute(
+            "INSERT INTO versions (uuid, prompt_uuid, semver, body, metadata, created_at, parent_uuid, author) 
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                &version.uuid,
+                &version.prompt_uuid,
+                &version.semver,
+                &version.body,
+                &version.metadata,
+                &version.created_at,
+                &version.parent_uuid,
+                &version.author
+            ],
+        );
+        
+        match result {
+            Ok(_) => return Ok(()),
+            Err(rusqlite::Error::SqliteFailure(e, _))
+                if e.extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_UNIQUE =>
+            {
+                log::warn!("Version {} already exists for prompt {} (attempt {}), bumping",
+                           version.semver, version.prompt_uuid, attempt + 1);
+                version.semver = bump_patch_version(&version.semver)?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    
+    log::error!("Could not allocate a version number for prompt {} after {} attempts",
+                version.prompt_uuid, MAX_SEMVER_RETRIES);
+    Err(AppError::Conflict("Version number collision, please retry".to_string()))
+}
+
+/// Number of versions returned by list_versions / list_versions_full
+const VERSION_LIST_LIMIT: usize = 5;
+
+/// Sort versions newest-first by parsed semver (major, minor, patch), with created_at as a
+/// tiebreaker. Semvers are stored as text, so SQL ordering would put 1.0.10 before 1.0.9;
+/// unparseable semvers sort last.
+fn sort_by_semver_desc<T>(versions: &mut [T], key: impl Fn(&T) -> (&str, &str)) {
+    versions.sort_by(|a, b| {
+        let (semver_a, created_a) = key(a);
+        let (semver_b, created_b) = key(b);
+        parse_semver(semver_b).ok().cmp(&parse_semver(semver_a).ok())
+            .then_with(|| created_b.cmp(created_a))
+    });
+}
+
+/// ORDER BY terms that put a prompt's latest version first, matching sort_by_semver_desc:
+/// highest semver compared numerically, then newest created_at; semvers parse_semver rejects
+/// sort last. Every "latest version" lookup uses this so history, editor and exports agree.
+pub const LATEST_VERSION_FIRST: &str = "
+    (semver GLOB '[0-9]*.[0-9]*.[0-9]*' AND semver NOT GLOB '*[^0-9.]*'
+     AND length(semver) - length(replace(semver, '.', '')) = 2) DESC,
+    CAST(semver AS INTEGER) DESC,
+    CAST(substr(semver, instr(semver, '.') + 1) AS INTEGER) DESC,
+    CAST(substr(substr(semver, instr(semver, '.') + 1), instr(substr(semver, instr(semver, '.') + 1), '.') + 1) AS INTEGER) DESC,
+    created_at DESC";
+
+/// Body with trailing whitespace removed from every line and no trailing newlines,
+/// so "a  \nb\n\n" and "a\nb" compare equal
+fn normalize_trailing_whitespace(body: &str) -> String {
+    body.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end_matches('\n')
+        .to_string()
+}
+
+/// Whether a line starts a block that must stay on its own line: list items, headings,
+/// blockquotes, table rows, indented code and XML-style tags
+fn starts_block(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    if line.starts_with("    ") || line.starts_with('\t') {
+        return true;
+    }
+    if ["- ", "* ", "+ ", "#", ">", "|", "<"].iter().any(|marker| trimmed.starts_with(marker)) {
+        return true;
+    }
+    // Ordered list items: "1. " or "1) "
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && (trimmed[digits..].starts_with(". ") || trimmed[digits..].starts_with(") "))
+}
+
+/// Whether the line after this one must start a new line rather than be joined to it
+fn ends_block(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.starts_with('#') || trimmed.starts_with('|') || trimmed.ends_with('>') || line.ends_with("  ")
+}
+
+/// Undo hard wrapping from copied text: lines broken mid-paragraph are joined with a space and
+/// runs of spaces collapse to one. Blank lines still separate paragraphs, and lists, headings,
+/// quotes, tables, XML tags and fenced code blocks keep their line structure.
+fn soft_wrap_paragraphs(body: &str) -> String {
+    let mut output: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    let mut joinable = false;
+
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            output.push(line.trim_end().to_string());
+            joinable = false;
+            continue;
+        }
+        if in_fence {
+            output.push(line.to_string());
+            continue;
+        }
+        if line.trim().is_empty() {
+            output.push(String::new());
+            joinable = false;
+            continue;
+        }
+
+        let indent_len = line.len() - line.trim_start().len();
+        let collapsed = line.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        match output.last_mut() {
+            Some(previous) if joinable && !starts_block(line) => {
+                previous.push(' ');
+                previous.push_str(&collapsed);
+            }
+            _ => output.push(format!("{}{}", &line[..indent_len], collapsed)),
+        }
+        joinable = !ends_block(line);
+    }
+
+    let mut result = output.join("\n");
+    if body.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Find an existing (non-deleted) version of the prompt with the same content.
+/// With `ignore_trailing_whitespace`, bodies that differ only in trailing whitespace per line
+/// or in final newlines count as the same content.
 fn detect_version_conflict(
-    tx: &rusqlite::Transaction,
+    conn: &rusqlite::Connection,
     prompt_uuid: &str,
     new_body: &str,
-) -> Result<Option<String>> {
-    let mut stmt = tx.prepare(
-        "SELECT semver FROM versions 
-         WHERE prompt_uuid = ?1 AND body = ?2 
-         LIMIT 1"
-    )?;
+    ignore_trailing_whitespace: bool,
+) -> Result<Option<VersionInfo>> {
+    let map_row = |row: &rusqlite::Row| Ok(VersionInfo {
+        uuid: row.get(0)?,
+        semver: row.get(1)?,
+        created_at: row.get(2)?,
+        parent_uuid: row.get(3)?,
+        author: row.get(4)?,
+    });
     
-    let mut rows = stmt.query_map([prompt_uuid, new_body], |row| {
-        Ok(row.get::<_, String>(0)?)
-    })?;
+    if !ignore_trailing_whitespace {
+        let existing = conn.query_row(
+            "SELECT uuid, semver, created_at, parent_uuid, author FROM versions 
+             WHERE prompt_uuid = ?1 AND body = ?2 AND deleted_at IS NULL 
+             ORDER BY created_at 
+             LIMIT 1",
+            [prompt_uuid, new_body],
+            map_row
+        ).optional()?;
+        return Ok(existing);
+    }
     
-    match rows.next() {
-        Some(row) => Ok(Some(row?)),
-        None => Ok(None),
+    // Normalized comparison has to happen here rather than in SQL
+    let normalized = normalize_trailing_whitespace(new_body);
+    let mut stmt = conn.prepare(
+        "SELECT uuid, semver, created_at, parent_uuid, author, body FROM versions 
+         WHERE prompt_uuid = ?1 AND deleted_at IS NULL 
+         ORDER BY created_at"
+    )?;
+    let mut rows = stmt.query([prompt_uuid])?;
+    while let Some(row) = rows.next()? {
+        let body: String = row.get(5)?;
+        if normalize_trailing_whitespace(&body) == normalized {
+            return Ok(Some(map_row(row)?));
+        }
     }
+    
+    Ok(None)
 }
 
-/// Create or update markdown file for a version
+/// Create or update markdown file for a version, returning its path (also when the file
+/// already had this content and wasn't rewritten)
 fn sync_version_to_file(
     app_handle: &tauri::AppHandle,
     prompt_uuid: &str,
@@ -83,49 +295,44 @@ fn sync_version_to_file(
     body: &str,
     semver: &str,
     tags: &[String],
-) -> Result<()> {
-    let documents_dir = app_handle
-        .path()
-        .document_dir()
-        .map_err(|e| AppError::Path(e.to_string()))?;
-    
-    let prompts_dir = documents_dir.join("PromptMaster");
+    author: Option<&str>,
+) -> Result<PathBuf> {
+    let prompts_dir = prompts_dir(app_handle)?;
     std::fs::create_dir_all(&prompts_dir)?;
     
     let date = Utc::now().format("%Y-%m-%d").to_string();
     
     // Sanitize title for filename
-    let slug = title
-        .chars()
-        .filter_map(|c| {
-            if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' {
-                Some(c.to_ascii_lowercase())
-            } else {
-                None
-            }
-        })
-        .collect::<String>()
-        .replace(' ', "-");
-    
-    let filename = format!("{}--{}--v{}.md", date, slug, semver);
-    let file_path = prompts_dir.join(&filename);
-    
-    // Check if file already exists and has same content to avoid unnecessary writes
-    if file_path.exists() {
-        if let Ok(existing_content) = fs::read_to_string(&file_path) {
-            let new_content = create_markdown_content(prompt_uuid, title, body, semver, tags);
-            if existing_content == new_content {
-                log::debug!("Skipping file write - content unchanged: {}", filename);
-                return Ok(());
-            }
-        }
-    }
+    let slug = slugify(title);
+    
+    let file_path = resolve_version_file_path(&prompts_dir, &date, &slug, semver, prompt_uuid);
+    let filename = file_path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
     
-    let frontmatter = create_markdown_content(prompt_uuid, title, body, semver, tags);
-    fs::write(&file_path, frontmatter)?;
+    // Custom frontmatter fields live in the version's metadata; keep them in the rewritten file
+    let custom_fields = get_database()?.with_connection(|conn| {
+        conn.query_row(
+            "SELECT metadata FROM versions WHERE prompt_uuid = ?1 AND semver = ?2",
+            [prompt_uuid, semver],
+            |row| row.get::<_, Option<String>>(0)
+        ).optional()
+    })?.flatten().as_deref().and_then(custom_fields_of);
+    
+    // Skip the write when the file already has the same content to avoid triggering the watcher
+    let frontmatter = create_markdown_content(prompt_uuid, title, body, semver, tags, author, custom_fields);
+    if write_if_changed(&file_path, &frontmatter)?.action == FileAction::Unchanged {
+        log::debug!("Skipping file write - content unchanged: {}", filename);
+        return Ok(file_path);
+    }
     
     log::info!("Synced version {} to file: {}", semver, filename);
-    Ok(())
+    Ok(file_path)
+}
+
+/// custom_fields of a version's metadata JSON, if any
+fn custom_fields_of(metadata_json: &str) -> Option<serde_json::Value> {
+    PromptMetadata::from_json(metadata_json).ok().and_then(|metadata| metadata.custom_fields)
 }
 
 /// Create markdown content with frontmatter
@@ -135,28 +342,22 @@ fn create_markdown_content(
     body: &str,
     semver: &str,
     tags: &[String],
+    author: Option<&str>,
+    custom_fields: Option<serde_json::Value>,
 ) -> String {
     let now = Utc::now().format("%Y-%m-%d").to_string();
     
-    format!(
-        r#"---
-uuid: "{}"
-version: "{}"
-title: "{}"
-tags: {:?}
-created: {}
-modified: {}
----
-
-{}"#,
-        uuid,
-        semver,
-        title,
-        tags,
-        now,
-        now,
-        body
-    )
+    build_markdown(&Frontmatter {
+        uuid: uuid.to_string(),
+        version: semver.to_string(),
+        title: title.to_string(),
+        tags: tags.to_vec(),
+        created: now.clone(),
+        modified: now,
+        author: author.map(str::to_string),
+        custom_fields,
+        ..Frontmatter::default()
+    }, body)
 }
 
 /// Get the latest version body for a prompt
@@ -170,13 +371,14 @@ pub async fn get_latest_version(prompt_uuid: String) -> std::result::Result<Opti
     let db = get_database()?;
     
     let result = db.with_connection(|conn| {
-        // Get the latest version by created_at (most recent)
-        let mut stmt = conn.prepare(
+        // Get the latest version (highest semver)
+        let mut stmt = conn.prepare(&format!(
             "SELECT body FROM versions 
-             WHERE prompt_uuid = ?1 
-             ORDER BY created_at DESC
-             LIMIT 1"
-        )?;
+             WHERE prompt_uuid = ?1 AND deleted_at IS NULL 
+             ORDER BY {}
+             LIMIT 1",
+            LATEST_VERSION_FIRST
+        ))?;
         
         let mut rows = stmt.query_map([&prompt_uuid], |row| {
             Ok(row.get::<_, String>(0)?)
@@ -197,15 +399,76 @@ pub async fn get_latest_version(prompt_uuid: String) -> std::result::Result<Opti
     Ok(result)
 }
 
-/// Save a new version with automatic patch bump
+/// Most prompts get_latest_versions accepts in one call
+const MAX_LATEST_VERSIONS_BATCH: usize = 500;
+
+/// Latest version body of each prompt in `prompt_uuids`, in a single query.
+/// "Latest" matches get_latest_version: LATEST_VERSION_FIRST, trashed versions excluded.
+fn load_latest_bodies(conn: &rusqlite::Connection, prompt_uuids: &[String]) -> rusqlite::Result<HashMap<String, String>> {
+    let uuids_json = serde_json::to_string(prompt_uuids)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT prompt_uuid, body FROM (
+             SELECT prompt_uuid, body,
+                    ROW_NUMBER() OVER (PARTITION BY prompt_uuid ORDER BY {}) AS rank
+             FROM versions
+             WHERE deleted_at IS NULL AND prompt_uuid IN (SELECT value FROM json_each(?1))
+         )
+         WHERE rank = 1",
+        LATEST_VERSION_FIRST
+    ))?;
+    let rows = stmt.query_map([uuids_json], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// Get the latest version body of many prompts at once (for list and grid previews).
+/// Returns prompt_uuid → body; prompts without versions are left out.
+#[tauri::command]
+pub async fn get_latest_versions(prompt_uuids: Vec<String>) -> std::result::Result<HashMap<String, String>, String> {
+    if prompt_uuids.len() > MAX_LATEST_VERSIONS_BATCH {
+        return Err(AppError::InvalidInput(format!(
+            "Too many prompts requested (max {})", MAX_LATEST_VERSIONS_BATCH
+        )).into());
+    }
+    for prompt_uuid in &prompt_uuids {
+        validate_uuid(prompt_uuid)?;
+    }
+    
+    let db = get_database()?;
+    let bodies = db.with_connection(|conn| load_latest_bodies(conn, &prompt_uuids))?;
+    
+    log::debug!("Retrieved latest versions for {} of {} prompts", bodies.len(), prompt_uuids.len());
+    Ok(bodies)
+}
+
+/// Save a new version with automatic patch bump.
+/// The previous latest version's metadata (notes, models, ...) carries forward to the new
+/// version; fields supplied in `metadata_json` are merged on top of it.
+/// A body identical to an existing version is rejected with a structured `duplicate_content`
+/// error describing that version, unless `allow_duplicate` is true. With
+/// `ignore_trailing_whitespace`, bodies differing only in trailing whitespace also count as identical.
+/// With `normalize_whitespace`, hard-wrapped paragraphs are joined and repeated spaces collapsed
+/// before anything else (see soft_wrap_paragraphs); off by default.
+/// Returns the version along with the path of its markdown file.
 #[tauri::command]
 pub async fn save_new_version(
     prompt_uuid: String,
     body: String,
+    metadata_json: Option<String>,
+    allow_duplicate: Option<bool>,
+    ignore_trailing_whitespace: Option<bool>,
+    normalize_whitespace: Option<bool>,
     app_handle: tauri::AppHandle,
-) -> std::result::Result<Version, String> {
+) -> std::result::Result<SavedVersion, String> {
     log::info!("Saving new version for prompt: {} (body: {} chars)", prompt_uuid, body.len());
     
+    // Soft-wrap first so validation and the duplicate check see the body that gets stored
+    let body = if normalize_whitespace.unwrap_or(false) {
+        soft_wrap_paragraphs(&body)
+    } else {
+        body
+    };
+    
     // Validate input with security checks
     validate_uuid(&prompt_uuid)?;
     validate_prompt_content(&body)?;
@@ -217,11 +480,22 @@ pub async fn save_new_version(
         return Err("Version body too long (max 100,000 characters)".to_string());
     }
     
+    let new_metadata = match metadata_json {
+        Some(ref json_str) => {
+            let metadata = PromptMetadata::from_json(json_str)?;
+            metadata.validate()?;
+            Some(metadata)
+        }
+        None => None,
+    };
+    
     let db = get_database()?;
     let version_uuid = Uuid::now_v7().to_string();
     let now = Utc::now().to_rfc3339();
     
     let result = db.with_transaction(|tx| {
+        ensure_unlocked(tx, &prompt_uuid)?;
+        
         // Get prompt details (title, tags) and verify it exists
         let (prompt_title, prompt_tags): (String, String) = {
             let mut stmt = tx.prepare("SELECT title, tags FROM prompts WHERE uuid = ?1")?;
@@ -231,95 +505,72 @@ pub async fn save_new_version(
             
             match rows.next() {
                 Some(row) => row?,
-                None => return Err(rusqlite::Error::InvalidColumnName(
+                None => return Err(AppError::NotFound(
                     format!("Prompt with UUID {} does not exist", prompt_uuid)
                 )),
             }
         };
         
         // Check for version conflicts (same content already exists)
-        if let Some(existing_version) = detect_version_conflict(tx, &prompt_uuid, &body)
-            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))? {
-            return Err(rusqlite::Error::InvalidColumnName(
-                format!("Content already exists in version {}", existing_version)
-            ));
+        if !allow_duplicate.unwrap_or(false) {
+            let ignore_whitespace = ignore_trailing_whitespace.unwrap_or(false);
+            if let Some(existing) = detect_version_conflict(tx, &prompt_uuid, &body, ignore_whitespace)? {
+                return Err(AppError::DuplicateContent { existing });
+            }
         }
         
-        // Get the latest version to determine next semver
-        // Use a more robust query that handles race conditions
+        // Get the latest version, the new version's parent
         let latest_version = {
-            let mut stmt = tx.prepare(
-                "SELECT semver, uuid FROM versions 
-                 WHERE prompt_uuid = ?1 
-                 ORDER BY created_at DESC, semver DESC
-                 LIMIT 1"
-            )?;
+            let mut stmt = tx.prepare(&format!(
+                "SELECT uuid, metadata FROM versions 
+                 WHERE prompt_uuid = ?1 AND deleted_at IS NULL 
+                 ORDER BY {}
+                 LIMIT 1",
+                LATEST_VERSION_FIRST
+            ))?;
             
             let mut rows = stmt.query_map([&prompt_uuid], |row| {
-                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?
+                ))
             })?;
             
             rows.next().transpose()?
         };
         
-        let (new_semver, parent_uuid) = match latest_version {
-            Some((latest_semver, latest_uuid)) => {
-                // Try to bump version, but handle potential duplicates
-                let mut candidate_semver = bump_patch_version(&latest_semver)
-                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-                
-                // Check if this semver already exists (race condition protection)
-                let mut check_stmt = tx.prepare(
-                    "SELECT COUNT(*) FROM versions WHERE prompt_uuid = ?1 AND semver = ?2"
-                )?;
-                let exists: i64 = check_stmt.query_row([&prompt_uuid, &candidate_semver], |row| {
-                    Ok(row.get(0)?)
-                })?;
-                
-                // If the semver already exists, find the actual latest and increment from there
-                if exists > 0 {
-                    log::warn!("Version {} already exists, finding actual latest version", candidate_semver);
-                    
-                    // Get the highest existing semver
-                    let mut max_stmt = tx.prepare(
-                        "SELECT semver FROM versions 
-                         WHERE prompt_uuid = ?1 
-                         ORDER BY 
-                           CAST(substr(semver, 1, instr(semver, '.') - 1) AS INTEGER) DESC,
-                           CAST(substr(semver, instr(semver, '.') + 1, instr(substr(semver, instr(semver, '.') + 1), '.') - 1) AS INTEGER) DESC,
-                           CAST(substr(semver, length(semver) - instr(reverse(semver), '.') + 2) AS INTEGER) DESC
-                         LIMIT 1"
-                    )?;
-                    
-                    let highest_semver: String = max_stmt.query_row([&prompt_uuid], |row| {
-                        Ok(row.get(0)?)
-                    })?;
-                    
-                    candidate_semver = bump_patch_version(&highest_semver)
-                        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-                }
-                
-                (candidate_semver, Some(latest_uuid))
-            }
-            None => {
-                // First version
-                ("1.0.0".to_string(), None)
+        // Carry the previous metadata forward and apply any supplied fields on top
+        let previous_metadata = latest_version.as_ref()
+            .and_then(|(_, metadata)| metadata.as_deref())
+            .and_then(|json_str| PromptMetadata::from_json(json_str).ok());
+        let version_metadata = match (previous_metadata, &new_metadata) {
+            (Some(mut previous), Some(new)) => {
+                previous.merge_with(new);
+                Some(previous)
             }
+            (Some(previous), None) => Some(previous),
+            (None, Some(new)) => Some(new.clone()),
+            (None, None) => None,
         };
+        let version_metadata_json = version_metadata
+            .map(|metadata| metadata.to_json())
+            .transpose()?;
         
-        // Insert new version
-        tx.execute(
-            "INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at, parent_uuid) 
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![
-                &version_uuid,
-                &prompt_uuid,
-                &new_semver,
-                &body,
-                &now,
-                &parent_uuid
-            ],
-        )?;
+        let candidate_semver = next_patch_semver(tx, &prompt_uuid)?.unwrap_or_else(|| "1.0.0".to_string());
+        let parent_uuid = latest_version.map(|(latest_uuid, _)| latest_uuid);
+        
+        // Insert new version, bumping again if a concurrent save took the number
+        let mut version = Version {
+            uuid: version_uuid.clone(),
+            prompt_uuid: prompt_uuid.clone(),
+            semver: candidate_semver,
+            body: body.clone(),
+            metadata: version_metadata_json,
+            created_at: now.clone(),
+            parent_uuid,
+            author: current_author(tx)?,
+        };
+        insert_version_with_retry(tx, &mut version)?;
         
         // Update prompt's updated_at timestamp
         tx.execute(
@@ -327,33 +578,87 @@ pub async fn save_new_version(
             params![&now, &prompt_uuid],
         )?;
         
-        Ok((Version {
-            uuid: version_uuid.clone(),
-            prompt_uuid: prompt_uuid.clone(),
-            semver: new_semver.clone(),
-            body: body.clone(),
-            metadata: None,
-            created_at: now,
-            parent_uuid,
-        }, prompt_title, prompt_tags, new_semver))
+        let new_semver = version.semver.clone();
+        Ok((version, prompt_title, prompt_tags, new_semver))
     })?;
     
     // Sync to file system after successful database transaction
     let tags: Vec<String> = serde_json::from_str(&result.2)
         .unwrap_or_else(|_| Vec::new());
     
-    if let Err(e) = sync_version_to_file(&app_handle, &prompt_uuid, &result.1, &result.0.body, &result.3, &tags) {
-        log::warn!("Failed to sync version to file: {}", e);
-        // Continue - don't fail the whole operation for file sync issues
-    }
+    let file_path = match sync_version_to_file(&app_handle, &prompt_uuid, &result.1, &result.0.body, &result.3, &tags, result.0.author.as_deref()) {
+        Ok(path) => Some(path.to_string_lossy().into_owned()),
+        Err(e) => {
+            log::warn!("Failed to sync version to file: {}", e);
+            // Continue - don't fail the whole operation for file sync issues
+            None
+        }
+    };
     
     log::info!("Successfully saved new version {} for prompt {}", 
                result.0.semver, prompt_uuid);
     
-    Ok(result.0)
+    Ok(SavedVersion {
+        version: result.0,
+        file_path,
+    })
+}
+
+/// Outcome of replace_in_latest
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplaceResult {
+    /// The new version, or None when nothing matched
+    pub version: Option<Version>,
+    pub replacements: usize,
+}
+
+/// Replace every occurrence of `find` in `body`, literally or as a regex (where `replace`
+/// may use `$1`, `${name}`). Returns the new body and the number of matches.
+fn replace_in_body(body: &str, find: &str, replace: &str, regex: bool) -> Result<(String, usize)> {
+    if find.is_empty() {
+        return Err(AppError::InvalidInput("Search text cannot be empty".to_string()));
+    }
+    
+    if regex {
+        let pattern = Regex::new(find)?;
+        let replacements = pattern.find_iter(body).count();
+        Ok((pattern.replace_all(body, replace).into_owned(), replacements))
+    } else {
+        Ok((body.replace(find, replace), body.matches(find).count()))
+    }
+}
+
+/// Find and replace in a prompt's latest version and save the result as a new version through
+/// save_new_version, so it is validated, bumped and synced like any other save.
+/// When nothing matches, no version is created and `version` is None.
+#[tauri::command]
+pub async fn replace_in_latest(
+    prompt_uuid: String,
+    find: String,
+    replace: String,
+    regex: bool,
+    app_handle: tauri::AppHandle,
+) -> std::result::Result<ReplaceResult, String> {
+    log::info!("Replacing in latest version of prompt {} (regex: {})", prompt_uuid, regex);
+    
+    validate_uuid(&prompt_uuid)?;
+    
+    let db = get_database()?;
+    let latest_body = db.with_connection(|conn| load_latest_bodies(conn, std::slice::from_ref(&prompt_uuid)))?
+        .remove(&prompt_uuid)
+        .ok_or_else(|| AppError::NotFound(format!("No versions for prompt {}", prompt_uuid)))?;
+    
+    let (body, replacements) = replace_in_body(&latest_body, &find, &replace, regex)?;
+    if replacements == 0 {
+        log::info!("No matches in latest version of prompt {}, nothing saved", prompt_uuid);
+        return Ok(ReplaceResult { version: None, replacements });
+    }
+    
+    let version = save_new_version(prompt_uuid, body, None, None, None, None, app_handle).await?.version;
+    Ok(ReplaceResult { version: Some(version), replacements })
 }
 
-/// List all versions for a prompt, ordered by semver descending
+/// List the most recent versions for a prompt, ordered by semver descending
 #[tauri::command]
 pub async fn list_versions(prompt_uuid: String) -> std::result::Result<Vec<VersionInfo>, String> {
     log::info!("Listing versions for prompt: {}", prompt_uuid);
@@ -366,10 +671,8 @@ pub async fn list_versions(prompt_uuid: String) -> std::result::Result<Vec<Versi
     
     let versions = db.with_connection(|conn| {
         let mut stmt = conn.prepare(
-            "SELECT uuid, semver, created_at, parent_uuid FROM versions 
-             WHERE prompt_uuid = ?1 
-             ORDER BY created_at DESC
-             LIMIT 5"
+            "SELECT uuid, semver, created_at, parent_uuid, author FROM versions 
+             WHERE prompt_uuid = ?1 AND deleted_at IS NULL"
         )?;
         
         let version_iter = stmt.query_map([&prompt_uuid], |row| {
@@ -378,6 +681,7 @@ pub async fn list_versions(prompt_uuid: String) -> std::result::Result<Vec<Versi
                 semver: row.get(1)?,
                 created_at: row.get(2)?,
                 parent_uuid: row.get(3)?,
+                author: row.get(4)?,
             })
         })?;
         
@@ -386,6 +690,8 @@ pub async fn list_versions(prompt_uuid: String) -> std::result::Result<Vec<Versi
             versions.push(version?);
         }
         
+        sort_by_semver_desc(&mut versions, |v| (v.semver.as_str(), v.created_at.as_str()));
+        versions.truncate(VERSION_LIST_LIMIT);
         Ok(versions)
     })?;
     
@@ -409,7 +715,8 @@ pub async fn list_versions(prompt_uuid: String) -> std::result::Result<Vec<Versi
     Ok(versions)
 }
 
-/// List all versions for a prompt with full content in a single query (performance optimized)
+/// List the most recent versions for a prompt with full content in a single query (performance optimized),
+/// ordered by semver descending
 #[tauri::command]
 pub async fn list_versions_full(prompt_uuid: String) -> std::result::Result<Vec<Version>, String> {
     log::info!("Listing full versions for prompt: {}", prompt_uuid);
@@ -421,11 +728,9 @@ pub async fn list_versions_full(prompt_uuid: String) -> std::result::Result<Vec<
     
     let versions = db.with_connection(|conn| {
         let mut stmt = conn.prepare(
-            "SELECT uuid, prompt_uuid, semver, body, metadata, created_at, parent_uuid 
+            "SELECT uuid, prompt_uuid, semver, body, metadata, created_at, parent_uuid, author 
              FROM versions 
-             WHERE prompt_uuid = ?1 
-             ORDER BY created_at DESC
-             LIMIT 5"
+             WHERE prompt_uuid = ?1 AND deleted_at IS NULL"
         )?;
         
         let version_iter = stmt.query_map([&prompt_uuid], |row| {
@@ -437,6 +742,7 @@ pub async fn list_versions_full(prompt_uuid: String) -> std::result::Result<Vec<
                 metadata: row.get(4)?,
                 created_at: row.get(5)?,
                 parent_uuid: row.get(6)?,
+                author: row.get(7)?,
             })
         })?;
         
@@ -445,6 +751,8 @@ pub async fn list_versions_full(prompt_uuid: String) -> std::result::Result<Vec<
             versions.push(version?);
         }
         
+        sort_by_semver_desc(&mut versions, |v| (v.semver.as_str(), v.created_at.as_str()));
+        versions.truncate(VERSION_LIST_LIMIT);
         Ok(versions)
     })?;
     
@@ -455,7 +763,7 @@ pub async fn list_versions_full(prompt_uuid: String) -> std::result::Result<Vec<
 
 /// Get a specific version by UUID
 #[tauri::command]
-pub async fn get_version_by_uuid(version_uuid: String) -> std::result::Result<Option<Version>, String> {
+pub async fn get_version_by_uuid(version_uuid: String) -> std::result::Result<Option<VersionDetails>, String> {
     log::info!("Getting version by UUID: {}", version_uuid);
     
     if version_uuid.trim().is_empty() {
@@ -466,8 +774,8 @@ pub async fn get_version_by_uuid(version_uuid: String) -> std::result::Result<Op
     
     let result = db.with_connection(|conn| {
         let mut stmt = conn.prepare(
-            "SELECT uuid, prompt_uuid, semver, body, metadata, created_at, parent_uuid 
-             FROM versions WHERE uuid = ?1"
+            "SELECT uuid, prompt_uuid, semver, body, metadata, created_at, parent_uuid, author 
+             FROM versions WHERE uuid = ?1 AND deleted_at IS NULL"
         )?;
         
         let mut rows = stmt.query_map([&version_uuid], |row| {
@@ -479,11 +787,24 @@ pub async fn get_version_by_uuid(version_uuid: String) -> std::result::Result<Op
                 metadata: row.get(4)?,
                 created_at: row.get(5)?,
                 parent_uuid: row.get(6)?,
+                author: row.get(7)?,
             })
         })?;
         
         match rows.next() {
-            Some(row) => Ok(Some(row?)),
+            Some(row) => {
+                let version = row?;
+                let version_tags = load_version_tags(conn, &version.uuid)?;
+                let metadata = version.metadata.as_deref()
+                    .and_then(|json| PromptMetadata::from_json(json).ok())
+                    .unwrap_or_default();
+                Ok(Some(VersionDetails {
+                    version,
+                    version_tags,
+                    target_model: metadata.target_model,
+                    parameters: metadata.parameters,
+                }))
+            }
             None => Ok(None),
         }
     })?;
@@ -493,6 +814,74 @@ pub async fn get_version_by_uuid(version_uuid: String) -> std::result::Result<Op
     Ok(result)
 }
 
+/// Walk parent_uuid links from a version back to its root.
+/// Returns the chain ordered from the oldest ancestor to the given version.
+#[tauri::command]
+pub async fn get_version_chain(version_uuid: String) -> std::result::Result<Vec<VersionInfo>, String> {
+    log::info!("Getting version chain for: {}", version_uuid);
+    
+    validate_uuid(&version_uuid)?;
+    
+    let db = get_database()?;
+    
+    let (mut chain, cycle_at) = db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT uuid, semver, created_at, parent_uuid, author FROM versions WHERE uuid = ?1"
+        )?;
+        
+        let mut chain = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        let mut next = Some(version_uuid.clone());
+        
+        while let Some(current) = next {
+            // A repeated UUID means the parent links loop back on themselves
+            if !seen.insert(current.clone()) {
+                return Ok((chain, Some(current)));
+            }
+            
+            let version = stmt.query_row([&current], |row| {
+                Ok(VersionInfo {
+                    uuid: row.get(0)?,
+                    semver: row.get(1)?,
+                    created_at: row.get(2)?,
+                    parent_uuid: row.get(3)?,
+                    author: row.get(4)?,
+                })
+            }).optional()?;
+            
+            match version {
+                Some(version) => {
+                    next = version.parent_uuid.clone();
+                    chain.push(version);
+                }
+                None => {
+                    if chain.is_empty() {
+                        return Err(rusqlite::Error::QueryReturnedNoRows);
+                    }
+                    // Dangling parent reference - the chain ends at the last known version
+                    log::warn!("Version chain for {} references missing parent {}", version_uuid, current);
+                    next = None;
+                }
+            }
+        }
+        
+        Ok((chain, None))
+    }).map_err(|e| match e {
+        AppError::Database(rusqlite::Error::QueryReturnedNoRows) => "Version not found".to_string(),
+        e => e.to_string(),
+    })?;
+    
+    if let Some(uuid) = cycle_at {
+        log::error!("Cycle detected in version chain for {} at {}", version_uuid, uuid);
+        return Err(format!("Version history contains a cycle at version {}", uuid));
+    }
+    
+    chain.reverse();
+    log::debug!("Version chain for {} has {} entries", version_uuid, chain.len());
+    
+    Ok(chain)
+}
+
 /// Rollback to a specific version by creating a new version with the old content
 #[tauri::command]
 pub async fn rollback_to_version(
@@ -510,7 +899,7 @@ pub async fn rollback_to_version(
     // First, get the version to rollback to including metadata
     let rollback_version = db.with_connection(|conn| {
         let mut stmt = conn.prepare(
-            "SELECT prompt_uuid, body, metadata FROM versions WHERE uuid = ?1"
+            "SELECT prompt_uuid, body, metadata FROM versions WHERE uuid = ?1 AND deleted_at IS NULL"
         )?;
         
         let mut rows = stmt.query_map([&version_uuid], |row| {
@@ -536,6 +925,8 @@ pub async fn rollback_to_version(
     let now = Utc::now().to_rfc3339();
     
     let new_version = db.with_transaction(|tx| {
+        ensure_unlocked(tx, &prompt_uuid)?;
+        
         // Get prompt details for file sync
         let (prompt_title, prompt_tags): (String, String) = {
             let mut stmt = tx.prepare("SELECT title, tags FROM prompts WHERE uuid = ?1")?;
@@ -545,52 +936,32 @@ pub async fn rollback_to_version(
             
             match rows.next() {
                 Some(row) => row?,
-                None => return Err(rusqlite::Error::InvalidColumnName(
+                None => return Err(AppError::NotFound(
                     format!("Prompt with UUID {} does not exist", prompt_uuid)
                 )),
             }
         };
         
-        // Get the latest version to determine next semver (for rollback)
-        let latest_version = {
-            let mut stmt = tx.prepare(
-                "SELECT semver, uuid FROM versions 
-                 WHERE prompt_uuid = ?1 
-                 ORDER BY created_at DESC, semver DESC
-                 LIMIT 1"
-            )?;
-            
-            let mut rows = stmt.query_map([&prompt_uuid], |row| {
-                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
-            })?;
-            
-            rows.next().transpose()?
-        };
-        
-        let (new_semver, parent_uuid) = match latest_version {
-            Some((latest_semver, latest_uuid)) => {
-                let new_semver = bump_patch_version(&latest_semver)
-                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-                (new_semver, Some(latest_uuid))
-            }
-            None => {
-                ("1.0.0".to_string(), None)
-            }
-        };
+        // The latest version is the rollback's parent
+        let parent_uuid: Option<String> = tx.query_row(
+            &format!("SELECT uuid FROM versions WHERE prompt_uuid = ?1 AND deleted_at IS NULL ORDER BY {} LIMIT 1", LATEST_VERSION_FIRST),
+            [&prompt_uuid],
+            |row| row.get(0)
+        ).optional()?;
+        let new_semver = next_patch_semver(tx, &prompt_uuid)?.unwrap_or_else(|| "1.0.0".to_string());
         
         // Insert new version (no content duplication check for rollback)
-        tx.execute(
-            "INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at, parent_uuid) 
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![
-                &new_version_uuid,
-                &prompt_uuid,
-                &new_semver,
-                &rollback_body,
-                &now,
-                &parent_uuid
-            ],
-        )?;
+        let mut version = Version {
+            uuid: new_version_uuid.clone(),
+            prompt_uuid: prompt_uuid.clone(),
+            semver: new_semver,
+            body: rollback_body.clone(),
+            metadata: None,
+            created_at: now.clone(),
+            parent_uuid,
+            author: current_author(tx)?,
+        };
+        insert_version_with_retry(tx, &mut version)?;
         
         // Update prompt's updated_at timestamp
         tx.execute(
@@ -598,22 +969,15 @@ pub async fn rollback_to_version(
             params![&now, &prompt_uuid],
         )?;
         
-        Ok((Version {
-            uuid: new_version_uuid.clone(),
-            prompt_uuid: prompt_uuid.clone(),
-            semver: new_semver.clone(),
-            body: rollback_body.clone(),
-            metadata: None,
-            created_at: now.clone(),
-            parent_uuid,
-        }, prompt_title, prompt_tags, new_semver))
+        let new_semver = version.semver.clone();
+        Ok((version, prompt_title, prompt_tags, new_semver))
     })?;
     
     // Sync to file system after successful database transaction
     let tags: Vec<String> = serde_json::from_str(&new_version.2)
         .unwrap_or_else(|_| Vec::new());
     
-    if let Err(e) = sync_version_to_file(&app_handle, &prompt_uuid, &new_version.1, &new_version.0.body, &new_version.3, &tags) {
+    if let Err(e) = sync_version_to_file(&app_handle, &prompt_uuid, &new_version.1, &new_version.0.body, &new_version.3, &tags, new_version.0.author.as_deref()) {
         log::warn!("Failed to sync rollback version to file: {}", e);
     }
     
@@ -623,4 +987,1281 @@ pub async fn rollback_to_version(
                version_uuid, final_version.semver);
     
     Ok(final_version)
-}
\ No newline at end of file
+}
+
+/// Result of promote_version
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PromotedVersion {
+    pub prompt_uuid: String,
+    pub version_uuid: String,
+    pub semver: String,
+    /// Absolute path of the prompt's `slug--prod.md` file, when one was written or updated
+    pub prod_file: Option<String>,
+}
+
+/// The version a prompt's prod pointer now refers to, with what its prod file needs
+struct ProdVersion {
+    prompt_uuid: String,
+    title: String,
+    tags: Vec<String>,
+    version: Version,
+}
+
+/// Point the prompt's prod_version_uuid at a (non-deleted) version
+fn set_prod_version(conn: &rusqlite::Connection, version_uuid: &str) -> Result<ProdVersion> {
+    let version = conn.query_row(
+        "SELECT uuid, prompt_uuid, semver, body, metadata, created_at, parent_uuid, author
+         FROM versions WHERE uuid = ?1 AND deleted_at IS NULL",
+        [version_uuid],
+        |row| Ok(Version {
+            uuid: row.get(0)?,
+            prompt_uuid: row.get(1)?,
+            semver: row.get(2)?,
+            body: row.get(3)?,
+            metadata: row.get(4)?,
+            created_at: row.get(5)?,
+            parent_uuid: row.get(6)?,
+            author: row.get(7)?,
+        })
+    ).optional()?.ok_or_else(|| AppError::NotFound(format!("Version {} not found", version_uuid)))?;
+    
+    ensure_unlocked(conn, &version.prompt_uuid)?;
+    
+    let (title, tags): (String, Option<String>) = conn.query_row(
+        "SELECT title, tags FROM prompts WHERE uuid = ?1",
+        [&version.prompt_uuid],
+        |row| Ok((row.get(0)?, row.get(1)?))
+    ).optional()?.ok_or_else(|| AppError::NotFound(format!("Prompt with UUID {} does not exist", version.prompt_uuid)))?;
+    
+    conn.execute(
+        "UPDATE prompts SET prod_version_uuid = ?1 WHERE uuid = ?2",
+        [&version.uuid, &version.prompt_uuid],
+    )?;
+    
+    Ok(ProdVersion {
+        prompt_uuid: version.prompt_uuid.clone(),
+        title,
+        tags: tags.and_then(|tags| serde_json::from_str(&tags).ok()).unwrap_or_default(),
+        version,
+    })
+}
+
+/// Write the prod version to the prompt's `slug--prod.md` file. Unless `create` is set, only
+/// an existing prod file is updated. Returns the file's path when it exists afterwards.
+fn write_prod_file(prompts_dir: &Path, prod: &ProdVersion, create: bool) -> Result<Option<PathBuf>> {
+    let path = resolve_prod_file_path(prompts_dir, &slugify(&prod.title), &prod.prompt_uuid);
+    if !create && !path.exists() {
+        return Ok(None);
+    }
+    
+    let custom_fields = prod.version.metadata.as_deref().and_then(custom_fields_of);
+    let content = create_markdown_content(
+        &prod.prompt_uuid, &prod.title, &prod.version.body, &prod.version.semver,
+        &prod.tags, prod.version.author.as_deref(), custom_fields,
+    );
+    fs::create_dir_all(prompts_dir)?;
+    write_if_changed(&path, &content)?;
+    Ok(Some(path))
+}
+
+/// Mark a version as the prompt's production version. With `write_prod_file`, the version is
+/// also written to a stable `slug--prod.md` file in the PromptMaster directory; once that file
+/// exists it is updated in place on every later promotion. Prod files are never ingested by the
+/// watcher or rescans, so editing one doesn't create versions.
+#[tauri::command]
+pub async fn promote_version(
+    version_uuid: String,
+    write_prod_file: bool,
+    app_handle: tauri::AppHandle,
+) -> std::result::Result<PromotedVersion, String> {
+    log::info!("Promoting version {} to prod", version_uuid);
+    
+    validate_uuid(&version_uuid)?;
+    
+    let db = get_database()?;
+    let prod = db.with_transaction(|tx| set_prod_version(tx, &version_uuid))?;
+    
+    let prompts_dir = prompts_dir(&app_handle)?;
+    let prod_file = self::write_prod_file(&prompts_dir, &prod, write_prod_file)?;
+    if let Some(ref path) = prod_file {
+        log::info!("Prod file for prompt {} now at version {}: {}", prod.prompt_uuid, prod.version.semver, path.display());
+    }
+    
+    Ok(PromotedVersion {
+        prompt_uuid: prod.prompt_uuid,
+        version_uuid: prod.version.uuid,
+        semver: prod.version.semver,
+        prod_file: prod_file.map(|path| path.to_string_lossy().into_owned()),
+    })
+}
+
+/// Pick the versions to delete when pruning: everything except the `keep` highest semvers
+/// and the protected UUIDs (prod version, versions referenced by runs).
+/// `versions` holds (uuid, semver, created_at); returns (uuid, semver) pairs.
+fn select_versions_to_prune(
+    mut versions: Vec<(String, String, String)>,
+    keep: usize,
+    protected: &HashSet<String>,
+) -> Vec<(String, String)> {
+    sort_by_semver_desc(&mut versions, |(_, semver, created_at)| (semver.as_str(), created_at.as_str()));
+    
+    versions
+        .into_iter()
+        .skip(keep)
+        .filter(|(uuid, _, _)| !protected.contains(uuid))
+        .map(|(uuid, semver, _)| (uuid, semver))
+        .collect()
+}
+
+/// Remove the markdown files of a prompt's pruned versions. Files are matched by the
+/// version in their filename and the prompt UUID in their frontmatter.
+fn remove_version_files(prompts_dir: &Path, prompt_uuid: &str, semvers: &HashSet<String>) -> usize {
+    let entries = match fs::read_dir(prompts_dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::warn!("Failed to read prompts directory {}: {}", prompts_dir.display(), e);
+            return 0;
+        }
+    };
+    
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let filename = entry.file_name().to_string_lossy().into_owned();
+        
+        let version_matches = parse_filename(&filename)
+            .is_some_and(|(_, _, version, _)| semvers.contains(&version));
+        if !version_matches {
+            continue;
+        }
+        
+        let owned = fs::read_to_string(&path).ok()
+            .and_then(|content| parse_frontmatter(&content).ok())
+            .is_some_and(|parsed| parsed.uuid == prompt_uuid);
+        if !owned {
+            continue;
+        }
+        
+        match fs::remove_file(&path) {
+            Ok(()) => removed += 1,
+            Err(e) => log::warn!("Failed to remove pruned version file {}: {}", filename, e),
+        }
+    }
+    
+    removed
+}
+
+/// Move all but the `keep` most recent versions (by semver) of a prompt to the trash and remove
+/// their markdown files. The prod version and versions referenced by runs are never pruned.
+/// Trashed versions can be brought back with restore_version until purge_trashed removes them.
+/// Returns the number of versions pruned.
+#[tauri::command]
+pub async fn prune_versions(
+    prompt_uuid: String,
+    keep: usize,
+    app_handle: tauri::AppHandle,
+) -> std::result::Result<usize, String> {
+    log::info!("Pruning versions for prompt {} (keeping {})", prompt_uuid, keep);
+    
+    validate_uuid(&prompt_uuid)?;
+    
+    if keep == 0 {
+        return Err("keep must be at least 1".to_string());
+    }
+    
+    let db = get_database()?;
+    
+    let pruned = db.with_transaction(|tx| {
+        ensure_unlocked(tx, &prompt_uuid)?;
+        
+        let prod_version_uuid: Option<String> = tx.query_row(
+            "SELECT prod_version_uuid FROM prompts WHERE uuid = ?1",
+            [&prompt_uuid],
+            |row| row.get(0)
+        ).optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Prompt {}", prompt_uuid)))?;
+        
+        let mut protected: HashSet<String> = prod_version_uuid.into_iter().collect();
+        {
+            let mut stmt = tx.prepare(
+                "SELECT DISTINCT r.version_uuid FROM runs r
+                 JOIN versions v ON v.uuid = r.version_uuid
+                 WHERE v.prompt_uuid = ?1"
+            )?;
+            for uuid in stmt.query_map([&prompt_uuid], |row| row.get::<_, String>(0))? {
+                protected.insert(uuid?);
+            }
+        }
+        
+        let versions = {
+            let mut stmt = tx.prepare(
+                "SELECT uuid, semver, created_at FROM versions WHERE prompt_uuid = ?1 AND deleted_at IS NULL"
+            )?;
+            let rows = stmt.query_map([&prompt_uuid], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        
+        let to_prune = select_versions_to_prune(versions, keep, &protected);
+        
+        // Pruned versions go to the trash; purge_trashed removes them for good
+        let mut stmt = tx.prepare("UPDATE versions SET deleted_at = ?1 WHERE uuid = ?2")?;
+        let now = Utc::now().to_rfc3339();
+        for (uuid, _) in &to_prune {
+            stmt.execute([&now, uuid])?;
+        }
+        
+        Ok(to_prune)
+    })?;
+    
+    // Remove markdown files after the database transaction has committed
+    if !pruned.is_empty() {
+        let semvers: HashSet<String> = pruned.iter().map(|(_, semver)| semver.clone()).collect();
+        match prompts_dir(&app_handle) {
+            Ok(dir) => {
+                let removed = remove_version_files(&dir, &prompt_uuid, &semvers);
+                log::debug!("Removed {} markdown files for pruned versions", removed);
+            }
+            Err(e) => log::warn!("Failed to resolve documents directory, pruned version files were kept: {}", e),
+        }
+    }
+    
+    log::info!("Pruned {} versions for prompt {}", pruned.len(), prompt_uuid);
+    
+    Ok(pruned.len())
+}
+
+/// Move the most recent version (by created_at) of a prompt to the trash, refusing when it is the
+/// only version, the prod version, or has runs. Returns the trashed semver and the new latest version.
+fn delete_latest_version(conn: &rusqlite::Connection, prompt_uuid: &str) -> Result<(String, VersionInfo)> {
+    ensure_unlocked(conn, prompt_uuid)?;
+    
+    let prod_version_uuid: Option<String> = conn.query_row(
+        "SELECT prod_version_uuid FROM prompts WHERE uuid = ?1",
+        [prompt_uuid],
+        |row| row.get(0)
+    ).optional()?
+        .ok_or_else(|| AppError::NotFound(format!("Prompt {}", prompt_uuid)))?;
+    
+    let mut latest_two = {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT uuid, semver, created_at, parent_uuid, author FROM versions
+             WHERE prompt_uuid = ?1 AND deleted_at IS NULL
+             ORDER BY {}
+             LIMIT 2",
+            LATEST_VERSION_FIRST
+        ))?;
+        let rows = stmt.query_map([prompt_uuid], |row| Ok(VersionInfo {
+            uuid: row.get(0)?,
+            semver: row.get(1)?,
+            created_at: row.get(2)?,
+            parent_uuid: row.get(3)?,
+            author: row.get(4)?,
+        }))?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()?.into_iter()
+    };
+    
+    let (latest, restored) = match (latest_two.next(), latest_two.next()) {
+        (Some(latest), Some(restored)) => (latest, restored),
+        _ => return Err(AppError::Validation(
+            "Cannot undo the only version of a prompt".to_string()
+        )),
+    };
+    
+    if prod_version_uuid.as_deref() == Some(latest.uuid.as_str()) {
+        return Err(AppError::Validation(format!(
+            "Cannot undo version {}: it is the prod version", latest.semver
+        )));
+    }
+    
+    let run_count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM runs WHERE version_uuid = ?1",
+        [&latest.uuid],
+        |row| row.get(0)
+    )?;
+    if run_count > 0 {
+        return Err(AppError::Validation(format!(
+            "Cannot undo version {}: it has {} recorded runs", latest.semver, run_count
+        )));
+    }
+    
+    let now = Utc::now().to_rfc3339();
+    conn.execute("UPDATE versions SET deleted_at = ?1 WHERE uuid = ?2", params![&now, &latest.uuid])?;
+    conn.execute(
+        "UPDATE prompts SET updated_at = ?1 WHERE uuid = ?2",
+        params![&now, prompt_uuid],
+    )?;
+    
+    Ok((latest.semver, restored))
+}
+
+/// Undo the most recent version save of a prompt by moving that version to the trash.
+/// Unlike rollback_to_version this creates nothing; it refuses when the version is the only
+/// one, is the prod version or has runs. The markdown file is re-synced to the now-latest
+/// version, which is returned.
+#[tauri::command]
+pub async fn undo_last_version(
+    prompt_uuid: String,
+    app_handle: tauri::AppHandle,
+) -> std::result::Result<VersionInfo, String> {
+    log::info!("Undoing last version of prompt {}", prompt_uuid);
+    
+    validate_uuid(&prompt_uuid)?;
+    
+    let db = get_database()?;
+    let (removed_semver, restored, title, tags_json, body) = db.with_transaction(|tx| {
+        let (removed_semver, restored) = delete_latest_version(tx, &prompt_uuid)?;
+        let (title, tags_json): (String, Option<String>) = tx.query_row(
+            "SELECT title, tags FROM prompts WHERE uuid = ?1",
+            [&prompt_uuid],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        )?;
+        let body: String = tx.query_row(
+            "SELECT body FROM versions WHERE uuid = ?1",
+            [&restored.uuid],
+            |row| row.get(0)
+        )?;
+        Ok((removed_semver, restored, title, tags_json, body))
+    })?;
+    
+    // Files are only touched once the deletion has committed
+    match prompts_dir(&app_handle) {
+        Ok(dir) => {
+            let semvers = HashSet::from([removed_semver.clone()]);
+            remove_version_files(&dir, &prompt_uuid, &semvers);
+        }
+        Err(e) => log::warn!("Failed to resolve documents directory, undone version file was kept: {}", e),
+    }
+    
+    let tags: Vec<String> = tags_json
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    if let Err(e) = sync_version_to_file(&app_handle, &prompt_uuid, &title, &body, &restored.semver, &tags, restored.author.as_deref()) {
+        log::warn!("Failed to sync restored version to file: {}", e);
+    }
+    
+    log::info!("Undid version {} of prompt {}, latest is now {}", removed_semver, prompt_uuid, restored.semver);
+    Ok(restored)
+}
+
+/// A version with the prompt fields needed to render it as a markdown file
+struct VersionExport {
+    prompt_uuid: String,
+    title: String,
+    tags: Vec<String>,
+    semver: String,
+    body: String,
+    author: Option<String>,
+    custom_fields: Option<serde_json::Value>,
+}
+
+fn load_version_export(conn: &rusqlite::Connection, version_uuid: &str) -> rusqlite::Result<Option<VersionExport>> {
+    conn.query_row(
+        "SELECT v.prompt_uuid, p.title, p.tags, v.semver, v.body, v.author, v.metadata
+         FROM versions v JOIN prompts p ON p.uuid = v.prompt_uuid
+         WHERE v.uuid = ?1 AND v.deleted_at IS NULL",
+        [version_uuid],
+        |row| {
+            let tags_json: Option<String> = row.get(2)?;
+            Ok(VersionExport {
+                prompt_uuid: row.get(0)?,
+                title: row.get(1)?,
+                tags: tags_json.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default(),
+                semver: row.get(3)?,
+                body: row.get(4)?,
+                author: row.get(5)?,
+                custom_fields: row.get::<_, Option<String>>(6)?.as_deref().and_then(custom_fields_of),
+            })
+        }
+    ).optional()
+}
+
+/// A version's body exactly as stored, without frontmatter, for pasting into other tools.
+/// Bodies containing `---` lines are returned intact.
+#[tauri::command]
+pub async fn get_version_plain(version_uuid: String) -> std::result::Result<String, String> {
+    validate_uuid(&version_uuid)?;
+    
+    let db = get_database()?;
+    let export = db.with_connection(|conn| load_version_export(conn, &version_uuid))?
+        .ok_or_else(|| AppError::NotFound(format!("Version {}", version_uuid)))?;
+    
+    Ok(export.body)
+}
+
+/// Render a version for export: the plain body, or with `include_frontmatter` the same
+/// frontmatter markdown that is written to the prompts directory
+#[tauri::command]
+pub async fn export_version(version_uuid: String, include_frontmatter: bool) -> std::result::Result<String, String> {
+    log::info!("Exporting version {} (frontmatter: {})", version_uuid, include_frontmatter);
+    
+    validate_uuid(&version_uuid)?;
+    
+    let db = get_database()?;
+    let export = db.with_connection(|conn| load_version_export(conn, &version_uuid))?
+        .ok_or_else(|| AppError::NotFound(format!("Version {}", version_uuid)))?;
+    
+    if !include_frontmatter {
+        return Ok(export.body);
+    }
+    
+    Ok(create_markdown_content(
+        &export.prompt_uuid, &export.title, &export.body, &export.semver, &export.tags,
+        export.author.as_deref(), export.custom_fields,
+    ))
+}
+
+/// Render a version's body as sanitized HTML, without frontmatter, for the preview pane or
+/// saving a shareable copy. Raw HTML and unsafe link targets in the body are neutralized.
+#[tauri::command]
+pub async fn render_version_html(version_uuid: String) -> std::result::Result<String, String> {
+    log::info!("Rendering version {} as HTML", version_uuid);
+    
+    validate_uuid(&version_uuid)?;
+    
+    let db = get_database()?;
+    let export = db.with_connection(|conn| load_version_export(conn, &version_uuid))?
+        .ok_or_else(|| AppError::NotFound(format!("Version {}", version_uuid)))?;
+    
+    Ok(render_markdown_html(&export.body))
+}
+
+/// A version in the library-wide activity feed of get_recent_versions
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct RecentVersion {
+    pub uuid: String,
+    pub prompt_uuid: String,
+    pub prompt_title: String,
+    pub semver: String,
+    pub created_at: String,
+}
+
+/// Newest non-deleted versions across all prompts, optionally only those created at or after
+/// `since` (UTC RFC3339). Version timestamps are always written as UTC RFC3339, so they sort
+/// as plain strings and the scan can walk idx_versions_created. Versions with the same
+/// timestamp are ordered by their time-ordered UUIDv7.
+fn load_recent_versions(conn: &rusqlite::Connection, limit: usize, since: Option<&str>) -> rusqlite::Result<Vec<RecentVersion>> {
+    let mut stmt = conn.prepare(
+        "SELECT v.uuid, v.prompt_uuid, p.title, v.semver, v.created_at
+         FROM versions v
+         JOIN prompts p ON p.uuid = v.prompt_uuid
+         WHERE v.deleted_at IS NULL AND (?2 IS NULL OR v.created_at >= ?2)
+         ORDER BY v.created_at DESC, v.uuid DESC
+         LIMIT ?1"
+    )?;
+    let rows = stmt.query_map(params![limit as i64, since], |row| Ok(RecentVersion {
+        uuid: row.get(0)?,
+        prompt_uuid: row.get(1)?,
+        prompt_title: row.get(2)?,
+        semver: row.get(3)?,
+        created_at: row.get(4)?,
+    }))?;
+    rows.collect()
+}
+
+/// Most recently created versions across the whole library, newest first, for an activity
+/// feed. `since` is an inclusive RFC3339 lower bound; `limit` is capped by the
+/// `max_result_limit` setting.
+#[tauri::command]
+pub async fn get_recent_versions(limit: usize, since: Option<String>) -> std::result::Result<Vec<RecentVersion>, String> {
+    let since = parse_rfc3339_filter("since", since.as_deref())?.map(|since| since.to_rfc3339());
+    
+    let db = get_database()?;
+    let limit = limit.clamp(1, db.with_connection(max_result_limit)?);
+    log::info!("Getting {} recent versions since {:?}", limit, since);
+    
+    let versions = db.with_connection(|conn| load_recent_versions(conn, limit, since.as_deref()))?;
+    
+    Ok(versions)
+}
+
+/// Most recent versions annotated by get_version_diff_stats
+const MAX_DIFF_STATS_VERSIONS: usize = 200;
+
+/// How much a version changed relative to its parent
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct VersionDiffStats {
+    pub uuid: String,
+    pub semver: String,
+    pub parent_uuid: Option<String>,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+/// Lines added and removed going from `old` to `new`; None for `old` counts every line as added
+fn line_diff_counts(old: Option<&str>, new: &str) -> (usize, usize) {
+    let diff = similar::TextDiff::from_lines(old.unwrap_or(""), new);
+    diff.iter_all_changes().fold((0, 0), |(added, removed), change| match change.tag() {
+        similar::ChangeTag::Insert => (added + 1, removed),
+        similar::ChangeTag::Delete => (added, removed + 1),
+        similar::ChangeTag::Equal => (added, removed),
+    })
+}
+
+/// Diff stats for the prompt's `limit` most recent non-deleted versions, newest first.
+/// Parents are looked up by uuid, so a trashed parent is still diffed against.
+fn load_version_diff_stats(conn: &rusqlite::Connection, prompt_uuid: &str, limit: usize) -> rusqlite::Result<Vec<VersionDiffStats>> {
+    let versions: Vec<(String, String, Option<String>, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT uuid, semver, parent_uuid, body FROM versions
+             WHERE prompt_uuid = ?1 AND deleted_at IS NULL
+             ORDER BY created_at DESC LIMIT ?2"
+        )?;
+        let rows = stmt.query_map(params![prompt_uuid, limit as i64], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+
+    let mut parent_body = conn.prepare("SELECT body FROM versions WHERE uuid = ?1")?;
+    let mut stats = Vec::with_capacity(versions.len());
+    for (uuid, semver, parent_uuid, body) in versions {
+        let parent: Option<String> = match parent_uuid {
+            Some(ref parent_uuid) => parent_body.query_row([parent_uuid], |row| row.get(0)).optional()?,
+            None => None,
+        };
+        let (lines_added, lines_removed) = line_diff_counts(parent.as_deref(), &body);
+        stats.push(VersionDiffStats { uuid, semver, parent_uuid, lines_added, lines_removed });
+    }
+    Ok(stats)
+}
+
+/// Lines added/removed by each version relative to its parent, newest first, for "+12 -3"
+/// badges in the history list. Root versions count all their lines as added. Covers the 200
+/// most recent versions and is computed on every call.
+#[tauri::command]
+pub async fn get_version_diff_stats(prompt_uuid: String) -> std::result::Result<Vec<VersionDiffStats>, String> {
+    log::info!("Getting version diff stats for prompt: {}", prompt_uuid);
+    
+    validate_uuid(&prompt_uuid)?;
+    
+    let db = get_database()?;
+    let stats = db.with_connection(|conn| load_version_diff_stats(conn, &prompt_uuid, MAX_DIFF_STATS_VERSIONS))?;
+    
+    Ok(stats)
+}
+
+/// Share of a context window above which get_version_stats warns
+const CONTEXT_WARN_FRACTION: f64 = 0.8;
+
+/// How a version's estimated size compares to a model's context window
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContextBudget {
+    /// Below CONTEXT_WARN_FRACTION of the window
+    Fits,
+    /// At or above CONTEXT_WARN_FRACTION, but within the window
+    Warn,
+    /// Larger than the window
+    Over,
+}
+
+/// Size statistics of a version body
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct VersionStats {
+    pub characters: usize,
+    pub words: usize,
+    pub lines: usize,
+    /// Rough token estimate (about 4 characters per token); not tokenizer-exact
+    pub estimated_tokens: usize,
+    pub context_window: Option<usize>,
+    /// estimated_tokens as a fraction of context_window
+    pub context_usage: Option<f64>,
+    pub budget: Option<ContextBudget>,
+}
+
+/// Count a body's size and, given a context window, how much of it the body uses
+fn version_stats(body: &str, context_window: Option<usize>) -> VersionStats {
+    let characters = body.chars().count();
+    let estimated_tokens = characters.div_ceil(4);
+    let context_usage = context_window.map(|window| estimated_tokens as f64 / window as f64);
+    let budget = context_usage.map(|usage| {
+        if usage > 1.0 {
+            ContextBudget::Over
+        } else if usage >= CONTEXT_WARN_FRACTION {
+            ContextBudget::Warn
+        } else {
+            ContextBudget::Fits
+        }
+    });
+
+    VersionStats {
+        characters,
+        words: body.split_whitespace().count(),
+        lines: body.lines().count(),
+        estimated_tokens,
+        context_window,
+        context_usage,
+        budget,
+    }
+}
+
+/// Size statistics for a version with an estimated token count. With `context_window` (the
+/// target model's window in tokens), also reports the share used and a fits/warn/over status;
+/// "warn" starts at 80% of the window.
+#[tauri::command]
+pub async fn get_version_stats(
+    version_uuid: String,
+    context_window: Option<usize>,
+) -> std::result::Result<VersionStats, String> {
+    log::info!("Getting stats for version {}", version_uuid);
+    
+    validate_uuid(&version_uuid)?;
+    if context_window == Some(0) {
+        return Err(AppError::Validation("context_window must be positive".to_string()).into());
+    }
+    
+    let db = get_database()?;
+    let export = db.with_connection(|conn| load_version_export(conn, &version_uuid))?
+        .ok_or_else(|| AppError::NotFound(format!("Version {}", version_uuid)))?;
+    
+    Ok(version_stats(&export.body, context_window))
+}
+
+/// Take a version out of the trash and re-sync its markdown file
+#[tauri::command]
+pub async fn restore_version(
+    version_uuid: String,
+    app_handle: tauri::AppHandle,
+) -> std::result::Result<VersionInfo, String> {
+    log::info!("Restoring version {}", version_uuid);
+    
+    validate_uuid(&version_uuid)?;
+    
+    let db = get_database()?;
+    let (restored, prompt_uuid, body, title, tags_json) = db.with_transaction(|tx| {
+        let (prompt_uuid, body, deleted_at, restored): (String, String, Option<String>, VersionInfo) = tx.query_row(
+            "SELECT prompt_uuid, body, deleted_at, uuid, semver, created_at, parent_uuid, author FROM versions WHERE uuid = ?1",
+            [&version_uuid],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, VersionInfo {
+                uuid: row.get(3)?,
+                semver: row.get(4)?,
+                created_at: row.get(5)?,
+                parent_uuid: row.get(6)?,
+                author: row.get(7)?,
+            }))
+        ).optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Version {}", version_uuid)))?;
+        
+        if deleted_at.is_none() {
+            return Err(AppError::Validation(format!("Version {} is not in the trash", restored.semver)));
+        }
+        ensure_unlocked(tx, &prompt_uuid)?;
+        
+        tx.execute("UPDATE versions SET deleted_at = NULL WHERE uuid = ?1", [&version_uuid])?;
+        let (title, tags_json): (String, Option<String>) = tx.query_row(
+            "SELECT title, tags FROM prompts WHERE uuid = ?1",
+            [&prompt_uuid],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        )?;
+        
+        Ok((restored, prompt_uuid, body, title, tags_json))
+    })?;
+    
+    let tags: Vec<String> = tags_json
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    if let Err(e) = sync_version_to_file(&app_handle, &prompt_uuid, &title, &body, &restored.semver, &tags, restored.author.as_deref()) {
+        log::warn!("Failed to sync restored version to file: {}", e);
+    }
+    
+    log::info!("Restored version {} of prompt {}", restored.semver, prompt_uuid);
+    Ok(restored)
+}
+
+/// Permanently delete trashed versions (and their version tags) trashed at or before `cutoff`
+/// (RFC3339). Versions referenced by runs are kept.
+fn purge_trashed_before(conn: &rusqlite::Connection, cutoff: &str) -> rusqlite::Result<usize> {
+    let condition = "deleted_at IS NOT NULL AND deleted_at <= ?1
+                     AND uuid NOT IN (SELECT version_uuid FROM runs)";
+    conn.execute(
+        &format!("DELETE FROM version_tags WHERE version_uuid IN (SELECT uuid FROM versions WHERE {})", condition),
+        [cutoff],
+    )?;
+    conn.execute(&format!("DELETE FROM versions WHERE {}", condition), [cutoff])
+}
+
+/// Permanently delete versions that have been in the trash for at least `older_than_days` days.
+/// Returns the number of versions purged.
+#[tauri::command]
+pub async fn purge_trashed(older_than_days: u32) -> std::result::Result<usize, String> {
+    log::info!("Purging versions trashed more than {} days ago", older_than_days);
+    
+    let cutoff = (Utc::now() - chrono::Duration::days(i64::from(older_than_days))).to_rfc3339();
+    
+    let db = get_database()?;
+    let purged = db.with_transaction(|tx| Ok(purge_trashed_before(tx, &cutoff)?))?;
+    
+    log::info!("Purged {} trashed versions", purged);
+    Ok(purged)
+}
+
+/// Version-level tags, alphabetical
+fn load_version_tags(conn: &rusqlite::Connection, version_uuid: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT tag FROM version_tags WHERE version_uuid = ?1 ORDER BY tag COLLATE NOCASE"
+    )?;
+    let tags = stmt.query_map([version_uuid], |row| row.get(0))?;
+    tags.collect()
+}
+
+/// Tag a single version (e.g. "benchmarked", "shipped"). Version tags use the same rules as
+/// prompt tags but are stored separately and don't appear in the prompt's tags or search index.
+/// Adding a tag the version already has (case-insensitive) is a no-op. Returns the version's tags.
+#[tauri::command]
+pub async fn add_version_tag(version_uuid: String, tag: String) -> std::result::Result<Vec<String>, String> {
+    log::info!("Adding tag to version {}", version_uuid);
+
+    validate_uuid(&version_uuid)?;
+    let tag = tag.trim().to_string();
+    validate_tag(&tag)?;
+
+    let db = get_database()?;
+    let tags = db.with_transaction(|tx| {
+        tx.query_row("SELECT 1 FROM versions WHERE uuid = ?1", [&version_uuid], |_| Ok(()))
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("Version {}", version_uuid)))?;
+
+        tx.execute(
+            "INSERT OR IGNORE INTO version_tags (version_uuid, tag, created_at) VALUES (?1, ?2, ?3)",
+            params![&version_uuid, &tag, Utc::now().to_rfc3339()],
+        )?;
+
+        Ok(load_version_tags(tx, &version_uuid)?)
+    })?;
+
+    Ok(tags)
+}
+
+/// Remove a tag (case-insensitive) from a version. Returns true if the version had it.
+#[tauri::command]
+pub async fn remove_version_tag(version_uuid: String, tag: String) -> std::result::Result<bool, String> {
+    log::info!("Removing tag from version {}", version_uuid);
+
+    validate_uuid(&version_uuid)?;
+
+    let db = get_database()?;
+    let removed = db.with_connection(|conn| {
+        conn.execute(
+            "DELETE FROM version_tags WHERE version_uuid = ?1 AND tag = ?2",
+            params![&version_uuid, tag.trim()],
+        )
+    })?;
+
+    Ok(removed > 0)
+}
+
+/// List a version's tags, alphabetical
+#[tauri::command]
+pub async fn list_version_tags(version_uuid: String) -> std::result::Result<Vec<String>, String> {
+    validate_uuid(&version_uuid)?;
+
+    let db = get_database()?;
+    let tags = db.with_connection(|conn| load_version_tags(conn, &version_uuid))?;
+
+    Ok(tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{create_test_schema, test_connection};
+    use rusqlite::Connection;
+
+    fn insert_prompts(conn: &Connection, uuids: &[&str]) {
+        for uuid in uuids {
+            conn.execute(
+                "INSERT INTO prompts (uuid, title, created_at, updated_at) VALUES (?1, ?1, '2024-01-01', '2024-01-01')",
+                [uuid],
+            ).unwrap();
+        }
+    }
+
+    fn open_test_db(path: &Path) -> Connection {
+        let conn = Connection::open(path).unwrap();
+        conn.busy_timeout(std::time::Duration::from_secs(5)).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_detect_version_conflict_whitespace() {
+        let conn = test_connection();
+        insert_prompts(&conn, &["p1", "p2"]);
+        conn.execute_batch(
+            "INSERT INTO versions VALUES ('v1', 'p1', '1.0.0', 'Line one  \nLine two\n\n', NULL, '2024-01-01', NULL, NULL, 'ada');
+             INSERT INTO versions VALUES ('v2', 'p1', '1.0.1', 'Something else', NULL, '2024-01-02', 'v1', NULL, NULL);
+             INSERT INTO versions VALUES ('v3', 'p1', '1.0.2', 'Deleted', NULL, '2024-01-03', 'v2', '2024-01-04', NULL);"
+        ).unwrap();
+
+        // Exact comparison by default
+        assert!(detect_version_conflict(&conn, "p1", "Line one\nLine two", false).unwrap().is_none());
+        let existing = detect_version_conflict(&conn, "p1", "Line one  \nLine two\n\n", false).unwrap().unwrap();
+        assert_eq!((existing.uuid.as_str(), existing.semver.as_str()), ("v1", "1.0.0"));
+        assert_eq!(existing.created_at, "2024-01-01");
+        assert_eq!(existing.author.as_deref(), Some("ada"));
+
+        // Trailing spaces, CRLF line endings and final newlines are ignored when normalizing
+        for body in ["Line one\nLine two", "Line one\t\nLine two   \n", "Line one\r\nLine two\r\n"] {
+            let existing = detect_version_conflict(&conn, "p1", body, true).unwrap();
+            assert_eq!(existing.map(|v| v.uuid).as_deref(), Some("v1"), "{:?}", body);
+        }
+        // Leading whitespace and blank lines inside the body still count
+        assert!(detect_version_conflict(&conn, "p1", "  Line one\nLine two", true).unwrap().is_none());
+        assert!(detect_version_conflict(&conn, "p1", "Line one\n\nLine two", true).unwrap().is_none());
+        // Deleted versions and other prompts are not conflicts
+        assert!(detect_version_conflict(&conn, "p1", "Deleted", true).unwrap().is_none());
+        assert!(detect_version_conflict(&conn, "p2", "Something else", false).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_load_recent_versions() {
+        let conn = test_connection();
+        // 01-f belongs to a prompt that is gone, which only predates enforced foreign keys
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, created_at, updated_at) VALUES
+                ('p1', 'Summarize', '2024-01-01', '2024-01-01'), ('p2', 'Translate', '2024-01-01', '2024-01-01');
+             PRAGMA foreign_keys = OFF;
+             INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at, deleted_at) VALUES
+                ('01-a', 'p1', '1.0.0', '', '2024-01-01T10:00:00.250+00:00', NULL),
+                ('01-b', 'p2', '1.0.0', '', '2024-01-02T09:00:00+00:00', NULL),
+                ('01-c', 'p1', '1.0.1', '', '2024-01-03T08:00:00+00:00', '2024-01-04T00:00:00+00:00'),
+                ('01-d', 'p1', '1.0.2', '', '2024-01-03T08:00:00+00:00', NULL),
+                ('01-e', 'p2', '1.0.1', '', '2024-01-03T08:00:00+00:00', NULL),
+                ('01-f', 'gone', '1.0.0', '', '2024-01-05T00:00:00+00:00', NULL);
+             PRAGMA foreign_keys = ON;"
+        ).unwrap();
+
+        let uuids = |limit, since: Option<&str>| -> Vec<String> {
+            let since = parse_rfc3339_filter("since", since).unwrap().map(|since| since.to_rfc3339());
+            load_recent_versions(&conn, limit, since.as_deref()).unwrap().into_iter().map(|v| v.uuid).collect()
+        };
+        // 01-d and 01-e have the same timestamp; the newer UUID comes first
+        assert_eq!(uuids(10, None), vec!["01-e", "01-d", "01-b", "01-a"]);
+        assert_eq!(uuids(2, None), vec!["01-e", "01-d"]);
+        assert_eq!(uuids(10, Some("2024-01-02T09:00:00Z")), vec!["01-e", "01-d", "01-b"]);
+        // Bounds in other offsets are compared in UTC
+        assert_eq!(uuids(10, Some("2024-01-03T10:00:00+02:00")), vec!["01-e", "01-d"]);
+        assert_eq!(uuids(10, Some("2024-01-01T10:00:00.5+00:00")), vec!["01-e", "01-d", "01-b"]);
+        assert_eq!(load_recent_versions(&conn, 1, None).unwrap()[0].prompt_title, "Translate");
+        assert!(parse_rfc3339_filter("since", Some("yesterday")).is_err());
+    }
+
+    #[test]
+    fn test_load_version_diff_stats() {
+        let conn = test_connection();
+        insert_prompts(&conn, &["p1"]);
+        conn.execute_batch(
+            "INSERT INTO versions (uuid, prompt_uuid, semver, parent_uuid, body, created_at, deleted_at) VALUES
+                ('v1', 'p1', '1.0.0', NULL, 'one\ntwo\nthree', '2024-01-01', NULL),
+                ('v2', 'p1', '1.0.1', 'v1', 'one\n2\nthree\nfour', '2024-01-02', '2024-01-03'),
+                ('v3', 'p1', '1.0.2', 'v2', 'one\nfour', '2024-01-04', NULL);"
+        ).unwrap();
+
+        let stats = load_version_diff_stats(&conn, "p1", 10).unwrap();
+        let summary: Vec<(&str, usize, usize)> = stats.iter()
+            .map(|s| (s.uuid.as_str(), s.lines_added, s.lines_removed))
+            .collect();
+        // v2 is trashed but still serves as v3's parent
+        assert_eq!(summary, vec![("v3", 0, 2), ("v1", 3, 0)]);
+
+        assert_eq!(load_version_diff_stats(&conn, "p1", 1).unwrap().len(), 1);
+        assert_eq!(line_diff_counts(Some("a\nb\n"), "a\nc\n"), (1, 1));
+    }
+
+    #[test]
+    fn test_version_stats_budget() {
+        let body = "word ".repeat(200);
+        let stats = version_stats(&body, None);
+        assert_eq!((stats.characters, stats.words, stats.lines, stats.estimated_tokens), (1000, 200, 1, 250));
+        assert_eq!(stats.budget, None);
+
+        assert_eq!(version_stats(&body, Some(1000)).budget, Some(ContextBudget::Fits));
+        let warn = version_stats(&body, Some(300));
+        assert_eq!(warn.budget, Some(ContextBudget::Warn));
+        assert!((warn.context_usage.unwrap() - 250.0 / 300.0).abs() < 1e-9);
+        assert_eq!(version_stats(&body, Some(250)).budget, Some(ContextBudget::Warn));
+        assert_eq!(version_stats(&body, Some(249)).budget, Some(ContextBudget::Over));
+    }
+
+    #[test]
+    fn test_soft_wrap_paragraphs_joins_wrapped_lines() {
+        let body = "You are a careful\nreviewer.   Read the\n  contract below.\n\nSecond   paragraph\nwraps too.\n";
+        assert_eq!(
+            soft_wrap_paragraphs(body),
+            "You are a careful reviewer. Read the contract below.\n\nSecond paragraph wraps too.\n"
+        );
+    }
+
+    #[test]
+    fn test_soft_wrap_paragraphs_preserves_structure() {
+        let body = "# Task\nSummarize:\n- first item\n  continues here\n- second item\n1. one\n2) two\n\n\
+                    > quoted\n> lines\n| a | b |\n| - | - |\n<context>\n{{input}}\n</context>\n\
+                    Line with hard break  \nnext\n\n```\ncode   stays\nas is\n```\n    indented code";
+        assert_eq!(
+            soft_wrap_paragraphs(body),
+            "# Task\nSummarize:\n- first item continues here\n- second item\n1. one\n2) two\n\n\
+             > quoted\n> lines\n| a | b |\n| - | - |\n<context>\n{{input}}\n</context>\n\
+             Line with hard break\nnext\n\n```\ncode   stays\nas is\n```\n    indented code"
+        );
+    }
+
+    #[test]
+    fn test_replace_in_body() {
+        let body = "Use GPT-4 for this. GPT-4 is fast.";
+        assert_eq!(replace_in_body(body, "GPT-4", "gpt-4o", false).unwrap(),
+                   ("Use gpt-4o for this. gpt-4o is fast.".to_string(), 2));
+        // Literal mode treats regex syntax as text
+        assert_eq!(replace_in_body(body, "GPT-.", "x", false).unwrap().1, 0);
+
+        assert_eq!(replace_in_body(body, r"GPT-(\d)", "gpt-${1}o", true).unwrap(),
+                   ("Use gpt-4o for this. gpt-4o is fast.".to_string(), 2));
+        assert!(replace_in_body(body, "(", "x", true).is_err());
+        assert!(replace_in_body(body, "", "x", false).is_err());
+    }
+
+    #[test]
+    fn test_load_latest_bodies() {
+        let conn = test_connection();
+        insert_prompts(&conn, &["a", "b", "c"]);
+        conn.execute_batch(
+            "INSERT INTO versions VALUES ('a1', 'a', '1.0.0', 'a one', NULL, '2025-01-01', NULL, NULL, NULL);
+             INSERT INTO versions VALUES ('a2', 'a', '1.0.1', 'a two', NULL, '2025-01-02', 'a1', NULL, NULL);
+             INSERT INTO versions VALUES ('a3', 'a', '1.0.2', 'a trashed', NULL, '2025-01-03', 'a2', '2025-01-04', NULL);
+             INSERT INTO versions VALUES ('b1', 'b', '1.0.0', 'b one', NULL, '2025-01-01', NULL, NULL, NULL);
+             INSERT INTO versions VALUES ('c1', 'c', '1.0.0', 'c one', NULL, '2025-01-01', NULL, NULL, NULL);"
+        ).unwrap();
+
+        let uuids = ["a", "b", "missing"].map(String::from);
+        let bodies = load_latest_bodies(&conn, &uuids).unwrap();
+        assert_eq!(bodies.len(), 2);
+        assert_eq!(bodies["a"], "a two");
+        assert_eq!(bodies["b"], "b one");
+
+        assert!(load_latest_bodies(&conn, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_concurrent_inserts_get_distinct_semvers() {
+        let db_path = std::env::temp_dir().join(format!("promptmaster-test-{}.db", Uuid::now_v7()));
+        let prompt_uuid = Uuid::now_v7().to_string();
+        let conn = open_test_db(&db_path);
+        create_test_schema(&conn);
+        insert_prompts(&conn, &[&prompt_uuid]);
+        drop(conn);
+
+        // Every writer saw 1.0.0 as the latest version and tries 1.0.1 first
+        let writers = 8;
+        let handles: Vec<_> = (0..writers)
+            .map(|i| {
+                let db_path = db_path.clone();
+                let prompt_uuid = prompt_uuid.clone();
+                std::thread::spawn(move || {
+                    let conn = open_test_db(&db_path);
+                    let mut version = Version {
+                        uuid: Uuid::now_v7().to_string(),
+                        prompt_uuid,
+                        semver: "1.0.1".to_string(),
+                        body: format!("body {}", i),
+                        metadata: None,
+                        created_at: Utc::now().to_rfc3339(),
+                        parent_uuid: None,
+                        author: None,
+                    };
+                    insert_version_with_retry(&conn, &mut version).unwrap();
+                    version.semver
+                })
+            })
+            .collect();
+
+        let mut semvers: Vec<String> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        semvers.sort_by_key(|semver| parse_semver(semver).unwrap());
+
+        let expected: Vec<String> = (1..=writers).map(|patch| format!("1.0.{}", patch)).collect();
+        assert_eq!(semvers, expected);
+
+        let _ = fs::remove_file(&db_path);
+    }
+
+    #[test]
+    fn test_sort_by_semver_desc() {
+        let mut versions = vec![
+            ("1.0.9", "2025-01-09"),
+            ("1.0.10", "2025-01-01"), // created earlier, e.g. clock skew
+            ("2.0.0", "2025-01-05"),
+            ("bogus", "2025-02-01"),
+            ("1.0.9", "2025-01-10"),
+        ];
+        sort_by_semver_desc(&mut versions, |v| *v);
+
+        assert_eq!(versions, vec![
+            ("2.0.0", "2025-01-05"),
+            ("1.0.10", "2025-01-01"),
+            ("1.0.9", "2025-01-10"),
+            ("1.0.9", "2025-01-09"),
+            ("bogus", "2025-02-01"),
+        ]);
+    }
+
+    #[test]
+    fn test_latest_version_first_matches_sort_by_semver_desc() {
+        let conn = test_connection();
+        insert_prompts(&conn, &["p"]);
+        let mut versions = vec![
+            ("1.0.9", "2025-01-09"), ("1.0.10", "2025-01-01"), ("2.0.0", "2025-01-05"), ("10.0.0", "2025-01-02"),
+            ("1.10.0", "2025-01-03"), ("v3.0.0", "2025-02-01"), ("1.0", "2025-02-02"), ("4.0.0-beta", "2025-02-03"),
+            ("1..2", "2025-02-04"), ("1.0.9", "2025-01-10"),
+        ];
+        // Each row gets its own uuid so duplicate semvers can share the prompt
+        conn.execute_batch("DROP INDEX idx_versions_unique_semver").unwrap();
+        for (semver, created_at) in &versions {
+            conn.execute(
+                "INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at) VALUES (?1, 'p', ?2, '', ?3)",
+                params![Uuid::now_v7().to_string(), semver, created_at],
+            ).unwrap();
+        }
+
+        let ordered: Vec<(String, String)> = conn
+            .prepare(&format!("SELECT semver, created_at FROM versions ORDER BY {}", LATEST_VERSION_FIRST)).unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap()
+            .collect::<rusqlite::Result<_>>().unwrap();
+        sort_by_semver_desc(&mut versions, |v| *v);
+
+        // Unparseable semvers only need to come after every valid one
+        let valid = versions.iter().filter(|(semver, _)| parse_semver(semver).is_ok()).count();
+        let ordered: Vec<(&str, &str)> = ordered.iter().map(|(s, c)| (s.as_str(), c.as_str())).collect();
+        assert_eq!(ordered[..valid], versions[..valid]);
+        assert!(ordered[valid..].iter().all(|(semver, _)| parse_semver(semver).is_err()));
+    }
+
+    #[test]
+    fn test_select_versions_to_prune() {
+        let versions: Vec<(String, String, String)> = ["1.0.0", "1.0.1", "1.0.2", "1.0.10", "1.0.3"]
+            .iter()
+            .map(|semver| (format!("uuid-{}", semver), semver.to_string(), "2025-01-01".to_string()))
+            .collect();
+        let protected: HashSet<String> = ["uuid-1.0.0".to_string()].into_iter().collect();
+
+        let mut pruned: Vec<String> = select_versions_to_prune(versions, 2, &protected)
+            .into_iter()
+            .map(|(_, semver)| semver)
+            .collect();
+        pruned.sort();
+
+        // 1.0.10 and 1.0.3 are kept as the newest; 1.0.0 is protected
+        assert_eq!(pruned, vec!["1.0.1".to_string(), "1.0.2".to_string()]);
+    }
+
+    #[test]
+    fn test_remove_version_files_only_touches_owned_files() {
+        let dir = std::env::temp_dir().join(format!("promptmaster-test-{}", Uuid::now_v7()));
+        fs::create_dir_all(&dir).unwrap();
+        let owner = Uuid::now_v7().to_string();
+        let other = Uuid::now_v7().to_string();
+
+        let write = |filename: &str, uuid: &str, semver: &str| {
+            let content = build_markdown(&Frontmatter {
+                uuid: uuid.to_string(),
+                version: semver.to_string(),
+                title: "Prompt".to_string(),
+                ..Frontmatter::default()
+            }, "body");
+            fs::write(dir.join(filename), content).unwrap();
+        };
+        write("2025-01-01--prompt--v1.0.1.md", &owner, "1.0.1");
+        write("2025-01-01--prompt--v1.0.2.md", &owner, "1.0.2");
+        write("2025-01-01--prompt--v1.0.1--abcdef12.md", &other, "1.0.1");
+
+        let semvers: HashSet<String> = ["1.0.1".to_string()].into_iter().collect();
+        assert_eq!(remove_version_files(&dir, &owner, &semvers), 1);
+
+        assert!(!dir.join("2025-01-01--prompt--v1.0.1.md").exists());
+        assert!(dir.join("2025-01-01--prompt--v1.0.2.md").exists());
+        assert!(dir.join("2025-01-01--prompt--v1.0.1--abcdef12.md").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_next_semver_skips_trashed_versions() {
+        let conn = test_connection();
+        insert_prompts(&conn, &["p", "empty"]);
+        conn.execute("INSERT INTO versions VALUES ('v0', 'p', '1.0.0', 'b', NULL, '2025-01-01', NULL, NULL, NULL)", []).unwrap();
+        // More trashed versions than insert_version_with_retry would walk past
+        for patch in 1..=MAX_SEMVER_RETRIES + 2 {
+            conn.execute(
+                "INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at, deleted_at) VALUES (?1, 'p', ?2, 'b', '2025-01-02', '2025-01-03')",
+                params![Uuid::now_v7().to_string(), format!("1.0.{}", patch)],
+            ).unwrap();
+        }
+
+        let semver = next_patch_semver(&conn, "p").unwrap().unwrap();
+        assert_eq!(semver, format!("1.0.{}", MAX_SEMVER_RETRIES + 3));
+        let mut version = Version {
+            uuid: Uuid::now_v7().to_string(),
+            prompt_uuid: "p".to_string(),
+            semver: semver.clone(),
+            body: "new".to_string(),
+            metadata: None,
+            created_at: "2025-01-04".to_string(),
+            parent_uuid: Some("v0".to_string()),
+            author: None,
+        };
+        insert_version_with_retry(&conn, &mut version).unwrap();
+        assert_eq!(version.semver, semver);
+
+        assert_eq!(next_patch_semver(&conn, "empty").unwrap(), None);
+    }
+
+    #[test]
+    fn test_insert_gives_up_after_max_retries() {
+        let conn = test_connection();
+        insert_prompts(&conn, &["p"]);
+
+        for patch in 0..MAX_SEMVER_RETRIES {
+            conn.execute(
+                "INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at) VALUES (?1, 'p', ?2, 'b', 'now')",
+                params![Uuid::now_v7().to_string(), format!("1.0.{}", patch)],
+            ).unwrap();
+        }
+
+        let mut version = Version {
+            uuid: Uuid::now_v7().to_string(),
+            prompt_uuid: "p".to_string(),
+            semver: "1.0.0".to_string(),
+            body: "b".to_string(),
+            metadata: None,
+            created_at: "now".to_string(),
+            parent_uuid: None,
+            author: None,
+        };
+        let err = insert_version_with_retry(&conn, &mut version).unwrap_err();
+        assert!(matches!(err, AppError::Conflict(ref msg) if msg.contains("collision")));
+    }
+
+    #[test]
+    fn test_version_tags_are_case_insensitive_and_sorted() {
+        let conn = test_connection();
+        insert_prompts(&conn, &["p"]);
+        conn.execute_batch(
+            "INSERT INTO versions (uuid, prompt_uuid, semver, body, created_at) VALUES
+                ('v1', 'p', '1.0.0', '', '2024-01-01'), ('v2', 'p', '1.0.1', '', '2024-01-02');"
+        ).unwrap();
+
+        for tag in ["shipped", "Benchmarked", "SHIPPED"] {
+            conn.execute(
+                "INSERT OR IGNORE INTO version_tags (version_uuid, tag, created_at) VALUES ('v1', ?1, '')",
+                [tag],
+            ).unwrap();
+        }
+        conn.execute("INSERT INTO version_tags VALUES ('v2', 'other', '')", []).unwrap();
+
+        assert_eq!(load_version_tags(&conn, "v1").unwrap(), vec!["Benchmarked", "shipped"]);
+        assert_eq!(conn.execute("DELETE FROM version_tags WHERE version_uuid = 'v1' AND tag = 'Shipped'", []).unwrap(), 1);
+        assert_eq!(load_version_tags(&conn, "v1").unwrap(), vec!["Benchmarked"]);
+    }
+
+    fn create_undo_tables() -> Connection {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO prompts (uuid, title, tags, created_at, updated_at)
+             VALUES ('p', 'Summarize', '[\"work\"]', '2025-01-01', '2025-01-01');
+             INSERT INTO versions VALUES ('v1', 'p', '1.0.0', 'one', NULL, '2025-01-01T00:00:00Z', NULL, NULL, NULL);"
+        ).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_delete_latest_version_restores_previous() {
+        let conn = create_undo_tables();
+        conn.execute_batch(
+            "INSERT INTO versions VALUES ('v2', 'p', '1.0.1', 'two', NULL, '2025-01-02T00:00:00Z', 'v1', NULL, NULL);
+             INSERT INTO version_tags VALUES ('v2', 'oops', '');"
+        ).unwrap();
+
+        let (removed, restored) = delete_latest_version(&conn, "p").unwrap();
+        assert_eq!(removed, "1.0.1");
+        assert_eq!(restored.uuid, "v1");
+        // Trashed, not deleted: the row and its tags survive for restore_version
+        let deleted_at: Option<String> = conn.query_row("SELECT deleted_at FROM versions WHERE uuid = 'v2'", [], |row| row.get(0)).unwrap();
+        assert!(deleted_at.is_some());
+        let tags: i64 = conn.query_row("SELECT COUNT(*) FROM version_tags", [], |row| row.get(0)).unwrap();
+        assert_eq!(tags, 1);
+
+        // Only one version left
+        let err = delete_latest_version(&conn, "p").unwrap_err();
+        assert!(matches!(err, AppError::Validation(ref msg) if msg.contains("only version")));
+    }
+
+    #[test]
+    fn test_delete_latest_version_guards() {
+        let conn = create_undo_tables();
+        conn.execute("INSERT INTO versions VALUES ('v2', 'p', '1.0.1', 'two', NULL, '2025-01-02T00:00:00Z', 'v1', NULL, NULL)", []).unwrap();
+
+        conn.execute("UPDATE prompts SET prod_version_uuid = 'v2'", []).unwrap();
+        let err = delete_latest_version(&conn, "p").unwrap_err();
+        assert!(matches!(err, AppError::Validation(ref msg) if msg.contains("prod version")));
+
+        conn.execute("UPDATE prompts SET prod_version_uuid = NULL", []).unwrap();
+        conn.execute("INSERT INTO runs (uuid, version_uuid) VALUES ('r1', 'v2')", []).unwrap();
+        let err = delete_latest_version(&conn, "p").unwrap_err();
+        assert!(matches!(err, AppError::Validation(ref msg) if msg.contains("runs")));
+
+        assert!(matches!(delete_latest_version(&conn, "missing").unwrap_err(), AppError::NotFound(_)));
+    }
+
+    #[test]
+    fn test_exported_markdown_keeps_body_with_fences() {
+        let body = "Intro\n---\nversion: not frontmatter\n---\nOutro";
+        let markdown = create_markdown_content("p", "Title", body, "1.2.3", &["a".to_string()], None, None);
+
+        let parsed = parse_frontmatter(&markdown).unwrap();
+        assert_eq!(parsed.body, body);
+        assert_eq!(parsed.version.as_deref(), Some("1.2.3"));
+    }
+
+    #[test]
+    fn test_purge_trashed_before() {
+        let conn = create_undo_tables();
+        conn.execute_batch(
+            "INSERT INTO versions VALUES ('old', 'p', '1.0.1', 'b', NULL, '', 'v1', '2025-01-01T00:00:00+00:00', NULL);
+             INSERT INTO versions VALUES ('recent', 'p', '1.0.2', 'c', NULL, '', 'v1', '2025-03-01T00:00:00+00:00', NULL);
+             INSERT INTO versions VALUES ('with-runs', 'p', '1.0.3', 'd', NULL, '', 'v1', '2025-01-01T00:00:00+00:00', NULL);
+             INSERT INTO runs (uuid, version_uuid) VALUES ('r1', 'with-runs');
+             INSERT INTO version_tags VALUES ('old', 'x', '');"
+        ).unwrap();
+
+        assert_eq!(purge_trashed_before(&conn, "2025-02-01T00:00:00+00:00").unwrap(), 1);
+
+        let remaining: Vec<String> = conn.prepare("SELECT uuid FROM versions ORDER BY uuid").unwrap()
+            .query_map([], |row| row.get(0)).unwrap()
+            .collect::<rusqlite::Result<_>>().unwrap();
+        assert_eq!(remaining, vec!["recent", "v1", "with-runs"]);
+        let tags: i64 = conn.query_row("SELECT COUNT(*) FROM version_tags", [], |row| row.get(0)).unwrap();
+        assert_eq!(tags, 0);
+    }
+
+    #[test]
+    fn test_promote_version_updates_prod_file_in_place() {
+        let conn = create_undo_tables();
+        conn.execute("INSERT INTO versions VALUES ('v2', 'p', '1.0.1', 'two', NULL, '2025-01-02T00:00:00Z', 'v1', NULL, NULL)", []).unwrap();
+        let dir = std::env::temp_dir().join(format!("promptmaster-test-{}", Uuid::now_v7()));
+
+        // Without a prod file yet, promoting only moves the pointer
+        let prod = set_prod_version(&conn, "v1").unwrap();
+        assert_eq!(write_prod_file(&dir, &prod, false).unwrap(), None);
+
+        let path = write_prod_file(&dir, &prod, true).unwrap().unwrap();
+        assert_eq!(path, dir.join("summarize--prod.md"));
+
+        let prod = set_prod_version(&conn, "v2").unwrap();
+        assert_eq!(write_prod_file(&dir, &prod, false).unwrap(), Some(path.clone()));
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("version: \"1.0.1\"") && content.ends_with("two"), "{}", content);
+        let pointer: String = conn.query_row("SELECT prod_version_uuid FROM prompts", [], |row| row.get(0)).unwrap();
+        assert_eq!(pointer, "v2");
+
+        conn.execute("UPDATE versions SET deleted_at = '2025-01-03' WHERE uuid = 'v1'", []).unwrap();
+        assert!(matches!(set_prod_version(&conn, "v1"), Err(AppError::NotFound(_))));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,29 +1,195 @@
 use notify::{RecommendedWatcher, RecursiveMode, Watcher, Config};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::channel;
-use crate::prompts::{update_prompt_from_file, recreate_prompt_file};
-use crate::error::{AppError, Result};
-use tauri::Manager;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::prompts::{update_prompt_from_file, recreate_prompt_file, FileSyncOutcome};
+use crate::attachments::ATTACHMENTS_DIR;
+use crate::error::Result;
+use crate::markdown::PROD_FILE_SUFFIX;
+use crate::paths::prompts_dir;
 use tauri::Emitter;
 
+/// How long after the app writes a file the watcher ignores events for it
+const APP_WRITE_WINDOW: Duration = Duration::from_secs(2);
+
+/// Set while the watcher thread is running
+static WATCHER_RUNNING: AtomicBool = AtomicBool::new(false);
+/// Set by pause_watcher; the watcher thread checks it before processing each event
+static WATCHER_PAUSED: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    /// Paths the app wrote itself, with when they were written
+    static ref RECENT_APP_WRITES: Mutex<HashMap<PathBuf, Instant>> = Mutex::new(HashMap::new());
+    /// Markdown files changed or deleted while the watcher was paused, processed on resume
+    static ref PAUSED_CHANGES: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct WatcherStatus {
+    pub running: bool,
+    pub paused: bool,
+    /// Files changed while paused, waiting to be processed on resume
+    pub pending_changes: usize,
+}
+
+/// Whether the watcher handles `path`: .md files outside the attachments directory that
+/// aren't hidden/temporary (".name"), backups ("name~") or generated prod files ("slug--prod.md")
+pub(crate) fn is_prompt_file(path: &Path, attachments_dir: &Path) -> bool {
+    let Some(name) = path.file_name().map(|name| name.to_string_lossy()) else {
+        return false;
+    };
+    path.extension().is_some_and(|ext| ext == "md")
+        && !path.starts_with(attachments_dir)
+        && !name.starts_with('.')
+        && !name.ends_with('~')
+        && !name.ends_with(PROD_FILE_SUFFIX)
+}
+
+/// Record that the app is writing `path`, so the watcher doesn't re-index the app's own write.
+/// Call this right before writing.
+pub fn record_app_write(path: &Path) {
+    if let Ok(mut writes) = RECENT_APP_WRITES.lock() {
+        writes.insert(path.to_path_buf(), Instant::now());
+    }
+}
+
+/// Whether `path` was written by the app within the window; expired entries are dropped
+fn is_recent_app_write(path: &Path) -> bool {
+    match RECENT_APP_WRITES.lock() {
+        Ok(mut writes) => check_recent_write(&mut writes, path, Instant::now()),
+        Err(_) => false,
+    }
+}
+
+fn check_recent_write(writes: &mut HashMap<PathBuf, Instant>, path: &Path, now: Instant) -> bool {
+    writes.retain(|_, written_at| now.duration_since(*written_at) < APP_WRITE_WINDOW);
+    writes.contains_key(path)
+}
+
+/// Re-index a changed markdown file and tell the UI how it went
+fn process_changed_file(app_handle: &tauri::AppHandle, path: &Path) {
+    match update_prompt_from_file(app_handle, path) {
+        Ok(FileSyncOutcome::Applied(updated)) => {
+            log::info!("Successfully updated prompt from file: {:?}", path);
+            // Lets the UI refresh exactly the re-indexed prompt
+            if let Err(e) = app_handle.emit("prompt-updated", updated) {
+                log::error!("Failed to emit prompt-updated event: {}", e);
+            }
+        }
+        Ok(FileSyncOutcome::Skipped) => {}
+        Ok(FileSyncOutcome::Conflict(conflict)) => {
+            // Let the user decide instead of clobbering newer in-app edits
+            if let Err(e) = app_handle.emit("file-conflict", conflict) {
+                log::error!("Failed to emit file-conflict event: {}", e);
+            }
+        }
+        Err(e) => {
+            log::warn!("Failed to re-index file {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Recreate a deleted markdown file from the database
+fn process_deleted_file(app_handle: &tauri::AppHandle, path: &Path) {
+    match recreate_prompt_file(app_handle, path) {
+        Ok(recreated) => {
+            if recreated {
+                log::info!("Successfully recreated prompt file: {:?}", path);
+            } else {
+                log::warn!("Deleted file not found in database: {:?}", path);
+            }
+        }
+        Err(e) => {
+            log::error!("Failed to recreate file {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Remember prompt files touched while paused, except the app's own writes
+fn queue_paused_changes(paths: &[PathBuf], attachments_dir: &Path) {
+    let paths: Vec<&PathBuf> = paths.iter()
+        .filter(|path| is_prompt_file(path, attachments_dir) && !is_recent_app_write(path))
+        .collect();
+    if paths.is_empty() {
+        return;
+    }
+    log::debug!("Watcher paused, queueing {} changed files", paths.len());
+    if let Ok(mut pending) = PAUSED_CHANGES.lock() {
+        pending.extend(paths.into_iter().cloned());
+    }
+}
+
+fn watcher_status() -> WatcherStatus {
+    WatcherStatus {
+        running: WATCHER_RUNNING.load(Ordering::SeqCst),
+        paused: WATCHER_PAUSED.load(Ordering::SeqCst),
+        pending_changes: PAUSED_CHANGES.lock().map(|pending| pending.len()).unwrap_or(0),
+    }
+}
+
+/// Stop processing file events, e.g. during bulk edits outside the app. Files changed while
+/// paused are remembered and processed once on resume_watcher.
+#[tauri::command]
+pub async fn pause_watcher() -> std::result::Result<WatcherStatus, String> {
+    log::info!("Pausing file watcher");
+    WATCHER_PAUSED.store(true, Ordering::SeqCst);
+    Ok(watcher_status())
+}
+
+/// Resume processing file events and catch up on files changed while paused: existing files
+/// are re-indexed, deleted ones recreated from the database
+#[tauri::command]
+pub async fn resume_watcher(app_handle: tauri::AppHandle) -> std::result::Result<WatcherStatus, String> {
+    // Take the queue before unpausing so each file is handled by exactly one side
+    let pending: BTreeSet<PathBuf> = PAUSED_CHANGES.lock()
+        .map(|mut pending| std::mem::take(&mut *pending))
+        .unwrap_or_default();
+    WATCHER_PAUSED.store(false, Ordering::SeqCst);
+    log::info!("Resuming file watcher, processing {} files changed while paused", pending.len());
+
+    for path in &pending {
+        if path.exists() {
+            process_changed_file(&app_handle, path);
+        } else {
+            process_deleted_file(&app_handle, path);
+        }
+    }
+
+    Ok(watcher_status())
+}
+
+/// Whether the watcher is running and paused, and how many changes await resume
+#[tauri::command]
+pub async fn get_watcher_status() -> std::result::Result<WatcherStatus, String> {
+    Ok(watcher_status())
+}
+
 pub fn start_file_watcher(app_handle: tauri::AppHandle) -> Result<()> {
     let (tx, rx) = channel();
     
     let mut watcher = RecommendedWatcher::new(tx, Config::default())?;
     
-    let prompts_dir = app_handle.path().document_dir()
-        .map_err(|e| AppError::Path(e.to_string()))?
-        .join("PromptMaster");
+    let prompts_dir = prompts_dir(&app_handle)?;
     
     // Ensure the directory exists before watching
     std::fs::create_dir_all(&prompts_dir)?;
     
     watcher.watch(&prompts_dir, RecursiveMode::Recursive)?;
     
+    // Attachments are opaque copies, never prompt files, even if they end in .md
+    let attachments_dir = prompts_dir.join(ATTACHMENTS_DIR);
+    
     // Store watcher to prevent it from being dropped
     let app_handle_clone = app_handle.clone();
     std::thread::spawn(move || {
         // Keep watcher alive in this thread
         let _watcher = watcher;
+        WATCHER_RUNNING.store(true, Ordering::SeqCst);
         
         for res in rx {
             match res {
@@ -39,19 +205,26 @@ pub fn start_file_watcher(app_handle: tauri::AppHandle) -> Result<()> {
 
                     let event_kind = format!("{:?}", event.kind);
                     
+                    // While paused, only remember which files changed so resume can catch up
+                    if WATCHER_PAUSED.load(Ordering::SeqCst) {
+                        if event_kind.contains("Write") || event_kind.contains("Create") || event_kind.contains("Remove") {
+                            queue_paused_changes(&event.paths, &attachments_dir);
+                        }
+                        continue;
+                    }
+                    
                     // Process write/create events for markdown files
                     if event_kind.contains("Write") || event_kind.contains("Create") {
                         // Filter to only process .md files and ignore database/temp files
                         let md_files: Vec<_> = event.paths.iter()
+                            .filter(|path| is_prompt_file(path, &attachments_dir))
+                            // Files the app just wrote itself are already in the database
                             .filter(|path| {
-                                // Only process .md files
-                                path.extension().map_or(false, |ext| ext == "md") &&
-                                // Ignore hidden/temporary files
-                                !path.file_name()
-                                    .map_or(true, |name| name.to_string_lossy().starts_with('.')) &&
-                                // Ignore backup files
-                                !path.file_name()
-                                    .map_or(true, |name| name.to_string_lossy().ends_with('~'))
+                                let own_write = is_recent_app_write(path);
+                                if own_write {
+                                    log::debug!("Ignoring app-initiated write: {:?}", path);
+                                }
+                                !own_write
                             })
                             .collect();
                         
@@ -76,14 +249,7 @@ pub fn start_file_watcher(app_handle: tauri::AppHandle) -> Result<()> {
                             std::thread::sleep(std::time::Duration::from_millis(100));
                             
                             for path in md_files {
-                                match update_prompt_from_file(&app_handle_clone, path) {
-                                    Ok(()) => {
-                                        log::info!("Successfully updated prompt from file: {:?}", path);
-                                    }
-                                    Err(e) => {
-                                        log::warn!("Failed to re-index file {:?}: {}", path, e);
-                                    }
-                                }
+                                process_changed_file(&app_handle_clone, path);
                             }
                         } else {
                             log::debug!("Ignoring non-markdown file changes: {:?}", event.paths);
@@ -92,16 +258,7 @@ pub fn start_file_watcher(app_handle: tauri::AppHandle) -> Result<()> {
                     // Handle delete events for markdown files
                     else if event_kind.contains("Remove") {
                         let md_files: Vec<_> = event.paths.iter()
-                            .filter(|path| {
-                                // Only process .md files
-                                path.extension().map_or(false, |ext| ext == "md") &&
-                                // Ignore hidden/temporary files
-                                !path.file_name()
-                                    .map_or(true, |name| name.to_string_lossy().starts_with('.')) &&
-                                // Ignore backup files
-                                !path.file_name()
-                                    .map_or(true, |name| name.to_string_lossy().ends_with('~'))
-                            })
+                            .filter(|path| is_prompt_file(path, &attachments_dir))
                             .collect();
                         
                         if !md_files.is_empty() {
@@ -123,18 +280,7 @@ pub fn start_file_watcher(app_handle: tauri::AppHandle) -> Result<()> {
                             
                             // Recreate deleted files from database
                             for path in md_files {
-                                match recreate_prompt_file(&app_handle_clone, path) {
-                                    Ok(recreated) => {
-                                        if recreated {
-                                            log::info!("Successfully recreated prompt file: {:?}", path);
-                                        } else {
-                                            log::warn!("Deleted file not found in database: {:?}", path);
-                                        }
-                                    }
-                                    Err(e) => {
-                                        log::error!("Failed to recreate file {:?}: {}", path, e);
-                                    }
-                                }
+                                process_deleted_file(&app_handle_clone, path);
                             }
                         } else {
                             log::debug!("Ignoring non-markdown file deletions: {:?}", event.paths);
@@ -147,8 +293,39 @@ pub fn start_file_watcher(app_handle: tauri::AppHandle) -> Result<()> {
             }
         }
         
+        WATCHER_RUNNING.store(false, Ordering::SeqCst);
         log::info!("File watcher thread shutting down");
     });
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_prompt_file() {
+        let attachments = Path::new("/prompts/attachments");
+        assert!(is_prompt_file(Path::new("/prompts/2025-01-01--a--v1.0.0.md"), attachments));
+        assert!(!is_prompt_file(Path::new("/prompts/attachments/p/notes.md"), attachments));
+        assert!(!is_prompt_file(Path::new("/prompts/.draft.md"), attachments));
+        assert!(!is_prompt_file(Path::new("/prompts/a.md~"), attachments));
+        assert!(!is_prompt_file(Path::new("/prompts/summarize--prod.md"), attachments));
+        assert!(!is_prompt_file(Path::new("/prompts/promptmaster.db"), attachments));
+    }
+
+    #[test]
+    fn test_check_recent_write_expires() {
+        let mut writes = HashMap::new();
+        let written_at = Instant::now();
+        let path = PathBuf::from("/prompts/2025-01-01--a--v1.0.0.md");
+        writes.insert(path.clone(), written_at);
+
+        assert!(check_recent_write(&mut writes, &path, written_at + Duration::from_millis(500)));
+        assert!(!check_recent_write(&mut writes, Path::new("/prompts/other.md"), written_at));
+
+        assert!(!check_recent_write(&mut writes, &path, written_at + APP_WRITE_WINDOW));
+        assert!(writes.is_empty());
+    }
+}
@@ -8,6 +8,35 @@ interface CategoryNode {
   count: number;
 }
 
+interface CategoryPathCount {
+  path: string;
+  prompt_count: number;
+}
+
+// get_all_category_paths lists every path including parents, sorted, so
+// each node's parent has already been created when the node is reached
+const buildCategoryTree = (paths: CategoryPathCount[]): CategoryNode[] => {
+  const roots: CategoryNode[] = [];
+  const nodes = new Map<string, CategoryNode>();
+
+  for (const { path, prompt_count } of paths) {
+    const separator = path.lastIndexOf("/");
+    const node: CategoryNode = {
+      path,
+      name: separator === -1 ? path : path.slice(separator + 1),
+      children: [],
+      count: prompt_count,
+    };
+    nodes.set(path, node);
+
+    const parent =
+      separator === -1 ? undefined : nodes.get(path.slice(0, separator));
+    (parent ? parent.children : roots).push(node);
+  }
+
+  return roots;
+};
+
 interface CategoryPickerProps {
   categoryPath: string;
   onChange: (path: string) => void;
@@ -25,110 +54,25 @@ export function CategoryPicker({
   const [expandedPaths, setExpandedPaths] = useState<Set<string>>(new Set());
   const dropdownRef = useRef<HTMLDivElement>(null);
 
-  // Mock category tree - in real implementation, this would come from backend
-  const categoryTree: CategoryNode[] = [
-    {
-      path: "Uncategorized",
-      name: "Uncategorized",
-      children: [],
-      count: 5,
-    },
-    {
-      path: "Marketing",
-      name: "Marketing",
-      children: [
-        {
-          path: "Marketing/Email",
-          name: "Email",
-          children: [
-            {
-              path: "Marketing/Email/Newsletters",
-              name: "Newsletters",
-              children: [],
-              count: 3,
-            },
-            {
-              path: "Marketing/Email/Campaigns",
-              name: "Campaigns",
-              children: [],
-              count: 7,
-            },
-          ],
-          count: 10,
-        },
-        {
-          path: "Marketing/Social Media",
-          name: "Social Media",
-          children: [
-            {
-              path: "Marketing/Social Media/Twitter",
-              name: "Twitter",
-              children: [],
-              count: 4,
-            },
-            {
-              path: "Marketing/Social Media/LinkedIn",
-              name: "LinkedIn",
-              children: [],
-              count: 2,
-            },
-          ],
-          count: 6,
-        },
-        {
-          path: "Marketing/SEO",
-          name: "SEO",
-          children: [],
-          count: 8,
-        },
-      ],
-      count: 24,
-    },
-    {
-      path: "Development",
-      name: "Development",
-      children: [
-        {
-          path: "Development/Code Review",
-          name: "Code Review",
-          children: [],
-          count: 5,
-        },
-        {
-          path: "Development/Documentation",
-          name: "Documentation",
-          children: [],
-          count: 12,
-        },
-        {
-          path: "Development/Debugging",
-          name: "Debugging",
-          children: [],
-          count: 3,
-        },
-      ],
-      count: 20,
-    },
-    {
-      path: "Content",
-      name: "Content",
-      children: [
-        {
-          path: "Content/Blog Posts",
-          name: "Blog Posts",
-          children: [],
-          count: 15,
-        },
-        {
-          path: "Content/Product Descriptions",
-          name: "Product Descriptions",
-          children: [],
-          count: 8,
-        },
-      ],
-      count: 23,
-    },
-  ];
+  const [categoryTree, setCategoryTree] = useState<CategoryNode[]>([]);
+
+  // Load category paths from backend
+  useEffect(() => {
+    const loadCategories = async () => {
+      try {
+        const { invoke } = await import("@tauri-apps/api/core");
+        const paths = await invoke<CategoryPathCount[]>(
+          "get_all_category_paths"
+        );
+        setCategoryTree(buildCategoryTree(paths));
+      } catch (error) {
+        console.error("Failed to load category paths:", error);
+        setCategoryTree([]);
+      }
+    };
+
+    loadCategories();
+  }, []);
 
   // Close dropdown when clicking outside
   useEffect(() => {
@@ -38,6 +38,8 @@ interface BackendVersion {
   metadata?: string;
   created_at: string;
   parent_uuid?: string;
+  /** Absolute path of the markdown file, returned by save_new_version */
+  file_path?: string | null;
 }
 
 interface Prompt {
@@ -52,6 +54,30 @@ interface Prompt {
 
 type ViewMode = "edit" | "preview" | "diff";
 
+interface DuplicateContentError {
+  code: "duplicate_content";
+  message: string;
+  existing_semver: string;
+  existing: {
+    uuid: string;
+    semver: string;
+    created_at: string;
+  };
+}
+
+// save_new_version reports identical content as a JSON error string
+const parseDuplicateContentError = (
+  error: unknown
+): DuplicateContentError | null => {
+  if (typeof error !== "string") return null;
+  try {
+    const parsed = JSON.parse(error);
+    return parsed?.code === "duplicate_content" ? parsed : null;
+  } catch {
+    return null;
+  }
+};
+
 // Helper function to calculate next patch version
 const getNextVersion = (currentVersion: string | undefined): string => {
   if (!currentVersion) {
@@ -111,6 +137,11 @@ export function EditorScreen() {
 
         setPrompt(currentPrompt);
 
+        // Record the open for "recently opened" lists; failures are non-fatal
+        invoke("touch_prompt", { uuid: promptId }).catch((error) =>
+          console.warn("Failed to record prompt access:", error)
+        );
+
         // Load latest version content and info
         try {
           const [latestVersionBody, versionList] = await Promise.all([
@@ -197,10 +228,29 @@ export function EditorScreen() {
     try {
       setSaving(true);
 
-      const newVersion = await invoke<BackendVersion>("save_new_version", {
-        promptUuid: prompt.uuid,
-        body: editorContent,
-      });
+      const saveVersion = (allowDuplicate: boolean) =>
+        invoke<BackendVersion>("save_new_version", {
+          promptUuid: prompt.uuid,
+          body: editorContent,
+          allowDuplicate,
+          ignoreTrailingWhitespace: true,
+        });
+
+      let newVersion: BackendVersion;
+      try {
+        newVersion = await saveVersion(false);
+      } catch (error) {
+        const duplicate = parseDuplicateContentError(error);
+        if (!duplicate) throw error;
+        if (
+          !window.confirm(
+            `You already have this as v${duplicate.existing_semver} — save anyway?`
+          )
+        ) {
+          return;
+        }
+        newVersion = await saveVersion(true);
+      }
 
       setCurrentVersionUuid(newVersion.uuid);
 